@@ -8,6 +8,17 @@ pub const EP_OUT: u8 = 0x02;
 
 pub const INITIALIZE: [u8; 1] = [0x13];
 
+/// Builds the command that turns the rumble motor of each port on or off
+pub fn rumble(ports: [bool; 4]) -> [u8; 5] {
+    [
+        0x11,
+        ports[0] as u8,
+        ports[1] as u8,
+        ports[2] as u8,
+        ports[3] as u8,
+    ]
+}
+
 const STATE_NORMAL: u8 = 0x10;
 const STATE_WAVEBIRD: u8 = 0x20;
 
@@ -9,6 +9,9 @@ pub const VENDOR_ID: u16 = 0x057E;
 pub const PRODUCT_ID_JOYCON_L: u16 = 0x2006;
 pub const PRODUCT_ID_JOYCON_R: u16 = 0x2007;
 
+/// Rumble data for one actuator that keeps it still
+pub const RUMBLE_NEUTRAL: [u8; 4] = [0x00, 0x01, 0x40, 0x40];
+
 buttons! {
     Buttons, Button: u32 =>
     Y       = 0,
@@ -35,6 +38,27 @@ buttons! {
     ZL      = 21,
 }
 
+/// Battery and power state from byte 2 of standard input reports
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Battery {
+    /// From 0 (empty) to 4 (full)
+    pub level: u8,
+    pub charging: bool,
+}
+
+impl Battery {
+    pub fn parse(data: u8) -> Self {
+        Battery {
+            level: (data >> 5).min(4),
+            charging: data & 0x10 != 0,
+        }
+    }
+
+    pub fn percent(&self) -> u8 {
+        self.level * 25
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Controller {
     pub buttons: Buttons,
@@ -60,3 +84,45 @@ impl Controller {
         }
     }
 }
+
+/// Encodes HD rumble data for one actuator.
+///
+/// Frequencies are in Hz and are clamped to the range the actuator supports.
+/// Amplitudes are in the range `0.0..=1.0`.
+pub fn encode_rumble(high_freq: f32, high_amp: f32, low_freq: f32, low_amp: f32) -> [u8; 4] {
+    fn encode_freq(freq: f32) -> u8 {
+        ((freq / 10.0).log2() * 32.0).round() as u8
+    }
+
+    fn encode_amp(amp: f32) -> u8 {
+        let amp = amp.clamp(0.0, 1.0);
+        if amp == 0.0 {
+            return 0;
+        }
+
+        let enc = (amp * 1000.0).log2() * 32.0 - 96.0;
+        let enc = if amp < 0.117 {
+            enc / (5.0 - amp * amp) - 1.0
+        } else if amp < 0.23 {
+            enc - 92.0
+        } else {
+            enc * 2.0 - 246.0
+        };
+
+        enc as u8
+    }
+
+    let hf = (encode_freq(high_freq.clamp(81.0, 1252.0)) as u16 - 0x60) * 4;
+    let lf = encode_freq(low_freq.clamp(41.0, 626.0)) - 0x40;
+
+    let hf_amp = encode_amp(high_amp);
+    let lf_amp = encode_amp(low_amp) / 2;
+    let lf_amp = (((lf_amp as u16) >> 1) + 0x40) | (((lf_amp as u16) & 1) << 15);
+
+    [
+        hf as u8,
+        ((hf >> 8) as u8).wrapping_add(hf_amp),
+        lf.wrapping_add((lf_amp >> 8) as u8),
+        lf_amp as u8,
+    ]
+}
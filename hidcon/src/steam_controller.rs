@@ -11,6 +11,9 @@ pub const PRODUCT_ID_WIRED: u16 = 0x1042;
 
 pub const EP_IN: u8 = 0x82;
 
+const REPORT_INPUT: u8 = 0x01;
+const REPORT_STATUS: u8 = 0x04;
+
 pub const ENABLE_MOTION: [u8; 64] = [
     0x87, 0x15, 0x32, 0x84, 0x03, 0x18, 0x00, 0x00, 0x31, 0x02, 0x00, 0x08, 0x07, 0x00, 0x07, 0x07,
     0x00, 0x30, 0x18, 0x00, 0x2f, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -25,6 +28,26 @@ pub const DISABLE_LIZARD_MODE: [u8; 64] = [
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
 ];
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HapticSide {
+    Right = 0,
+    Left = 1,
+}
+
+/// Builds a feature report that pulses the haptic actuator under one of the pads.
+///
+/// Times are in microseconds. A `repeat` of 0 stops the actuator.
+pub fn haptic_pulse(side: HapticSide, on_time: u16, off_time: u16, repeat: u16) -> [u8; 64] {
+    let mut report = [0; 64];
+    report[0] = 0x8F;
+    report[1] = 0x07;
+    report[2] = side as u8;
+    report[3..5].copy_from_slice(&on_time.to_le_bytes());
+    report[5..7].copy_from_slice(&off_time.to_le_bytes());
+    report[7..9].copy_from_slice(&repeat.to_le_bytes());
+    report
+}
+
 buttons! {
     Buttons, Button: u32 =>
     RPadTouch = 28,
@@ -48,6 +71,8 @@ buttons! {
 
 #[derive(Clone, Debug, Default)]
 pub struct Controller {
+    /// Incremented by the controller for every report it sends
+    pub sequence: u32,
     pub buttons: Buttons,
     pub left_trigger: u8,
     pub right_trigger: u8,
@@ -55,10 +80,23 @@ pub struct Controller {
     pub right_pad: Stick<i16>,
     pub acceleration: Acceleration<i16>,
     pub gyroscope: Gyroscope<i16>,
+    /// Battery charge in percent, from the last status report
+    pub battery: Option<u8>,
 }
 
 impl Controller {
     pub fn update(&mut self, packet: &[u8; 64]) -> Result<(), Infallible> {
+        self.sequence = u32::from_le_bytes(packet[4..8].try_into().unwrap());
+
+        match packet[2] {
+            REPORT_INPUT => {}
+            REPORT_STATUS => {
+                self.battery = Some(packet[14].min(100));
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+
         self.buttons.0 = u32::from_le_bytes(packet[7..11].try_into().unwrap());
 
         self.left_trigger = packet[11];
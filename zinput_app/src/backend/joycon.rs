@@ -1,19 +1,30 @@
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use anyhow::{bail, Context, Result};
 use hidapi::{HidApi, HidDevice};
+use hidcon::joycon::{encode_rumble, Battery, RUMBLE_NEUTRAL};
 use parking_lot::Mutex;
 use zinput_engine::{
-    device::component::{
-        controller::{Button, Controller, ControllerInfo},
-        motion::{Motion, MotionInfo},
+    device::{
+        component::{
+            controller::{Button, Controller, ControllerInfo},
+            motion::{Motion, MotionInfo},
+            motor::{Motor, MotorInfo, MotorKind},
+            player_leds::{PlayerLeds, PlayerLedsInfo},
+            status::{Connection, Status, StatusInfo},
+        },
+        feedback::{Feedback, HdRumble},
+        DeviceInfo,
     },
     plugin::{Plugin, PluginKind, PluginStatus},
     Engine,
 };
 
+use crate::backend::TickClock;
+
 const VENDOR_ID: u16 = 0x057E;
 const PID_JOYCON_L: u16 = 0x2006;
 const PID_JOYCON_R: u16 = 0x2007;
@@ -27,6 +38,9 @@ const PID_JOYCON_PRO: u16 = 0x2009;
 
 const T: &'static str = "backend:joycon";
 
+/// Time represented by one count of the timer in input reports
+const TIMER_TICK: Duration = Duration::from_millis(5);
+
 const STANDARD_FULL_MODE: [u8; 12] = [
     0x01, // Report: Rumble and Subcommand
     0x00, // Packet Number
@@ -38,7 +52,14 @@ const ENABLE_IMU: [u8; 12] = [
     0x01, // Report: Rumble and Subcommand
     0x00, // Packet Number
     0x00, 0x01, 0x40, 0x40, 0x00, 0x01, 0x40, 0x40, // Rumble: Neutral
-    0x40, 0x01, // Subcommand: Standard Full Mode
+    0x40, 0x01, // Subcommand: Enable IMU
+];
+
+const ENABLE_VIBRATION: [u8; 12] = [
+    0x01, // Report: Rumble and Subcommand
+    0x00, // Packet Number
+    0x00, 0x01, 0x40, 0x40, 0x00, 0x01, 0x40, 0x40, // Rumble: Neutral
+    0x48, 0x01, // Subcommand: Enable Vibration
 ];
 
 pub struct Joycon {
@@ -191,6 +212,10 @@ fn controller_thread(
         .write(&ENABLE_IMU)
         .context("failed to enable motion data")?;
 
+    joycon
+        .write(&ENABLE_VIBRATION)
+        .context("failed to enable vibration")?;
+
     joycon
         .write(&STANDARD_FULL_MODE)
         .context("failed to set controller to standard full mode")?;
@@ -211,6 +236,7 @@ fn controller_thread(
         }
 
         bundle.update(&buf)?;
+        bundle.update_feedback(&joycon)?;
     }
 
     Ok(())
@@ -359,12 +385,25 @@ fn read_calibration(dev: &HidDevice) -> Result<Calibration> {
     })
 }
 
-crate::device_bundle!(DeviceBundle, controller: Controller, motion: Motion);
+crate::device_bundle!(DeviceBundle,
+    controller: Controller,
+    motion: Motion,
+    status(statuses): Status,
+    motor: Motor,
+    player_led: PlayerLeds,
+);
 
 struct JoyconBundle<'a> {
     bundle: DeviceBundle<'a>,
     calibration: Calibration,
     joy_type: JoyconType,
+
+    packet_number: u8,
+    rumble_until: Option<Instant>,
+    /// Sent with subcommands so they do not interrupt rumble
+    rumble_data: [u8; 4],
+
+    clock: TickClock,
 }
 
 impl<'a> JoyconBundle<'a> {
@@ -374,27 +413,107 @@ impl<'a> JoyconBundle<'a> {
         calibration: Calibration,
         api: &'a Engine,
     ) -> Result<Self> {
-        let bundle = DeviceBundle::new(
+        let bundle = DeviceBundle::from_info(
             api,
-            format!("{} (id {})", joy_type, id + 1),
             // TODO: ID
-            None,
-            true,
+            DeviceInfo::new(format!("{} (id {})", joy_type, id + 1))
+                .with_kind("joycon".to_owned())
+                .autoload_config(true),
             [match joy_type {
                 JoyconType::Left => joycon_l_info(),
                 JoyconType::Right => joycon_r_info(),
                 JoyconType::Pro => joycon_pro_info(),
             }],
             [MotionInfo::new(true, true)],
+            [StatusInfo::new(true, false)],
+            [MotorInfo::new(MotorKind::Linear)],
+            [PlayerLedsInfo::new(4)],
         )?;
 
         Ok(JoyconBundle {
             bundle,
             calibration,
             joy_type,
+
+            packet_number: 0,
+            rumble_until: None,
+            rumble_data: RUMBLE_NEUTRAL,
+
+            clock: TickClock::new(8, TIMER_TICK),
         })
     }
 
+    fn update_feedback(&mut self, joycon: &HidDevice) -> Result<()> {
+        let rumble = match self.bundle.handle.take_feedback() {
+            Some(Feedback::PlayerLeds(leds)) => return self.send_player_leds(joycon, leds),
+            Some(feedback) => {
+                let Some(rumble) = feedback.hd_rumble()
+                else { return Ok(()); };
+
+                self.rumble_until = rumble.duration.map(|duration| Instant::now() + duration);
+                rumble
+            }
+            None => match self.rumble_until {
+                Some(until) if Instant::now() >= until => {
+                    self.rumble_until = None;
+                    HdRumble::OFF
+                }
+                _ => return Ok(()),
+            },
+        };
+
+        let data = if rumble.is_off() {
+            RUMBLE_NEUTRAL
+        } else {
+            encode_rumble(
+                rumble.high_freq,
+                rumble.high_amp,
+                rumble.low_freq,
+                rumble.low_amp,
+            )
+        };
+
+        let mut report = [0u8; 10];
+        report[0] = 0x10; // Report: Rumble only
+        report[1] = self.packet_number;
+        report[2..6].copy_from_slice(&data);
+        report[6..10].copy_from_slice(&data);
+
+        self.packet_number = (self.packet_number + 1) & 0xF;
+
+        joycon.write(&report).context("failed to send rumble")?;
+
+        self.rumble_data = data;
+        self.bundle.motor[0].amplitude =
+            (rumble.high_amp.max(rumble.low_amp).clamp(0.0, 1.0) * 255.0) as u8;
+
+        Ok(())
+    }
+
+    fn send_player_leds(&mut self, joycon: &HidDevice, leds: PlayerLeds) -> Result<()> {
+        let leds = PlayerLeds {
+            leds: leds.leds & 0x0F,
+        };
+
+        let mut report = [0u8; 12];
+        report[0] = 0x01; // Report: Rumble and Subcommand
+        report[1] = self.packet_number;
+        report[2..6].copy_from_slice(&self.rumble_data);
+        report[6..10].copy_from_slice(&self.rumble_data);
+        report[10] = 0x30; // Subcommand: Set Player Lights
+        report[11] = leds.leds;
+
+        self.packet_number = (self.packet_number + 1) & 0xF;
+
+        joycon
+            .write(&report)
+            .context("failed to set player lights")?;
+
+        self.bundle.player_led[0] = leds;
+
+        Ok(())
+    }
+
     fn update(&mut self, data: &[u8; 49]) -> Result<()> {
         let buttons = [data[3], data[4], data[5]];
         let left_stick = Self::parse_stick([data[6], data[7], data[8]]);
@@ -414,8 +533,10 @@ impl<'a> JoyconBundle<'a> {
         }
 
         self.update_motion(motions);
+        self.update_status(Battery::parse(data[2]));
 
-        self.bundle.update();
+        let time = self.clock.update(data[1] as u64);
+        self.bundle.update_with_time(time);
 
         Ok(())
     }
@@ -459,8 +580,8 @@ impl<'a> JoyconBundle<'a> {
         let lstick = self.calibration.lstick.apply(lstick);
         let rstick = self.calibration.rstick.apply(rstick);
 
-        let lstick = lstick.map(|v| (v * 255.0) as u8);
-        let rstick = rstick.map(|v| (v * 255.0) as u8);
+        let lstick = lstick.map(|v| (v * 65535.0) as u16);
+        let rstick = rstick.map(|v| (v * 65535.0) as u16);
 
         self.bundle.controller[0].buttons = new_buttons;
         self.bundle.controller[0].left_stick_x = lstick[0];
@@ -488,6 +609,14 @@ impl<'a> JoyconBundle<'a> {
         }
     }
 
+    fn update_status(&mut self, battery: Battery) {
+        let status = &mut self.bundle.status[0];
+        status.battery = battery.percent();
+        status.charging = battery.charging;
+        // only bluetooth connections are implemented
+        status.set_connection(Connection::Bluetooth);
+    }
+
     fn parse_stick(data: [u8; 3]) -> [u16; 2] {
         [
             data[0] as u16 | ((data[1] as u16 & 0xF) << 8),
@@ -1,26 +1,60 @@
+use std::time::Duration;
+
 pub mod joycon;
 #[cfg(target_os = "windows")]
 pub mod raw_input;
+pub mod replay;
 pub mod swi_recv;
 pub mod usb_devices;
 #[cfg(target_os = "windows")]
 pub mod xinput;
 
+/// Extends a device's wrapping tick counter into a time that keeps increasing
+pub struct TickClock {
+    tick: Duration,
+    mask: u64,
+    last: Option<u64>,
+    ticks: u64,
+}
+
+impl TickClock {
+    /// `bits` is the width of the device's counter and `tick` is how much time one count represents
+    pub fn new(bits: u32, tick: Duration) -> Self {
+        TickClock {
+            tick,
+            mask: u64::MAX >> (64 - bits),
+            last: None,
+            ticks: 0,
+        }
+    }
+
+    pub fn update(&mut self, counter: u64) -> Duration {
+        let counter = counter & self.mask;
+
+        if let Some(last) = self.last {
+            self.ticks += counter.wrapping_sub(last) & self.mask;
+        }
+        self.last = Some(counter);
+
+        Duration::from_nanos((self.tick.as_nanos() as u64).saturating_mul(self.ticks))
+    }
+}
+
 #[macro_export]
 macro_rules! device_bundle {
-    ($name:ident, $($cname:ident : $ctype:ty $( [ $clen:expr ] )?),* $(,)?) => {
+    ($name:ident, $($cname:ident $( ( $plural:ident ) )? : $ctype:ty $( [ $clen:expr ] )?),* $(,)?) => {
         type EngineRef<'a> = &'a zinput_engine::Engine;
 
-        crate::device_bundle!($name(EngineRef), $($cname : $ctype $( [ $clen ] )?),*);
+        crate::device_bundle!($name(EngineRef), $($cname $( ( $plural ) )? : $ctype $( [ $clen ] )?),*);
     };
 
-    ($name:ident (owned), $($cname:ident : $ctype:ty $( [ $clen:expr ] )?),* $(,)?) => {
+    ($name:ident (owned), $($cname:ident $( ( $plural:ident ) )? : $ctype:ty $( [ $clen:expr ] )?),* $(,)?) => {
         type EngineArc<'a> = Arc<zinput_engine::Engine>;
 
-        crate::device_bundle!($name(EngineArc), $($cname : $ctype $( [ $clen ] )?),*);
+        crate::device_bundle!($name(EngineArc), $($cname $( ( $plural ) )? : $ctype $( [ $clen ] )?),*);
     };
 
-    ($name:ident ( $($engine_type:tt)+ ), $($cname:ident : $ctype:ty $( [ $clen:expr ] )?),* $(,)?) => {
+    ($name:ident ( $($engine_type:tt)+ ), $($cname:ident $( ( $plural:ident ) )? : $ctype:ty $( [ $clen:expr ] )?),* $(,)?) => {
         use paste::paste;
 
         struct $name<'a> {
@@ -31,6 +65,7 @@ macro_rules! device_bundle {
 
         paste! {
             impl<'a> $name<'a> {
+                #[allow(dead_code)]
                 fn new(
                     engine: $($engine_type<'a>)+,
                     name: String,
@@ -42,7 +77,15 @@ macro_rules! device_bundle {
                     device_info.id = id;
                     device_info.autoload_config = autoload_config;
 
-                    $(let $cname = crate::device_bundle!(init(engine, $cname, device_info) $cname : $ctype $( [ $clen ] )?);)*
+                    Self::from_info(engine, device_info, $($cname,)*)
+                }
+
+                fn from_info(
+                    engine: $($engine_type<'a>)+,
+                    mut device_info: zinput_engine::device::DeviceInfo,
+                    $($cname: crate::device_bundle!(info $cname : $ctype $( [ $clen ] )? ),)*
+                ) -> std::result::Result<Self, zinput_engine::DeviceAlreadyExists> {
+                    $(let $cname = crate::device_bundle!(init(engine, $cname, device_info) $cname $( ( $plural ) )? : $ctype $( [ $clen ] )?);)*
 
                     let handle = engine.new_device(device_info)?;
 
@@ -53,11 +96,21 @@ macro_rules! device_bundle {
                     })
                 }
 
+                #[allow(dead_code)]
                 fn update(&self) {
                     use zinput_engine::device::component::ComponentData;
 
                     self.handle.update(|dev| {
-                        $(crate::device_bundle!(update(self, dev) $cname : $ctype $( [ $clen ] )?);)*
+                        $(crate::device_bundle!(update(self, dev) $cname $( ( $plural ) )? : $ctype $( [ $clen ] )?);)*
+                    });
+                }
+
+                #[allow(dead_code)]
+                fn update_with_time(&self, hardware_time: std::time::Duration) {
+                    use zinput_engine::device::component::ComponentData;
+
+                    self.handle.update_with_time(hardware_time, |dev| {
+                        $(crate::device_bundle!(update(self, dev) $cname $( ( $plural ) )? : $ctype $( [ $clen ] )?);)*
                     });
                 }
             }
@@ -76,30 +129,40 @@ macro_rules! device_bundle {
         crate::device_bundle!(info $cname : $ctype [ 1 ])
     };
 
+    // a device can have fewer of a component than the bundle has room for
     (info $cname:ident : $ctype:ty [ $clen:expr ]) => {
-        [<$ctype as zinput_engine::device::component::ComponentData>::Info; $clen]
+        impl Into<Vec<<$ctype as zinput_engine::device::component::ComponentData>::Info>>
     };
 
-    (init ( $engine:expr, $info:expr, $dinfo:ident ) $cname:ident : $ctype:ty) => {
-        crate::device_bundle!(init($engine, $info, $dinfo) $cname : $ctype [ 1 ])
+    // components are named by their plural on the device, which is the name with an `s` unless given
+    (init ( $engine:expr, $info:expr, $dinfo:ident ) $cname:ident : $ctype:ty $( [ $clen:expr ] )?) => {
+        paste! {
+            crate::device_bundle!(init($engine, $info, $dinfo) $cname ([< $cname s >]) : $ctype $( [ $clen ] )?)
+        }
     };
 
-    (init ( $engine:expr, $info:expr, $dinfo:ident ) $cname:ident : $ctype:ty [ $clen:expr ]) => {{
+    (init ( $engine:expr, $info:expr, $dinfo:ident ) $cname:ident ( $plural:ident ) : $ctype:ty) => {
+        crate::device_bundle!(init($engine, $info, $dinfo) $cname ($plural) : $ctype [ 1 ])
+    };
+
+    (init ( $engine:expr, $info:expr, $dinfo:ident ) $cname:ident ( $plural:ident ) : $ctype:ty [ $clen:expr ]) => {{
+        $dinfo.$plural = $info.into();
+        [(); $clen].map(|_| <$ctype>::default())
+    }};
+
+    (update ( $this:expr, $dev:ident ) $cname:ident : $ctype:ty $( [ $clen:expr ] )?) => {
         paste! {
-            $dinfo.[< $cname s >] = $info.into();
-            [(); $clen].map(|_| $ctype::default())
+            crate::device_bundle!(update($this, $dev) $cname ([< $cname s >]) : $ctype $( [ $clen ] )?)
         }
-    }};
+    };
 
-    (update ( $this:expr, $dev:ident ) $cname:ident : $ctype:ty) => {
-        crate::device_bundle!(update($this, $dev) $cname : $ctype [ 1 ])
+    (update ( $this:expr, $dev:ident ) $cname:ident ( $plural:ident ) : $ctype:ty) => {
+        crate::device_bundle!(update($this, $dev) $cname ($plural) : $ctype [ 1 ])
     };
 
-    (update ( $this:expr, $dev:ident ) $cname:ident : $ctype:ty [ $clen:expr ]) => {
-        paste! {
-            for i in 0..$clen {
-                $dev.[< $cname s >][i].update(&$this.$cname[i]);
-            }
+    (update ( $this:expr, $dev:ident ) $cname:ident ( $plural:ident ) : $ctype:ty [ $clen:expr ]) => {
+        for (to, from) in $dev.$plural.iter_mut().zip(&$this.$cname) {
+            to.update(from);
         }
     };
 }
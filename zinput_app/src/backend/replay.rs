@@ -0,0 +1,272 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use zinput_engine::{
+    eframe::{self, egui},
+    plugin::{Plugin, PluginKind, PluginStatus},
+    recording::{self, Recording},
+    Engine,
+};
+
+const T: &'static str = "backend:replay";
+
+/// Longest time the replay thread sleeps before checking whether it should stop
+const STOP_POLL: Duration = Duration::from_millis(100);
+
+/// Prefixed to the recorded device's id, so the replay never takes over the real device's
+/// uuid, config or slot, and can run while the real device is connected
+const ID_PREFIX: &'static str = "replay/";
+
+pub struct Replay {
+    inner: Mutex<Inner>,
+}
+
+impl Replay {
+    /// `dir` is the directory recordings are read from, see [`recording::recording_dir`]
+    pub fn new(dir: PathBuf) -> Self {
+        Replay {
+            inner: Mutex::new(Inner::new(dir)),
+        }
+    }
+}
+
+impl Plugin for Replay {
+    fn init(&self, zinput_api: Arc<Engine>) {
+        self.inner.lock().init(zinput_api)
+    }
+
+    fn stop(&self) {
+        self.inner.lock().stop()
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.inner.lock().status()
+    }
+
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    fn kind(&self) -> PluginKind {
+        PluginKind::Backend
+    }
+
+    fn update_gui(&self, _ctx: &egui::Context, _frame: &mut eframe::Frame, ui: &mut egui::Ui) {
+        let mut inner = self.inner.lock();
+        let gui = inner.gui();
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Recording")
+                .selected_text(gui.recording.clone().unwrap_or_default())
+                .show_ui(ui, |ui| {
+                    for name in &gui.recordings {
+                        ui.selectable_value(&mut gui.recording, Some(name.clone()), name);
+                    }
+                });
+
+            if ui.button("Refresh").clicked() {
+                match recording::saved_recordings(&gui.dir) {
+                    Ok(recordings) => gui.recordings = recordings,
+                    Err(err) => log::warn!(target: T, "failed to get recording list: {err:?}"),
+                }
+            }
+        });
+
+        ui.add(egui::Slider::new(&mut gui.speed, 0.1..=4.0).text("Speed"));
+        ui.checkbox(&mut gui.looping, "Loop");
+        ui.checkbox(&mut gui.recorded_config, "Use recorded config");
+    }
+}
+
+#[derive(Clone)]
+struct Gui {
+    dir: PathBuf,
+    recordings: Vec<String>,
+    recording: Option<String>,
+    speed: f64,
+    looping: bool,
+    recorded_config: bool,
+}
+
+impl Gui {
+    fn new(dir: PathBuf) -> Self {
+        Gui {
+            recordings: recording::saved_recordings(&dir).unwrap_or_default(),
+            dir,
+            recording: None,
+            speed: 1.0,
+            looping: false,
+            recorded_config: true,
+        }
+    }
+}
+
+enum Inner {
+    Uninit {
+        gui: Gui,
+    },
+    Init {
+        handle: JoinHandle<()>,
+        stop: Arc<AtomicBool>,
+        status: Arc<Mutex<PluginStatus>>,
+        gui: Gui,
+    },
+}
+
+impl Inner {
+    fn new(dir: PathBuf) -> Self {
+        Inner::Uninit { gui: Gui::new(dir) }
+    }
+
+    fn gui(&mut self) -> &mut Gui {
+        match self {
+            Inner::Uninit { gui } => gui,
+            Inner::Init { gui, .. } => gui,
+        }
+    }
+
+    fn init(&mut self, api: Arc<Engine>) {
+        if matches!(self, Inner::Init { .. }) {
+            self.stop();
+        }
+        let gui = self.gui().clone();
+
+        let status = Arc::new(Mutex::new(PluginStatus::Running));
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = std::thread::spawn(replay_thread(
+            gui.clone(),
+            status.clone(),
+            stop.clone(),
+            api,
+        ));
+
+        *self = Inner::Init {
+            handle,
+            stop,
+            status,
+            gui,
+        };
+    }
+
+    fn stop(&mut self) {
+        let gui = self.gui().clone();
+
+        match std::mem::replace(self, Inner::Uninit { gui }) {
+            Inner::Uninit { .. } => {}
+            Inner::Init {
+                handle,
+                stop,
+                status,
+                ..
+            } => {
+                stop.store(true, Ordering::SeqCst);
+
+                match handle.join() {
+                    Ok(()) => (),
+                    Err(_) => log::info!(target: T, "driver panicked"),
+                }
+
+                *status.lock() = PluginStatus::Stopped;
+            }
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        match self {
+            Inner::Uninit { .. } => PluginStatus::Stopped,
+            Inner::Init { status, .. } => status.lock().clone(),
+        }
+    }
+}
+
+impl Drop for Replay {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn replay_thread(
+    gui: Gui,
+    status: Arc<Mutex<PluginStatus>>,
+    stop: Arc<AtomicBool>,
+    api: Arc<Engine>,
+) -> impl FnOnce() {
+    move || {
+        log::info!(target: T, "driver initialized");
+
+        match replay(gui, stop, api) {
+            Ok(()) => {
+                log::info!(target: T, "driver stopped");
+                *status.lock() = PluginStatus::Stopped;
+            }
+            Err(err) => {
+                log::error!(target: T, "driver crashed: {:#}", err);
+                *status.lock() = PluginStatus::Error(format!("driver crashed: {:#}", err));
+            }
+        }
+    }
+}
+
+fn replay(gui: Gui, stop: Arc<AtomicBool>, api: Arc<Engine>) -> Result<()> {
+    let name = gui.recording.context("no recording selected")?;
+    let path = recording::recording_path(&gui.dir, &name);
+
+    let recording = Recording::open(&path)?;
+    let header = recording.header().clone();
+
+    let mut info = header.info;
+    info.id = info.id.map(|id| format!("{ID_PREFIX}{id}"));
+
+    let handle = api.new_device(info).context("failed to create device")?;
+
+    if gui.recorded_config {
+        if let Some(view) = api.get_device(handle.uuid()) {
+            view.set_config(header.config);
+        }
+    }
+
+    let mut recording = recording;
+
+    loop {
+        let started = Instant::now();
+
+        for frame in recording {
+            let frame = frame?;
+
+            let due = started + frame.time().div_f64(gui.speed);
+
+            loop {
+                if stop.load(Ordering::Acquire) {
+                    return Ok(());
+                }
+
+                let now = Instant::now();
+                if now >= due {
+                    break;
+                }
+
+                std::thread::sleep(Duration::min(due - now, STOP_POLL));
+            }
+
+            match frame.hardware_time() {
+                Some(time) => handle.update_with_time(time, |mut dev| dev.update_from(&frame.raw)),
+                None => handle.update(|mut dev| dev.update_from(&frame.raw)),
+            }
+        }
+
+        if !gui.looping || stop.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        recording = Recording::open(&path)?;
+    }
+}
@@ -13,7 +13,7 @@ use parking_lot::Mutex;
 use swi_packet::{SwiButton, SwiController, SwiPacketBuffer};
 use zinput_engine::{
     device::component::{
-        controller::{Button, ControllerInfo},
+        controller::{widen_axis, Button, ControllerInfo},
         motion::MotionInfo,
     },
     eframe::{self, egui},
@@ -335,10 +335,10 @@ impl<'a> DeviceBundle<'a> {
 
         ctrl.buttons = buttons;
 
-        ctrl.left_stick_x = from.left_stick[0];
-        ctrl.left_stick_y = from.left_stick[1];
-        ctrl.right_stick_x = from.right_stick[0];
-        ctrl.right_stick_y = from.right_stick[1];
+        ctrl.left_stick_x = widen_axis(from.left_stick[0]);
+        ctrl.left_stick_y = widen_axis(from.left_stick[1]);
+        ctrl.right_stick_x = widen_axis(from.right_stick[0]);
+        ctrl.right_stick_y = widen_axis(from.right_stick[1]);
 
         let motion = &mut self.motion[0];
 
@@ -1,13 +1,24 @@
-use std::{ops::ControlFlow, sync::Arc, time::Duration};
+use std::{
+    ops::ControlFlow,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::{anyhow, Context, Result};
 use hidcon::gc_adaptor::{
-    Button as HidButton, Buttons as HidButtons, Device as HidDevice, EP_IN, EP_OUT, INITIALIZE,
-    PRODUCT_ID, VENDOR_ID,
+    self, Button as HidButton, Buttons as HidButtons, ControllerState, Device as HidDevice, EP_IN,
+    EP_OUT, INITIALIZE, PRODUCT_ID, VENDOR_ID,
 };
 use rusb::{DeviceHandle, GlobalContext};
 use zinput_engine::{
-    device::component::controller::{Button, Controller, ControllerInfo},
+    device::{
+        component::{
+            controller::{widen_axis, Button, Controller, ControllerInfo},
+            motor::{Motor, MotorInfo, MotorKind},
+            status::{Connection, Status, StatusInfo},
+        },
+        DeviceInfo,
+    },
     Engine,
 };
 
@@ -32,7 +43,11 @@ fn filter(dev: &rusb::Device<rusb::GlobalContext>) -> bool {
         .unwrap_or(false)
 }
 
-crate::device_bundle!(DeviceBundle(owned), controller: Controller);
+crate::device_bundle!(DeviceBundle(owned),
+    controller: Controller,
+    status(statuses): Status,
+    motor: Motor,
+);
 
 struct GCDriver {
     packet: [u8; 37],
@@ -41,6 +56,8 @@ struct GCDriver {
     bundles: [Option<DeviceBundle<'static>>; 4],
     ids: [Option<String>; 4],
     device: HidDevice,
+    rumble: [bool; 4],
+    rumble_until: [Option<Instant>; 4],
 }
 
 impl DeviceDriver for GCDriver {
@@ -54,6 +71,8 @@ impl DeviceDriver for GCDriver {
             bundles: [None, None, None, None],
             ids: [None, None, None, None],
             device: HidDevice::default(),
+            rumble: [false; 4],
+            rumble_until: [None; 4],
         })
     }
 
@@ -94,6 +113,8 @@ impl DeviceDriver for GCDriver {
 
         self.device.update(&self.packet)?;
 
+        self.update_rumble(handle)?;
+
         for i in 0..4 {
             let is_active = self.device.controllers[i].is_some();
 
@@ -120,12 +141,28 @@ impl DeviceDriver for GCDriver {
                         self.device_id
                     );
 
-                    let bundle = DeviceBundle::new(
+                    // the wavebird receiver has no rumble motor
+                    let motors = match &self.device.controllers[i] {
+                        Some(controller) if controller.state == ControllerState::Wavebird => {
+                            Vec::new()
+                        }
+                        _ => vec![MotorInfo::new(MotorKind::Erm)],
+                    };
+
+                    let mut info = DeviceInfo::new(format!(
+                        "Gamecube Adaptor {} Slot {}",
+                        self.device_id,
+                        i + 1
+                    ))
+                    .with_kind("gc_adaptor".to_owned());
+                    info.id = self.ids[i].clone();
+
+                    let bundle = DeviceBundle::from_info(
                         self.engine.clone(),
-                        format!("Gamecube Adaptor {} Slot {}", self.device_id, i + 1),
-                        self.ids[i].clone(),
-                        false,
+                        info,
                         [gc_controller_info()],
+                        [StatusInfo::new(false, false)],
+                        motors,
                     )?;
 
                     self.bundles[i] = Some(bundle);
@@ -138,13 +175,18 @@ impl DeviceDriver for GCDriver {
                 bundle.controller[0].buttons = convert_buttons(controller.buttons);
                 // TODO: Change back
                 bundle.controller[0].left_stick_x =
-                    ((((controller.left_stick.x as f32) - 127.0) * 1.25) + 127.0) as u8;
+                    ((((controller.left_stick.x as f32) - 127.0) * 1.25 + 127.0) * 257.0) as u16;
                 bundle.controller[0].left_stick_y =
-                    ((((controller.left_stick.y as f32) - 127.0) * 1.25) + 127.0) as u8;
-                bundle.controller[0].right_stick_x = controller.right_stick.x;
-                bundle.controller[0].right_stick_y = controller.right_stick.y;
-                bundle.controller[0].l2_analog = controller.left_trigger;
-                bundle.controller[0].r2_analog = controller.right_trigger;
+                    ((((controller.left_stick.y as f32) - 127.0) * 1.25 + 127.0) * 257.0) as u16;
+                bundle.controller[0].right_stick_x = widen_axis(controller.right_stick.x);
+                bundle.controller[0].right_stick_y = widen_axis(controller.right_stick.y);
+                bundle.controller[0].l2_analog = widen_axis(controller.left_trigger);
+                bundle.controller[0].r2_analog = widen_axis(controller.right_trigger);
+
+                bundle.status[0].set_connection(match controller.state {
+                    ControllerState::Normal => Connection::Usb,
+                    ControllerState::Wavebird => Connection::Dongle,
+                });
 
                 bundle.update();
             }
@@ -152,6 +194,59 @@ impl DeviceDriver for GCDriver {
 
         Ok(ControlFlow::Continue(()))
     }
+
+    fn uninitialize(&mut self, handle: &mut DeviceHandle<GlobalContext>) -> Result<()> {
+        if self.rumble != [false; 4] {
+            handle
+                .write_interrupt(
+                    EP_OUT,
+                    &gc_adaptor::rumble([false; 4]),
+                    Duration::from_secs(1),
+                )
+                .context("failed to stop rumble")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl GCDriver {
+    fn update_rumble(&mut self, handle: &mut DeviceHandle<GlobalContext>) -> Result<()> {
+        let now = Instant::now();
+        let mut rumble = self.rumble;
+
+        for i in 0..4 {
+            let Some(bundle) = &mut self.bundles[i]
+            else {
+                rumble[i] = false;
+                self.rumble_until[i] = None;
+                continue;
+            };
+
+            if let Some(feedback) = bundle.handle.take_feedback().and_then(|f| f.rumble()) {
+                rumble[i] = !feedback.is_off();
+                self.rumble_until[i] = feedback.duration.map(|duration| now + duration);
+            } else if self.rumble_until[i].map_or(false, |until| now >= until) {
+                rumble[i] = false;
+                self.rumble_until[i] = None;
+            }
+
+            bundle.motor[0].amplitude = if rumble[i] { u8::MAX } else { 0 };
+        }
+
+        if rumble != self.rumble {
+            handle
+                .write_interrupt(
+                    EP_OUT,
+                    &gc_adaptor::rumble(rumble),
+                    Duration::from_millis(100),
+                )
+                .context("failed to set rumble")?;
+            self.rumble = rumble;
+        }
+
+        Ok(())
+    }
 }
 
 fn convert_buttons(buttons: HidButtons) -> u64 {
@@ -9,12 +9,17 @@ mod ctx {
     use winapi::{
         shared::{minwindef, windef},
         um::{
-            dbt::{DBT_DEVTYP_DEVICEINTERFACE, DEV_BROADCAST_DEVICEINTERFACE_W},
+            dbt::{
+                DBT_DEVICEARRIVAL, DBT_DEVICEREMOVECOMPLETE, DBT_DEVTYP_DEVICEINTERFACE,
+                DEV_BROADCAST_DEVICEINTERFACE_W,
+            },
             libloaderapi::GetModuleHandleW,
             winuser::{self, DEVICE_NOTIFY_WINDOW_HANDLE},
         },
     };
 
+    use super::HotPlugEvent;
+
     const T: &'static str = "usb_devices:hotplug";
 
     pub struct HotPlugInner {
@@ -25,9 +30,9 @@ mod ctx {
     impl HotPlugInner {
         pub fn register<F>(callback: F) -> Result<Self>
         where
-            F: FnMut() + Send + 'static,
+            F: FnMut(HotPlugEvent) + Send + 'static,
         {
-            let callback: Box<dyn FnMut() + Send + 'static> = Box::new(callback);
+            let callback: Box<dyn FnMut(HotPlugEvent) + Send + 'static> = Box::new(callback);
 
             let (send, recv) = crossbeam_channel::unbounded();
 
@@ -88,7 +93,7 @@ mod ctx {
     }
 
     fn hotplug_thread(
-        callback: Box<dyn FnMut() + Send + 'static>,
+        callback: Box<dyn FnMut(HotPlugEvent) + Send + 'static>,
         send: Sender<Result<Hwnd>>,
     ) -> impl FnOnce() {
         move || {
@@ -105,11 +110,11 @@ mod ctx {
     }
 
     struct State {
-        callback: Box<dyn FnMut() + Send + 'static>,
+        callback: Box<dyn FnMut(HotPlugEvent) + Send + 'static>,
     }
 
     fn run_hotplug_thread(
-        callback: Box<dyn FnMut() + Send + 'static>,
+        callback: Box<dyn FnMut(HotPlugEvent) + Send + 'static>,
         send: Sender<Result<Hwnd>>,
     ) -> Result<()> {
         let class_name = "ZInput usb_devices Hotplug".os_str();
@@ -219,7 +224,11 @@ mod ctx {
                 0
             }
             winuser::WM_DEVICECHANGE => {
-                (state.callback)();
+                match wparam {
+                    DBT_DEVICEARRIVAL => (state.callback)(HotPlugEvent::Arrived),
+                    DBT_DEVICEREMOVECOMPLETE => (state.callback)(HotPlugEvent::Left),
+                    _ => {}
+                }
                 0
             }
             _ => winuser::DefWindowProcW(hwnd, msg, wparam, lparam),
@@ -232,6 +241,8 @@ mod ctx {
     use anyhow::{Context, Result};
     use rusb::{GlobalContext, Hotplug, HotplugBuilder, Registration};
 
+    use super::HotPlugEvent;
+
     pub struct HotPlugInner {
         reg: Registration<GlobalContext>,
     }
@@ -239,7 +250,7 @@ mod ctx {
     impl HotPlugInner {
         pub fn register<F>(callback: F) -> Result<Self>
         where
-            F: FnMut() + Send + 'static,
+            F: FnMut(HotPlugEvent) + Send + 'static,
         {
             let reg = HotplugBuilder::new()
                 .register(GlobalContext {}, Box::new(HotPlugImpl { callback }))
@@ -255,18 +266,26 @@ mod ctx {
 
     impl<F> Hotplug<GlobalContext> for HotPlugImpl<F>
     where
-        F: FnMut() + Send,
+        F: FnMut(HotPlugEvent) + Send,
     {
-        fn device_arrived(&mut self, device: rusb::Device<GlobalContext>) {
-            (self.callback)()
+        fn device_arrived(&mut self, _device: rusb::Device<GlobalContext>) {
+            (self.callback)(HotPlugEvent::Arrived)
         }
 
-        fn device_left(&mut self, device: rusb::Device<GlobalContext>) {}
+        fn device_left(&mut self, _device: rusb::Device<GlobalContext>) {
+            (self.callback)(HotPlugEvent::Left)
+        }
     }
 }
 
 use ctx::HotPlugInner;
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HotPlugEvent {
+    Arrived,
+    Left,
+}
+
 pub struct HotPlug {
     _inner: HotPlugInner,
 }
@@ -274,7 +293,7 @@ pub struct HotPlug {
 impl HotPlug {
     pub fn register<F>(callback: F) -> Result<Self>
     where
-        F: FnMut() + Send + 'static,
+        F: FnMut(HotPlugEvent) + Send + 'static,
     {
         Ok(HotPlug {
             _inner: HotPlugInner::register(callback).context("failed to register hotplug event")?,
@@ -10,11 +10,12 @@ use std::{
 use anyhow::{Context, Result};
 use parking_lot::Mutex;
 use zinput_engine::{
+    event::Event,
     plugin::{Plugin, PluginKind, PluginStatus},
     Engine,
 };
 
-use self::hotplug::HotPlug;
+use self::hotplug::{HotPlug, HotPlugEvent};
 
 mod device_thread;
 mod gc_adaptor;
@@ -163,11 +164,24 @@ impl ScanContext {
     }
 }
 
-fn hotplug_function(scan_context: Arc<Mutex<ScanContext>>) -> impl FnMut() + Send + 'static {
-    move || match scan_context.lock().scan_devices() {
-        Ok(()) => {}
-        Err(e) => {
-            log::warn!(target: T, "failed to scan devices: {}", e);
+fn hotplug_function(
+    scan_context: Arc<Mutex<ScanContext>>,
+) -> impl FnMut(HotPlugEvent) + Send + 'static {
+    move |event| {
+        let mut scan_context = scan_context.lock();
+
+        match event {
+            HotPlugEvent::Arrived => {
+                scan_context.engine.emit(Event::UsbConnected);
+
+                match scan_context.scan_devices() {
+                    Ok(()) => {}
+                    Err(e) => {
+                        log::warn!(target: T, "failed to scan devices: {}", e);
+                    }
+                }
+            }
+            HotPlugEvent::Left => scan_context.engine.emit(Event::UsbDisconnected),
         }
     }
 }
@@ -7,7 +7,7 @@ use hidcon::powera_pro_controller::{
 };
 use rusb::{DeviceHandle, GlobalContext};
 use zinput_engine::{
-    device::component::controller::{Button, Controller, ControllerInfo},
+    device::component::controller::{widen_axis, Button, Controller, ControllerInfo},
     Engine,
 };
 
@@ -100,13 +100,13 @@ impl DeviceDriver for PADriver {
         self.bundle.controller[0].buttons =
             convert_buttons(self.controller.buttons, self.controller.dpad);
         self.bundle.controller[0].l2_analog =
-            (self.controller.buttons.is_pressed(HidButton::L2) as u8) * 255;
+            (self.controller.buttons.is_pressed(HidButton::L2) as u16) * u16::MAX;
         self.bundle.controller[0].r2_analog =
-            (self.controller.buttons.is_pressed(HidButton::R2) as u8) * 255;
-        self.bundle.controller[0].left_stick_x = self.controller.left_stick.x;
-        self.bundle.controller[0].left_stick_y = 255 - self.controller.left_stick.y;
-        self.bundle.controller[0].right_stick_x = self.controller.right_stick.x;
-        self.bundle.controller[0].right_stick_y = 255 - self.controller.right_stick.y;
+            (self.controller.buttons.is_pressed(HidButton::R2) as u16) * u16::MAX;
+        self.bundle.controller[0].left_stick_x = widen_axis(self.controller.left_stick.x);
+        self.bundle.controller[0].left_stick_y = widen_axis(255 - self.controller.left_stick.y);
+        self.bundle.controller[0].right_stick_x = widen_axis(self.controller.right_stick.x);
+        self.bundle.controller[0].right_stick_y = widen_axis(255 - self.controller.right_stick.y);
 
         self.bundle.update();
 
@@ -1,17 +1,27 @@
-use std::{ops::ControlFlow, sync::Arc, time::Duration};
+use std::{
+    ops::ControlFlow,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, Result};
 use hidcon::steam_controller::{
-    Button as HidButton, Controller as HidController, DISABLE_LIZARD_MODE, ENABLE_MOTION, EP_IN,
-    PRODUCT_ID_WIRELESS, VENDOR_ID,
+    haptic_pulse, Button as HidButton, Controller as HidController, HapticSide,
+    DISABLE_LIZARD_MODE, ENABLE_MOTION, EP_IN, PRODUCT_ID_WIRED, PRODUCT_ID_WIRELESS, VENDOR_ID,
 };
 
 use rusb::{Device, DeviceHandle, GlobalContext};
 use zinput_engine::{
-    device::component::{
-        controller::{Button, Controller, ControllerInfo},
-        motion::{Motion, MotionInfo},
-        touch_pad::{TouchPad, TouchPadInfo, TouchPadShape},
+    device::{
+        component::{
+            controller::{unsigned_axis, widen_axis, Button, Controller, ControllerInfo},
+            motion::{Motion, MotionInfo},
+            motor::{Motor, MotorInfo, MotorKind},
+            mouse::{Mouse, MouseInfo},
+            status::{Connection, Status, StatusInfo},
+            touch_pad::{TouchPad, TouchPadInfo, TouchPadShape},
+        },
+        DeviceInfo,
     },
     Engine,
 };
@@ -21,6 +31,10 @@ use super::{
     util::{self, UsbExt},
     UsbDriver,
 };
+use crate::backend::TickClock;
+
+/// Time between two reports from the controller
+const REPORT_INTERVAL: Duration = Duration::from_millis(4);
 
 pub(super) fn driver() -> UsbDriver {
     UsbDriver {
@@ -32,7 +46,11 @@ pub(super) fn driver() -> UsbDriver {
 fn filter(dev: &rusb::Device<rusb::GlobalContext>) -> bool {
     dev.device_descriptor()
         .ok()
-        .map(|desc| desc.vendor_id() == VENDOR_ID && desc.product_id() == PRODUCT_ID_WIRELESS)
+        .map(|desc| {
+            desc.vendor_id() == VENDOR_ID
+                && (desc.product_id() == PRODUCT_ID_WIRELESS
+                    || desc.product_id() == PRODUCT_ID_WIRED)
+        })
         .unwrap_or(false)
 }
 
@@ -40,6 +58,11 @@ crate::device_bundle!(DeviceBundle (owned),
     controller: Controller,
     motion: Motion,
     touch_pad: TouchPad[2],
+    status(statuses): Status,
+    // written to by touch pad configs, as lizard mode is disabled
+    mouse: Mouse,
+    // the pads' haptic actuators, left then right
+    motor: Motor[2],
 );
 
 struct SCDriver {
@@ -48,23 +71,32 @@ struct SCDriver {
     packet: [u8; 64],
     bundle: DeviceBundle<'static>,
     controller: HidController,
+    clock: TickClock,
+    /// The HID interface, which differs between the wired controller and the dongle
+    iface: u8,
+    /// The interface's interrupt endpoint for reports
+    endpoint: u8,
+    connection: Connection,
+    /// When the last rumble command ends, if it has a duration
+    rumble_until: Option<Instant>,
 }
 
 impl DeviceDriver for SCDriver {
     const NAME: &'static str = "Steam Controller";
 
     fn new(engine: &Arc<Engine>, adaptor_id: u64) -> Result<Self> {
-        let bundle = DeviceBundle::new(
+        let bundle = DeviceBundle::from_info(
             engine.clone(),
-            format!("Steam Controller {}", adaptor_id),
-            None,
-            true,
+            sc_device_info(adaptor_id, None),
             [sc_controller_info()],
             [MotionInfo::new(true, true)],
             [
-                TouchPadInfo::new(TouchPadShape::Circle, true),
-                TouchPadInfo::new(TouchPadShape::Circle, true),
+                TouchPadInfo::new(TouchPadShape::Circle, true, [u16::MAX; 2], 1),
+                TouchPadInfo::new(TouchPadShape::Circle, true, [u16::MAX; 2], 1),
             ],
+            [StatusInfo::new(true, false)],
+            [MouseInfo::new(0, true)],
+            sc_motor_info(),
         )?;
 
         Ok(SCDriver {
@@ -73,6 +105,11 @@ impl DeviceDriver for SCDriver {
             packet: [0; 64],
             bundle,
             controller: Default::default(),
+            clock: TickClock::new(32, REPORT_INTERVAL),
+            iface: 1,
+            endpoint: EP_IN,
+            connection: Connection::Dongle,
+            rumble_until: None,
         })
     }
 
@@ -96,6 +133,28 @@ impl DeviceDriver for SCDriver {
             .claim_interface(iface)
             .context("failed to claim interface")?;
 
+        self.iface = iface;
+        self.endpoint = device
+            .active_config_descriptor()
+            .ok()
+            .and_then(|config| {
+                config
+                    .interfaces()
+                    .filter(|interface| interface.number() == iface)
+                    .flat_map(|interface| interface.descriptors())
+                    .flat_map(|desc| desc.endpoint_descriptors().collect::<Vec<_>>())
+                    .find(|endpoint| {
+                        endpoint.direction() == rusb::Direction::In
+                            && endpoint.transfer_type() == rusb::TransferType::Interrupt
+                    })
+                    .map(|endpoint| endpoint.address())
+            })
+            .unwrap_or(EP_IN);
+        self.connection = match device.device_descriptor()?.product_id() {
+            PRODUCT_ID_WIRED => Connection::Usb,
+            _ => Connection::Dongle,
+        };
+
         Ok(handle)
     }
 
@@ -106,28 +165,31 @@ impl DeviceDriver for SCDriver {
             .and_then(|desc| handle.read_serial_number_string_ascii(&desc))
             .ok()
             .map(|mut serial| {
+                // steam controllers have always had this prefix,
+                // and their saved configs, slots and uuids are keyed by it
                 serial.insert_str(0, "pa_switch/");
                 serial
             });
 
-        self.bundle = DeviceBundle::new(
+        self.bundle = DeviceBundle::from_info(
             self.engine.clone(),
-            format!("Steam Controller {}", self.adaptor_id),
-            id,
-            true,
+            sc_device_info(self.adaptor_id, id),
             [sc_controller_info()],
             [MotionInfo::new(true, true)],
             [
-                TouchPadInfo::new(TouchPadShape::Circle, true),
-                TouchPadInfo::new(TouchPadShape::Circle, true),
+                TouchPadInfo::new(TouchPadShape::Circle, true, [u16::MAX; 2], 1),
+                TouchPadInfo::new(TouchPadShape::Circle, true, [u16::MAX; 2], 1),
             ],
+            [StatusInfo::new(true, false)],
+            [MouseInfo::new(0, true)],
+            sc_motor_info(),
         )?;
 
         handle.write_control(
             0x21,
             0x09,
             0x300,
-            1,
+            self.iface as u16,
             &DISABLE_LIZARD_MODE,
             Duration::from_secs(3),
         )?;
@@ -136,7 +198,7 @@ impl DeviceDriver for SCDriver {
             0x21,
             0x09,
             0x0300,
-            1,
+            self.iface as u16,
             &ENABLE_MOTION,
             Duration::from_secs(3),
         )?;
@@ -145,8 +207,11 @@ impl DeviceDriver for SCDriver {
     }
 
     fn update(&mut self, handle: &mut DeviceHandle<GlobalContext>) -> Result<ControlFlow<()>> {
-        let size = match handle.read_interrupt(EP_IN, &mut self.packet, Duration::from_millis(2000))
-        {
+        let size = match handle.read_interrupt(
+            self.endpoint,
+            &mut self.packet,
+            Duration::from_millis(2000),
+        ) {
             Ok(size) => size,
             Err(rusb::Error::Timeout) => return Ok(ControlFlow::Continue(())),
             Err(rusb::Error::NoDevice) => return Ok(ControlFlow::Break(())),
@@ -161,14 +226,51 @@ impl DeviceDriver for SCDriver {
         self.update_controller();
         self.update_touch_pads();
         self.update_motion();
+        self.update_status();
+
+        let time = self.clock.update(self.controller.sequence as u64);
+        self.bundle.update_with_time(time);
 
-        self.bundle.update();
+        self.update_feedback(handle)?;
 
         Ok(ControlFlow::Continue(()))
     }
 }
 
 impl SCDriver {
+    fn update_feedback(&mut self, handle: &mut DeviceHandle<GlobalContext>) -> Result<()> {
+        let Some(rumble) = self.bundle.handle.take_feedback().and_then(|f| f.rumble())
+        else {
+            if self.rumble_until.map_or(false, |until| Instant::now() >= until) {
+                self.rumble_until = None;
+                self.bundle.motor = [Motor::default(); 2];
+            }
+            return Ok(());
+        };
+
+        self.rumble_until = rumble.duration.map(|duration| Instant::now() + duration);
+        self.bundle.motor[0].amplitude = rumble.strong;
+        self.bundle.motor[1].amplitude = rumble.weak;
+
+        for (side, magnitude) in [
+            (HapticSide::Left, rumble.strong),
+            (HapticSide::Right, rumble.weak),
+        ] {
+            handle
+                .write_control(
+                    0x21,
+                    0x09,
+                    0x0300,
+                    self.iface as u16,
+                    &haptic_report(side, magnitude, rumble.duration),
+                    Duration::from_secs(1),
+                )
+                .context("failed to send haptic pulse")?;
+        }
+
+        Ok(())
+    }
+
     fn update_controller(&mut self) {
         let buttons = self.controller.buttons;
         let lpad_x = self.controller.left_pad.x;
@@ -222,13 +324,13 @@ impl SCDriver {
 
         self.bundle.controller[0].buttons = new_buttons;
 
-        self.bundle.controller[0].l2_analog = ltrig;
-        self.bundle.controller[0].r2_analog = rtrig;
-        self.bundle.controller[0].right_stick_x = ((rpad_x / 256) + 128) as u8;
-        self.bundle.controller[0].right_stick_y = ((rpad_y / 256) + 128) as u8;
+        self.bundle.controller[0].l2_analog = widen_axis(ltrig);
+        self.bundle.controller[0].r2_analog = widen_axis(rtrig);
+        self.bundle.controller[0].right_stick_x = unsigned_axis(rpad_x);
+        self.bundle.controller[0].right_stick_y = unsigned_axis(rpad_y);
         if !lpad_touch {
-            self.bundle.controller[0].left_stick_x = ((lpad_x / 256) + 128) as u8;
-            self.bundle.controller[0].left_stick_y = ((lpad_y / 256) + 128) as u8;
+            self.bundle.controller[0].left_stick_x = unsigned_axis(lpad_x);
+            self.bundle.controller[0].left_stick_y = unsigned_axis(lpad_y);
         }
     }
 
@@ -239,22 +341,33 @@ impl SCDriver {
         let rpad_x = self.controller.right_pad.x;
         let rpad_y = self.controller.right_pad.y;
 
-        self.bundle.touch_pad[1].touch_x = (rpad_x as i32 - i16::MIN as i32) as u32 as u16;
-        self.bundle.touch_pad[1].touch_y = (rpad_y as i32 - i16::MIN as i32) as u32 as u16;
-        self.bundle.touch_pad[1].pressed = buttons.is_pressed(HidButton::RClick);
-        self.bundle.touch_pad[1].touched = buttons.is_pressed(HidButton::RPadTouch);
-
-        if buttons.is_pressed(HidButton::LPadTouch) {
-            self.bundle.touch_pad[0].touched = true;
-            self.bundle.touch_pad[0].pressed = buttons.is_pressed(HidButton::LClick);
-            self.bundle.touch_pad[0].touch_x = (lpad_x as i32 - i16::MIN as i32) as u32 as u16;
-            self.bundle.touch_pad[0].touch_y = (lpad_y as i32 - i16::MIN as i32) as u32 as u16;
-        } else {
-            self.bundle.touch_pad[0].pressed = false;
-            self.bundle.touch_pad[0].touched = false;
-            self.bundle.touch_pad[0].touch_x = u16::MAX / 2;
-            self.bundle.touch_pad[0].touch_y = u16::MAX / 2;
+        fn position(x: i16, y: i16) -> [u16; 2] {
+            [
+                (x as i32 - i16::MIN as i32) as u16,
+                (i16::MAX as i32 - y as i32) as u16,
+            ]
         }
+
+        let rpad = &mut self.bundle.touch_pad[1];
+        rpad.pressed = buttons.is_pressed(HidButton::RClick);
+        rpad.set_touch(
+            0,
+            buttons
+                .is_pressed(HidButton::RPadTouch)
+                .then(|| position(rpad_x, rpad_y)),
+        );
+
+        // the left pad's position is the stick's while the pad is not touched
+        let lpad = &mut self.bundle.touch_pad[0];
+        let ltouch = buttons.is_pressed(HidButton::LPadTouch);
+        lpad.pressed = ltouch && buttons.is_pressed(HidButton::LClick);
+        lpad.set_touch(0, ltouch.then(|| position(lpad_x, lpad_y)));
+    }
+
+    fn update_status(&mut self) {
+        let status = &mut self.bundle.status[0];
+        status.battery = self.controller.battery.unwrap_or(0);
+        status.set_connection(self.connection);
     }
 
     fn update_motion(&mut self) {
@@ -270,6 +383,40 @@ impl SCDriver {
     }
 }
 
+/// Emulates a rumble motor by pulsing a pad actuator with a duty cycle matching `magnitude`
+fn haptic_report(side: HapticSide, magnitude: u8, duration: Option<Duration>) -> [u8; 64] {
+    // microseconds
+    const PERIOD: u32 = 4000;
+
+    if magnitude == 0 {
+        return haptic_pulse(side, 0, 0, 0);
+    }
+
+    let on_time = PERIOD * magnitude as u32 / 255;
+    let off_time = PERIOD - on_time;
+    let repeat = match duration {
+        Some(duration) => (duration.as_micros() / PERIOD as u128).clamp(1, u16::MAX as u128),
+        None => u16::MAX as u128,
+    };
+
+    haptic_pulse(side, on_time as u16, off_time as u16, repeat as u16)
+}
+
+fn sc_device_info(adaptor_id: u64, id: Option<String>) -> DeviceInfo {
+    let mut info = DeviceInfo::new(format!("Steam Controller {}", adaptor_id))
+        .with_kind("steam_controller".to_owned())
+        .autoload_config(true);
+    info.id = id;
+    info
+}
+
+fn sc_motor_info() -> [MotorInfo; 2] {
+    [
+        MotorInfo::new(MotorKind::Linear),
+        MotorInfo::new(MotorKind::Linear),
+    ]
+}
+
 fn sc_controller_info() -> ControllerInfo {
     use Button::*;
 
@@ -9,7 +9,9 @@ use std::{
 use anyhow::Result;
 use parking_lot::Mutex;
 use rusty_xinput::{XInputHandle, XInputState, XInputUsageError};
-use zinput_engine::device::component::controller::{Button, Controller, ControllerInfo};
+use zinput_engine::device::component::controller::{
+    unsigned_axis, widen_axis, Button, Controller, ControllerInfo,
+};
 use zinput_engine::{
     plugin::{Plugin, PluginKind, PluginStatus},
     Engine,
@@ -261,16 +263,16 @@ impl<'a> XController<'a> {
             right_thumb_button => Button::RStick,
         );
 
-        self.controller[0].l2_analog = state.left_trigger();
-        self.controller[0].r2_analog = state.right_trigger();
+        self.controller[0].l2_analog = widen_axis(state.left_trigger());
+        self.controller[0].r2_analog = widen_axis(state.right_trigger());
 
         let (lpad_x, lpad_y) = state.left_stick_raw();
         let (rpad_x, rpad_y) = state.right_stick_raw();
 
-        self.controller[0].left_stick_x = ((lpad_x / 256) + 128) as u8;
-        self.controller[0].left_stick_y = ((lpad_y / 256) + 128) as u8;
-        self.controller[0].right_stick_x = ((rpad_x / 256) + 128) as u8;
-        self.controller[0].right_stick_y = ((rpad_y / 256) + 128) as u8;
+        self.controller[0].left_stick_x = unsigned_axis(lpad_x);
+        self.controller[0].left_stick_y = unsigned_axis(lpad_y);
+        self.controller[0].right_stick_x = unsigned_axis(rpad_x);
+        self.controller[0].right_stick_y = unsigned_axis(rpad_y);
 
         self.update();
     }
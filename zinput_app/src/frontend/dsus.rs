@@ -20,11 +20,17 @@ use dsu_protocol::{
 };
 use parking_lot::Mutex;
 use zinput_engine::{
-    device::component::{
-        controller::{Button, Controller},
-        motion::Motion,
+    device::{
+        component::{
+            controller::{narrow_axis, Button, Controller},
+            motion::Motion,
+            player_leds::PlayerLeds,
+            status::{Connection, Status, StatusInfo},
+            touch_pad::{TouchPad, TouchPadInfo},
+        },
+        feedback::Feedback,
     },
-    DeviceView,
+    DeviceView, Stamp,
 };
 use zinput_engine::{
     eframe::{self, egui},
@@ -74,6 +80,18 @@ impl Plugin for Dsus {
     fn update_gui(&self, ctx: &egui::Context, frame: &mut eframe::Frame, ui: &mut egui::Ui) {
         self.inner.lock().update_gui(ctx, frame, ui)
     }
+
+    fn slots(&self) -> usize {
+        self.inner.lock().selected_devices.len()
+    }
+
+    fn bind_slot(&self, slot: usize, device: Option<Uuid>) {
+        self.inner.lock().bind_slot(slot, device);
+    }
+
+    fn slot_device(&self, slot: usize) -> Option<Uuid> {
+        *self.inner.lock().selected_devices.get(slot)?
+    }
 }
 
 struct Inner {
@@ -177,35 +195,25 @@ impl Inner {
         *self.status.lock() = PluginStatus::Stopped;
     }
 
+    fn bind_slot(&mut self, slot: usize, device: Option<Uuid>) {
+        let Some(selected) = self.selected_devices.get_mut(slot)
+        else { return; };
+
+        *selected = device;
+        self.device.send((slot, device)).unwrap();
+        self.controllers.lock()[slot] = device.is_some();
+    }
+
     fn update_gui(&mut self, _ctx: &egui::Context, _frame: &mut eframe::Frame, ui: &mut egui::Ui) {
         if let Some(engine) = self.engine.clone() {
             for i in 0..self.selected_devices.len() {
                 egui::ComboBox::from_label(format!("Dsus Controller {}", i + 1))
-                    .selected_text(
-                        self.selected_devices[i]
-                            .and_then(|id| engine.get_device(&id))
-                            .map_or("[None]".to_owned(), |view| view.info().name.clone()),
-                    )
+                    .selected_text(super::slot_device_name(&engine, self.selected_devices[i]))
                     .show_ui(ui, |ui| {
-                        if ui
-                            .selectable_value(&mut self.selected_devices[i], None, "[None]")
-                            .clicked()
-                        {
-                            self.device.send((i, None)).unwrap();
-                            self.controllers.lock()[i] = false;
-                        }
-                        for entry in engine.devices() {
-                            if ui
-                                .selectable_value(
-                                    &mut self.selected_devices[i],
-                                    Some(*entry.uuid()),
-                                    &entry.info().name,
-                                )
-                                .clicked()
-                            {
-                                self.device.send((i, Some(*entry.uuid()))).unwrap();
-                                self.controllers.lock()[i] = true;
-                            }
+                        let selected = &mut self.selected_devices[i];
+                        if let Some(device) = super::slot_device_options(ui, &engine, selected) {
+                            self.device.send((i, device)).unwrap();
+                            self.controllers.lock()[i] = device.is_some();
                         }
                     });
             }
@@ -462,9 +470,16 @@ fn dsus_thread(thread: Thread) -> Result<()> {
             recv(device_change) -> device_change => {
                 match device_change {
                     Ok((idx, Some(device_id))) => {
+                        // a reserved slot is bound again when its device connects
+                        if views[idx].as_ref().map(|view| *view.uuid()) == Some(device_id) {
+                            continue;
+                        }
+
                         views[idx] = engine.get_device(&device_id);
                         if let Some(view) = &mut views[idx] {
                             view.register_channel(update_send.clone());
+                            // the device may not have player leds
+                            let _ = view.send_feedback(Feedback::PlayerLeds(PlayerLeds::player(idx)));
                         }
 
                         server.set_connected(idx as u8, true);
@@ -497,9 +512,17 @@ fn dsus_thread(thread: Thread) -> Result<()> {
                             None => {},
                         }
                         match device.motions.get(0) {
-                            Some(motion) => server.update_motion(i as u8, motion),
+                            Some(motion) => server.update_motion(i as u8, motion, device.stamp()),
                             None => {},
                         }
+                        match (device.statuses.get(0), view.info().statuses.get(0)) {
+                            (Some(status), Some(info)) => server.update_status(i as u8, status, info),
+                            _ => {},
+                        }
+                        match (device.touch_pads.get(0), view.info().touch_pads.get(0)) {
+                            (Some(touch_pad), Some(info)) => server.update_touch_pad(i as u8, touch_pad, info),
+                            _ => {},
+                        }
                     }
                 }
 
@@ -576,10 +599,10 @@ impl Server {
 
         let dsu_data = &mut self.dsu_data[slot as usize];
 
-        dsu_data.set_analog_l1(data.l1_analog);
-        dsu_data.set_analog_l2(data.l2_analog);
-        dsu_data.set_analog_r2(data.r2_analog);
-        dsu_data.set_analog_l2(data.l2_analog);
+        dsu_data.set_analog_l1(narrow_axis(data.l1_analog));
+        dsu_data.set_analog_l2(narrow_axis(data.l2_analog));
+        dsu_data.set_analog_r2(narrow_axis(data.r2_analog));
+        dsu_data.set_analog_l2(narrow_axis(data.l2_analog));
 
         let buttons = translate!(data.buttons, dsu_data,
             Button::A =>      DButton::A      => set_analog_a,
@@ -605,16 +628,20 @@ impl Server {
         } else {
             0
         });
-        dsu_data.set_left_stick_x(data.left_stick_x);
-        dsu_data.set_left_stick_y(data.left_stick_y);
-        dsu_data.set_right_stick_x(data.right_stick_x);
-        dsu_data.set_right_stick_y(data.right_stick_y);
+        dsu_data.set_left_stick_x(narrow_axis(data.left_stick_x));
+        dsu_data.set_left_stick_y(narrow_axis(data.left_stick_y));
+        dsu_data.set_right_stick_x(narrow_axis(data.right_stick_x));
+        dsu_data.set_right_stick_y(narrow_axis(data.right_stick_y));
     }
 
-    fn update_motion(&mut self, slot: u8, data: &Motion) {
+    fn update_motion(&mut self, slot: u8, data: &Motion, stamp: Stamp) {
         let dsu_data = &mut self.dsu_data[slot as usize];
 
-        dsu_data.set_motion_timestamp((Instant::now() - self.started).as_micros() as u64);
+        // prefer the device's clock as it is not affected by transport jitter
+        let timestamp = stamp
+            .hardware_time
+            .unwrap_or_else(|| stamp.captured.saturating_duration_since(self.started));
+        dsu_data.set_motion_timestamp(timestamp.as_micros() as u64);
 
         dsu_data.set_accel_x(data.accel_x);
         dsu_data.set_accel_y(data.accel_y);
@@ -624,6 +651,59 @@ impl Server {
         dsu_data.set_gyro_roll(data.gyro_roll);
     }
 
+    fn update_status(&mut self, slot: u8, data: &Status, info: &StatusInfo) {
+        let header = self.dsu_data[slot as usize].controller_header_mut();
+
+        header.set_connection_type(match data.connection() {
+            Connection::Usb => ConnectionType::Usb,
+            Connection::Bluetooth => ConnectionType::Bluetooth,
+            Connection::Dongle | Connection::Unknown => ConnectionType::NotApplicable,
+        });
+
+        header.set_battery_status(match data.battery {
+            _ if !info.has_battery => BatteryStatus::NotApplicable,
+            100..=u8::MAX if data.charging => BatteryStatus::Charged,
+            _ if data.charging => BatteryStatus::Charging,
+            0..=10 => BatteryStatus::Dying,
+            11..=25 => BatteryStatus::Low,
+            26..=60 => BatteryStatus::Medium,
+            61..=90 => BatteryStatus::High,
+            _ => BatteryStatus::Full,
+        });
+    }
+
+    /// DSU carries two touches in the DualShock 4's resolution
+    fn update_touch_pad(&mut self, slot: u8, data: &TouchPad, info: &TouchPadInfo) {
+        const DSU_RESOLUTION: [u32; 2] = [1919, 942];
+
+        fn scale(value: u16, from: u16, to: u32) -> u16 {
+            (value as u32 * to / (from as u32).max(1)).min(to) as u16
+        }
+
+        let dsu_data = &mut self.dsu_data[slot as usize];
+        let mut touches = data.active();
+
+        match touches.next() {
+            Some(touch) => {
+                dsu_data.set_touch1_active(true);
+                dsu_data.set_touch1_id(touch.id);
+                dsu_data.set_touch1_x(scale(touch.x, info.resolution[0], DSU_RESOLUTION[0]));
+                dsu_data.set_touch1_y(scale(touch.y, info.resolution[1], DSU_RESOLUTION[1]));
+            }
+            None => dsu_data.set_touch1_active(false),
+        }
+
+        match touches.next() {
+            Some(touch) => {
+                dsu_data.set_touch2_active(true);
+                dsu_data.set_touch2_id(touch.id);
+                dsu_data.set_touch2_x(scale(touch.x, info.resolution[0], DSU_RESOLUTION[0]));
+                dsu_data.set_touch2_y(scale(touch.y, info.resolution[1], DSU_RESOLUTION[1]));
+            }
+            None => dsu_data.set_touch2_active(false),
+        }
+    }
+
     fn send_data(&mut self, clients: &DashMap<SocketAddr, DsuClient>) -> Result<()> {
         let mut hasher = crc32::Digest::new(crc32::IEEE);
         for mut client in clients.iter_mut() {
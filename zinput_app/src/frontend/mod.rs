@@ -1,6 +1,58 @@
+use zinput_engine::{eframe::egui, util::Uuid, Engine};
+
 pub mod dsus;
 pub mod swi_send;
 #[cfg(target_os = "linux")]
 pub mod uinput;
 #[cfg(target_os = "windows")]
 pub mod vigem;
+
+/// The name shown for the device bound to a slot, which may not be connected
+fn slot_device_name(engine: &Engine, device: Option<Uuid>) -> String {
+    let Some(uuid) = device
+    else { return "[None]".to_owned(); };
+
+    match engine.known_device(&uuid) {
+        Some(known) if engine.is_connected(&uuid) => known.display_name().to_owned(),
+        Some(known) => format!("{} (disconnected)", known.display_name()),
+        None => engine
+            .get_device(&uuid)
+            .map_or("[Disconnected]".to_owned(), |view| view.info().name.clone()),
+    }
+}
+
+/// Lists the devices a slot can be bound to and returns the one that was clicked
+///
+/// Disconnected devices are listed too, so a slot can be kept for a device before it connects.
+fn slot_device_options(
+    ui: &mut egui::Ui,
+    engine: &Engine,
+    selected: &mut Option<Uuid>,
+) -> Option<Option<Uuid>> {
+    let mut clicked = None;
+
+    if ui.selectable_value(selected, None, "[None]").clicked() {
+        clicked = Some(None);
+    }
+
+    let connected = engine
+        .devices()
+        .map(|entry| *entry.uuid())
+        .collect::<Vec<_>>();
+
+    for uuid in connected {
+        let name = slot_device_name(engine, Some(uuid));
+        if ui.selectable_value(selected, Some(uuid), name).clicked() {
+            clicked = Some(Some(uuid));
+        }
+    }
+
+    for known in engine.disconnected_devices() {
+        let name = format!("{} (disconnected)", known.display_name());
+        if ui.selectable_value(selected, Some(known.uuid), name).clicked() {
+            clicked = Some(Some(known.uuid));
+        }
+    }
+
+    clicked
+}
@@ -11,10 +11,11 @@ use std::{
 use anyhow::{Context, Result};
 use crossbeam_channel::{Receiver, RecvError, Sender};
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use swi_packet::{SwiButton, SwiController, SwiPacketBuffer};
 use zinput_engine::{
     device::component::{
-        controller::{Button, Controller},
+        controller::{narrow_axis, Button, Controller},
         motion::Motion,
     },
     DeviceView,
@@ -69,6 +70,43 @@ impl Plugin for Swi {
     fn update_gui(&self, ctx: &egui::Context, frame: &mut eframe::Frame, ui: &mut egui::Ui) {
         self.inner.lock().update_gui(ctx, frame, ui)
     }
+
+    fn slots(&self) -> usize {
+        self.inner.lock().gui().selected_devices.len()
+    }
+
+    fn bind_slot(&self, slot: usize, device: Option<Uuid>) {
+        self.inner.lock().bind_slot(slot, device);
+    }
+
+    fn slot_device(&self, slot: usize) -> Option<Uuid> {
+        *self.inner.lock().gui().selected_devices.get(slot)?
+    }
+
+    fn settings(&self) -> Option<serde_json::Value> {
+        let settings = Settings {
+            address: self.inner.lock().gui().address.clone(),
+        };
+
+        serde_json::to_value(settings).ok()
+    }
+
+    fn load_settings(&self, settings: serde_json::Value) {
+        match serde_json::from_value::<Settings>(settings) {
+            Ok(settings) => {
+                let mut inner = self.inner.lock();
+                let gui = inner.gui();
+                gui.old_address = settings.address.clone();
+                gui.address = settings.address;
+            }
+            Err(err) => log::warn!(target: T, "invalid settings: {err}"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct Settings {
+    address: String,
 }
 
 #[derive(Clone)]
@@ -133,6 +171,12 @@ impl Inner {
 
         let (device_send, device_recv) = crossbeam_channel::unbounded();
 
+        for (slot, device) in gui.selected_devices.iter().enumerate() {
+            if device.is_some() {
+                device_send.send((slot, *device)).unwrap();
+            }
+        }
+
         let handle = std::thread::spawn(new_swi_thread(Thread {
             engine: engine.clone(),
             device_recv,
@@ -183,6 +227,17 @@ impl Inner {
         }
     }
 
+    fn bind_slot(&mut self, slot: usize, device: Option<Uuid>) {
+        let Some(selected) = self.gui().selected_devices.get_mut(slot)
+        else { return; };
+
+        *selected = device;
+
+        if let Inner::Init { device_send, .. } = self {
+            device_send.send((slot, device)).unwrap();
+        }
+    }
+
     fn update_gui(&mut self, _ctx: &egui::Context, _frame: &mut eframe::Frame, ui: &mut egui::Ui) {
         match self {
             Inner::Uninit { gui } => {
@@ -197,29 +252,11 @@ impl Inner {
             } => {
                 for i in 0..gui.selected_devices.len() {
                     egui::ComboBox::from_label(format!("Swi Controller {}", i + 1))
-                        .selected_text(
-                            gui.selected_devices[i]
-                                .and_then(|id| engine.get_device(&id))
-                                .map_or("[None]".to_owned(), |view| view.info().name.clone()),
-                        )
+                        .selected_text(super::slot_device_name(engine, gui.selected_devices[i]))
                         .show_ui(ui, |ui| {
-                            if ui
-                                .selectable_value(&mut gui.selected_devices[i], None, "[None]")
-                                .clicked()
-                            {
-                                device_send.send((i, None)).unwrap();
-                            }
-                            for entry in engine.devices() {
-                                if ui
-                                    .selectable_value(
-                                        &mut gui.selected_devices[i],
-                                        Some(*entry.uuid()),
-                                        &entry.info().name,
-                                    )
-                                    .clicked()
-                                {
-                                    device_send.send((i, Some(*entry.uuid()))).unwrap();
-                                }
+                            let selected = &mut gui.selected_devices[i];
+                            if let Some(device) = super::slot_device_options(ui, engine, selected) {
+                                device_send.send((i, device)).unwrap();
                             }
                         });
                 }
@@ -272,6 +309,11 @@ fn swi_thread(thread: Thread) -> Result<()> {
             recv(device_change) -> device_change => {
                 match device_change {
                     Ok((idx, Some(device_id))) => {
+                        // a reserved slot is bound again when its device connects
+                        if views[idx].as_ref().map(|view| *view.uuid()) == Some(device_id) {
+                            continue;
+                        }
+
                         views[idx] = engine.get_device(&device_id);
                         if let Some(view) = &mut views[idx] {
                             view.register_channel(update_send.clone());
@@ -400,8 +442,8 @@ impl SwiConn {
             }
         }
 
-        self.ctrls[num].left_stick = [data.left_stick_x, data.left_stick_y];
-        self.ctrls[num].right_stick = [data.right_stick_x, data.right_stick_y];
+        self.ctrls[num].left_stick = [data.left_stick_x, data.left_stick_y].map(narrow_axis);
+        self.ctrls[num].right_stick = [data.right_stick_x, data.right_stick_y].map(narrow_axis);
     }
 
     fn update_motion(&mut self, num: usize, data: &Motion) {
@@ -1,7 +1,9 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fs::{File, OpenOptions},
-    path::PathBuf,
+    io,
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -13,20 +15,37 @@ use std::{
 use anyhow::{Context, Result};
 use crossbeam_channel::{Receiver, Sender};
 use input_linux::{
-    AbsoluteAxis, AbsoluteInfo, AbsoluteInfoSetup, EventKind as ILEventKind, Key, UInputHandle,
+    AbsoluteAxis, AbsoluteInfo, AbsoluteInfoSetup, EventKind as ILEventKind, ForceFeedbackKind,
+    InputProperty, Key, RelativeAxis, UInputHandle,
 };
 use parking_lot::Mutex;
-use zinput_engine::device::component::controller::{Button, Controller};
+use zinput_engine::device::{
+    component::{
+        controller::{Button, Controller},
+        keyboard::{Key as KbKey, Keyboard},
+        mouse::{Mouse, MouseButton},
+        player_leds::PlayerLeds,
+        touch_pad::{TouchPad, TouchPadInfo},
+    },
+    feedback::{Feedback, Rumble},
+};
 use zinput_engine::{
     eframe::{self, egui},
     event::{Event, EventKind},
     plugin::{Plugin, PluginKind, PluginStatus},
     util::Uuid,
-    Engine,
+    DeviceRead, DeviceView, Engine,
 };
 
 const T: &'static str = "frontend:uinput";
 
+/// Maximum number of force feedback effects a game can upload to one joystick
+const FF_EFFECTS_MAX: u32 = 16;
+/// How often uinput is checked for force feedback requests
+const FF_POLL_INTERVAL: Duration = Duration::from_millis(8);
+
+const SLOTS: usize = 4;
+
 pub struct UInput {
     inner: Mutex<Inner>,
     signals: Arc<Signals>,
@@ -83,6 +102,18 @@ impl Plugin for UInput {
             _ => {}
         }
     }
+
+    fn slots(&self) -> usize {
+        self.inner.lock().selected_devices.len()
+    }
+
+    fn bind_slot(&self, slot: usize, device: Option<Uuid>) {
+        self.inner.lock().bind_slot(slot, device);
+    }
+
+    fn slot_device(&self, slot: usize) -> Option<Uuid> {
+        *self.inner.lock().selected_devices.get(slot)?
+    }
 }
 
 struct Inner {
@@ -90,7 +121,7 @@ struct Inner {
     device_recv: Receiver<(usize, Option<Uuid>)>,
     engine: Option<Arc<Engine>>,
 
-    selected_devices: [Option<Uuid>; 4],
+    selected_devices: [Option<Uuid>; SLOTS],
 
     status: Arc<Mutex<PluginStatus>>,
 
@@ -107,7 +138,7 @@ impl Inner {
             device_recv,
             engine: None,
 
-            selected_devices: [None; 4],
+            selected_devices: [None; SLOTS],
 
             status: Arc::new(Mutex::new(PluginStatus::Stopped)),
 
@@ -145,33 +176,23 @@ impl Inner {
         }
     }
 
+    fn bind_slot(&mut self, slot: usize, device: Option<Uuid>) {
+        let Some(selected) = self.selected_devices.get_mut(slot)
+        else { return; };
+
+        *selected = device;
+        self.device.send((slot, device)).unwrap();
+    }
+
     fn update_gui(&mut self, _ctx: &egui::Context, _frame: &mut eframe::Frame, ui: &mut egui::Ui) {
         if let Some(engine) = self.engine.clone() {
             for i in 0..self.selected_devices.len() {
                 egui::ComboBox::from_label(format!("UInput Controller {}", i + 1))
-                    .selected_text(
-                        self.selected_devices[i]
-                            .and_then(|id| engine.get_device_info(&id))
-                            .map_or("[None]".to_owned(), |dev| dev.name.clone()),
-                    )
+                    .selected_text(super::slot_device_name(&engine, self.selected_devices[i]))
                     .show_ui(ui, |ui| {
-                        if ui
-                            .selectable_value(&mut self.selected_devices[i], None, "[None]")
-                            .clicked()
-                        {
-                            self.device.send((i, None)).unwrap();
-                        }
-                        for device_ref in engine.devices() {
-                            if ui
-                                .selectable_value(
-                                    &mut self.selected_devices[i],
-                                    Some(*device_ref.id()),
-                                    &device_ref.name,
-                                )
-                                .clicked()
-                            {
-                                self.device.send((i, Some(*device_ref.id()))).unwrap();
-                            }
+                        let selected = &mut self.selected_devices[i];
+                        if let Some(device) = super::slot_device_options(ui, &engine, selected) {
+                            self.device.send((i, device)).unwrap();
                         }
                     });
             }
@@ -228,53 +249,58 @@ fn uinput_thread(thread: Thread) -> Result<()> {
 
     let uinput = init_uinput()?;
 
-    let mut joysticks = Vec::<Joystick>::new();
+    // indexed by slot
+    let mut joysticks = (0..SLOTS).map(|_| None).collect::<Vec<Option<Joystick>>>();
+
+    let ff_tick = crossbeam_channel::tick(FF_POLL_INTERVAL);
 
     loop {
         crossbeam_channel::select! {
             recv(device_change) -> device_change => {
                 match device_change {
                     Ok((idx, Some(device_id))) => {
-                        if let Some(joystick) = joysticks.get(idx) {
-                            let mut signals = signals.listen_update.lock();
-                            signals.remove(&joystick.device_id);
-                        }
+                        let Some(slot) = joysticks.get_mut(idx)
+                        else {
+                            log::error!(target: T, "tried to add controller to index {} but there are only {} slots", idx, SLOTS);
+                            continue;
+                        };
 
-                        if idx > joysticks.len() {
-                            log::error!(target: T, "tried to add controller to index {} but there are only {} joysticks", idx, joysticks.len());
+                        // a reserved slot is bound again when its device connects
+                        if slot.as_ref().map(|joystick| joystick.device_id) == Some(device_id) {
                             continue;
                         }
 
-                        let name = match engine.get_device_info(&device_id) {
-                            Some(device) => device.name.clone(),
+                        if let Some(joystick) = slot.take() {
+                            let mut signals = signals.listen_update.lock();
+                            signals.remove(&joystick.device_id);
+                        }
+
+                        let view = match engine.get_device(&device_id) {
+                            Some(view) => view,
                             None => {
-                                log::error!(target: T, "tried to add non-existent controller");
+                                log::info!(target: T, "controller {} is not connected yet", idx + 1);
                                 continue;
                             }
                         };
 
                         signals.listen_update.lock().insert(device_id);
 
-                        let uinput_device = OpenOptions::new()
-                            .read(true)
-                            .write(true)
-                            .open(&uinput)
-                            .context("failed to open uinput device")?;
-
-                        let uinput_device = UInputHandle::new(uinput_device);
-
-                        let joystick = Joystick::new(&name, device_id, uinput_device)?;
+                        // the device may not have player leds
+                        let _ = view.send_feedback(Feedback::PlayerLeds(PlayerLeds::player(idx)));
 
-                        joysticks.insert(idx, joystick);
+                        *slot = Some(Joystick::new(view, &uinput)?);
                     }
                     Ok((idx, None)) => {
-                        if let Some(joystick) = joysticks.get(idx) {
-                            let mut signals = signals.listen_update.lock();
-                            signals.remove(&joystick.device_id);
-
-                            joysticks.remove(idx);
-                        } else {
-                            log::error!(target: T, "tried to remove controller out of bounds at index {} when len is {}", idx, joysticks.len());
+                        match joysticks.get_mut(idx) {
+                            Some(slot) => {
+                                if let Some(joystick) = slot.take() {
+                                    let mut signals = signals.listen_update.lock();
+                                    signals.remove(&joystick.device_id);
+                                }
+                            }
+                            None => {
+                                log::error!(target: T, "tried to remove controller out of bounds at index {} when there are {} slots", idx, SLOTS);
+                            }
                         }
                     }
                     Err(_) => {
@@ -291,23 +317,38 @@ fn uinput_thread(thread: Thread) -> Result<()> {
                     }
                 };
 
-                for joystick in &joysticks {
+                for joystick in joysticks.iter().flatten() {
                     if joystick.device_id == uid {
-                        let device = match engine.get_device(&uid) {
-                            Some(device) => device,
-                            None => continue,
-                        };
-
+                        let device = joystick.view.device();
                         if let Some(controller) = device.controllers.get(0) {
                             joystick.update_controller(controller)?;
                         }
+                        if let Some(keyboard) = device.keyboards.get(0) {
+                            joystick.update_keyboard(keyboard)?;
+                        }
+                        if let Some(mouse) = joystick.take_mouse() {
+                            joystick.update_mouse(&mouse)?;
+                        }
+                        if let (Some(touch_pad), Some(output)) = (device.touch_pads.get(0), &joystick.touch_pad) {
+                            output.update(touch_pad)?;
+                        }
                     }
                 }
             }
-            default(Duration::from_secs(1)) => {
+            recv(ff_tick) -> _ => {
                 if stop.load(Ordering::Acquire) {
                     break;
                 }
+
+                for joystick in joysticks.iter_mut().flatten() {
+                    let Some(rumble) = joystick.poll_feedback()?
+                    else { continue; };
+
+                    if let Some(view) = engine.get_device(&joystick.device_id) {
+                        // the device may not support rumble
+                        let _ = view.send_feedback(Feedback::Rumble(rumble));
+                    }
+                }
             }
         }
     }
@@ -317,19 +358,27 @@ fn uinput_thread(thread: Thread) -> Result<()> {
 
 struct Joystick {
     device_id: Uuid,
+    view: DeviceView,
+    /// Every update of the device, as mouse motion is only reported by the update it happened in
+    updates: Receiver<DeviceRead>,
 
     uinput_device: UInputHandle<File>,
+    /// Touch pads are their own device, as a joystick with multitouch axes is not seen as a touch pad
+    touch_pad: Option<TouchPadDevice>,
+
+    effects: HashMap<i16, Rumble>,
 }
 
 impl Joystick {
-    fn new(name: &str, device_id: Uuid, uinput_device: UInputHandle<File>) -> Result<Self> {
+    fn new(mut view: DeviceView, uinput: &Path) -> Result<Self> {
         macro_rules! keybits {
             ($device:expr, $($key:expr),* $(,)?) => {
                 $($device.set_keybit($key)?;)*
             }
         }
 
-        let ud = uinput_device;
+        let info = view.info();
+        let ud = open_uinput(uinput)?;
 
         ud.set_evbit(ILEventKind::Key)?;
         keybits!(
@@ -361,10 +410,38 @@ impl Joystick {
         ud.set_absbit(AbsoluteAxis::Z)?;
         ud.set_absbit(AbsoluteAxis::RZ)?;
 
+        if let Some(keyboard) = info.keyboards.get(0) {
+            for key in KbKey::KEYS {
+                let usage = key.usage() as usize;
+                if keyboard.keys[usage / 64] & (1 << (usage % 64)) != 0 {
+                    ud.set_keybit(Key::from_code(linux_key(*key) as u16)?)?;
+                }
+            }
+        }
+
+        if let Some(mouse) = info.mouses.get(0) {
+            for button in MouseButton::BUTTONS {
+                if button.is_pressed(mouse.buttons) {
+                    ud.set_keybit(Key::from_code(linux_mouse_button(button) as u16)?)?;
+                }
+            }
+
+            ud.set_evbit(ILEventKind::Relative)?;
+            ud.set_relbit(RelativeAxis::X)?;
+            ud.set_relbit(RelativeAxis::Y)?;
+            if mouse.has_wheel {
+                ud.set_relbit(RelativeAxis::Wheel)?;
+                ud.set_relbit(RelativeAxis::HorizontalWheel)?;
+            }
+        }
+
+        ud.set_evbit(ILEventKind::ForceFeedback)?;
+        ud.set_ffbit(ForceFeedbackKind::Rumble)?;
+
         const DEFAULT_INFO: AbsoluteInfo = AbsoluteInfo {
             value: 0,
             minimum: 0,
-            maximum: 255,
+            maximum: u16::MAX as i32,
             fuzz: 0,
             flat: 0,
             resolution: 0,
@@ -372,8 +449,8 @@ impl Joystick {
 
         ud.create(
             &input_linux::InputId::default(),
-            name.as_bytes(),
-            0,
+            info.name.as_bytes(),
+            FF_EFFECTS_MAX,
             &[
                 AbsoluteInfoSetup {
                     axis: AbsoluteAxis::X,
@@ -403,13 +480,129 @@ impl Joystick {
         )
         .context("failed to create uinput device")?;
 
+        let touch_pad = match info.touch_pads.get(0) {
+            Some(touch_info) => Some(TouchPadDevice::new(
+                touch_info,
+                &format!("{} Touch Pad", info.name),
+                open_uinput(uinput)?,
+            )?),
+            None => None,
+        };
+
+        let device_id = *view.uuid();
+        let (sender, updates) = crossbeam_channel::unbounded();
+        view.subscribe_updates(sender);
+
         Ok(Joystick {
             device_id,
+            view,
+            updates,
 
             uinput_device: ud,
+            touch_pad,
+
+            effects: HashMap::new(),
         })
     }
 
+    /// Handles pending force feedback requests and returns the rumble that should now be playing
+    fn poll_feedback(&mut self) -> Result<Option<Rumble>> {
+        use input_linux::sys as ils;
+
+        // safety: input_event is plain old data
+        let mut events: [ils::input_event; 16] = unsafe { std::mem::zeroed() };
+        let mut rumble = None;
+
+        loop {
+            let read = match self.uinput_device.read(&mut events) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err).context("failed to read uinput events"),
+            };
+
+            for event in &events[..read] {
+                match (event.type_ as i32, event.code as i32) {
+                    (ils::EV_UINPUT, ils::UI_FF_UPLOAD) => {
+                        self.upload_effect(event.value as u32)?
+                    }
+                    (ils::EV_UINPUT, ils::UI_FF_ERASE) => self.erase_effect(event.value as u32)?,
+                    (ils::EV_FF, code) => {
+                        let Some(effect) = self.effects.get(&(code as i16))
+                        else { continue; };
+
+                        rumble = Some(if event.value > 0 {
+                            *effect
+                        } else {
+                            Rumble::OFF
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(rumble)
+    }
+
+    fn upload_effect(&mut self, request_id: u32) -> Result<()> {
+        use input_linux::sys as ils;
+
+        // safety: uinput_ff_upload is plain old data
+        let mut upload: ils::uinput_ff_upload = unsafe { std::mem::zeroed() };
+        upload.request_id = request_id;
+
+        self.uinput_device
+            .ff_upload_begin(&mut upload)
+            .context("failed to begin force feedback upload")?;
+
+        let effect = &upload.effect;
+        if effect.type_ as i32 == ils::FF_RUMBLE {
+            // safety: the kernel fills in the rumble variant for FF_RUMBLE effects
+            let data = unsafe { effect.u.rumble };
+
+            self.effects.insert(
+                effect.id,
+                Rumble {
+                    strong: (data.strong_magnitude >> 8) as u8,
+                    weak: (data.weak_magnitude >> 8) as u8,
+                    duration: match effect.replay.length {
+                        0 => None,
+                        length => Some(Duration::from_millis(length as u64)),
+                    },
+                },
+            );
+        } else {
+            upload.retval = -libc::EINVAL;
+        }
+
+        self.uinput_device
+            .ff_upload_end(&upload)
+            .context("failed to end force feedback upload")?;
+
+        Ok(())
+    }
+
+    fn erase_effect(&mut self, request_id: u32) -> Result<()> {
+        use input_linux::sys as ils;
+
+        // safety: uinput_ff_erase is plain old data
+        let mut erase: ils::uinput_ff_erase = unsafe { std::mem::zeroed() };
+        erase.request_id = request_id;
+
+        self.uinput_device
+            .ff_erase_begin(&mut erase)
+            .context("failed to begin force feedback erase")?;
+
+        self.effects.remove(&(erase.effect_id as i16));
+
+        self.uinput_device
+            .ff_erase_end(&erase)
+            .context("failed to end force feedback erase")?;
+
+        Ok(())
+    }
+
     fn update_controller(&self, data: &Controller) -> Result<()> {
         use input_linux::sys as ils;
 
@@ -464,21 +657,333 @@ impl Joystick {
                 Button::Home   => ils::BTN_MODE,
             }
             analogs {
-                data.left_stick_x          => ils::ABS_X,
-                (255 - data.left_stick_y)  => ils::ABS_Y,
-                data.right_stick_x         => ils::ABS_RX,
-                (255 - data.right_stick_y) => ils::ABS_RY,
-                data.l2_analog             => ils::ABS_Z,
-                data.r2_analog             => ils::ABS_RZ,
+                data.left_stick_x               => ils::ABS_X,
+                (u16::MAX - data.left_stick_y)  => ils::ABS_Y,
+                data.right_stick_x              => ils::ABS_RX,
+                (u16::MAX - data.right_stick_y) => ils::ABS_RY,
+                data.l2_analog                  => ils::ABS_Z,
+                data.r2_analog                  => ils::ABS_RZ,
             }
         };
 
-        let mut written = 0;
-        while written < events.len() {
-            written += self.uinput_device.write(&events[written..])?;
+        write_events(&self.uinput_device, &events)
+    }
+
+    fn update_keyboard(&self, data: &Keyboard) -> Result<()> {
+        use input_linux::sys as ils;
+
+        let events = KbKey::KEYS
+            .iter()
+            .map(|key| event(ils::EV_KEY, linux_key(*key), data.is_pressed(*key) as i32))
+            .chain([event(ils::EV_SYN, ils::SYN_REPORT, 0)])
+            .collect::<Vec<_>>();
+
+        write_events(&self.uinput_device, &events)
+    }
+
+    /// The mouse motion of every update since the last call, with the newest buttons
+    fn take_mouse(&self) -> Option<Mouse> {
+        let mut total: Option<Mouse> = None;
+
+        for update in self.updates.try_iter() {
+            let Some(mouse) = update.mouses.get(0)
+            else { continue; };
+
+            total = Some(match total {
+                Some(total) => Mouse {
+                    move_x: total.move_x.saturating_add(mouse.move_x),
+                    move_y: total.move_y.saturating_add(mouse.move_y),
+                    wheel_x: total.wheel_x.saturating_add(mouse.wheel_x),
+                    wheel_y: total.wheel_y.saturating_add(mouse.wheel_y),
+                    buttons: mouse.buttons,
+                },
+                None => *mouse,
+            });
         }
 
-        Ok(())
+        total
+    }
+
+    fn update_mouse(&self, data: &Mouse) -> Result<()> {
+        use input_linux::sys as ils;
+
+        let mut events = MouseButton::BUTTONS
+            .iter()
+            .map(|button| {
+                event(
+                    ils::EV_KEY,
+                    linux_mouse_button(*button),
+                    button.is_pressed(data.buttons) as i32,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // relative events with a value of 0 are ignored by the kernel
+        events.extend([
+            event(ils::EV_REL, ils::REL_X, data.move_x),
+            event(ils::EV_REL, ils::REL_Y, data.move_y),
+            event(ils::EV_REL, ils::REL_HWHEEL, data.wheel_x),
+            event(ils::EV_REL, ils::REL_WHEEL, data.wheel_y),
+            event(ils::EV_SYN, ils::SYN_REPORT, 0),
+        ]);
+
+        write_events(&self.uinput_device, &events)
+    }
+}
+
+struct TouchPadDevice {
+    uinput_device: UInputHandle<File>,
+    max_touches: usize,
+}
+
+impl TouchPadDevice {
+    fn new(info: &TouchPadInfo, name: &str, ud: UInputHandle<File>) -> Result<Self> {
+        use input_linux::sys as ils;
+
+        let max_touches = info.max_touches.max(1) as usize;
+
+        ud.set_propbit(InputProperty::Pointer)?;
+        if info.is_button {
+            ud.set_propbit(InputProperty::ButtonPad)?;
+        }
+
+        // one tool key for each number of fingers the pad tracks
+        ud.set_evbit(ILEventKind::Key)?;
+        for key in [
+            ils::BTN_LEFT,
+            ils::BTN_TOUCH,
+            ils::BTN_TOOL_FINGER,
+            ils::BTN_TOOL_DOUBLETAP,
+            ils::BTN_TOOL_TRIPLETAP,
+            ils::BTN_TOOL_QUADTAP,
+        ]
+        .iter()
+        .take(max_touches + 2)
+        {
+            ud.set_keybit(Key::from_code(*key as u16)?)?;
+        }
+
+        let axis_info = |maximum: i32| AbsoluteInfo {
+            value: 0,
+            minimum: 0,
+            maximum,
+            fuzz: 0,
+            flat: 0,
+            resolution: 0,
+        };
+
+        let [width, height] = info.resolution.map(|max| max as i32);
+        let axes = [
+            (ils::ABS_X, axis_info(width)),
+            (ils::ABS_Y, axis_info(height)),
+            (ils::ABS_MT_SLOT, axis_info(max_touches as i32 - 1)),
+            (ils::ABS_MT_TRACKING_ID, axis_info(u8::MAX as i32)),
+            (ils::ABS_MT_POSITION_X, axis_info(width)),
+            (ils::ABS_MT_POSITION_Y, axis_info(height)),
+        ]
+        .into_iter()
+        .map(|(axis, info)| {
+            Ok(AbsoluteInfoSetup {
+                axis: AbsoluteAxis::from_code(axis as u16)?,
+                info,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+        ud.set_evbit(ILEventKind::Absolute)?;
+        for setup in &axes {
+            ud.set_absbit(setup.axis)?;
+        }
+
+        ud.create(&input_linux::InputId::default(), name.as_bytes(), 0, &axes)
+            .context("failed to create uinput touch pad")?;
+
+        Ok(TouchPadDevice {
+            uinput_device: ud,
+            max_touches,
+        })
+    }
+
+    fn update(&self, data: &TouchPad) -> Result<()> {
+        use input_linux::sys as ils;
+
+        let mut events = Vec::new();
+
+        for (slot, touch) in data.touches.iter().take(self.max_touches).enumerate() {
+            events.push(event(ils::EV_ABS, ils::ABS_MT_SLOT, slot as i32));
+            if touch.active {
+                events.extend([
+                    event(ils::EV_ABS, ils::ABS_MT_TRACKING_ID, touch.id as i32),
+                    event(ils::EV_ABS, ils::ABS_MT_POSITION_X, touch.x as i32),
+                    event(ils::EV_ABS, ils::ABS_MT_POSITION_Y, touch.y as i32),
+                ]);
+            } else {
+                events.push(event(ils::EV_ABS, ils::ABS_MT_TRACKING_ID, -1));
+            }
+        }
+
+        // single touch events for programs that don't read slots
+        if let Some(first) = data.first() {
+            events.extend([
+                event(ils::EV_ABS, ils::ABS_X, first.x as i32),
+                event(ils::EV_ABS, ils::ABS_Y, first.y as i32),
+            ]);
+        }
+
+        let count = data.active().count();
+        events.extend([
+            event(ils::EV_KEY, ils::BTN_LEFT, data.pressed as i32),
+            event(ils::EV_KEY, ils::BTN_TOUCH, (count > 0) as i32),
+            event(ils::EV_KEY, ils::BTN_TOOL_FINGER, (count == 1) as i32),
+        ]);
+        for (key, fingers) in [
+            (ils::BTN_TOOL_DOUBLETAP, 2),
+            (ils::BTN_TOOL_TRIPLETAP, 3),
+            (ils::BTN_TOOL_QUADTAP, 4),
+        ] {
+            if fingers <= self.max_touches {
+                events.push(event(ils::EV_KEY, key, (count == fingers) as i32));
+            }
+        }
+        events.push(event(ils::EV_SYN, ils::SYN_REPORT, 0));
+
+        write_events(&self.uinput_device, &events)
+    }
+}
+
+fn write_events(uinput_device: &UInputHandle<File>, events: &[input_linux::sys::input_event]) -> Result<()> {
+    let mut written = 0;
+    while written < events.len() {
+        written += uinput_device.write(&events[written..])?;
+    }
+
+    Ok(())
+}
+
+fn open_uinput(uinput: &Path) -> Result<UInputHandle<File>> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(uinput)
+        .context("failed to open uinput device")?;
+
+    Ok(UInputHandle::new(file))
+}
+
+fn event(kind: i32, code: i32, value: i32) -> input_linux::sys::input_event {
+    use input_linux::sys as ils;
+
+    ils::input_event {
+        time: ils::timeval { tv_sec: 0, tv_usec: 0 },
+        type_: kind as _,
+        code: code as _,
+        value,
+    }
+}
+
+fn linux_mouse_button(button: MouseButton) -> i32 {
+    use input_linux::sys as ils;
+
+    match button {
+        MouseButton::Left => ils::BTN_LEFT,
+        MouseButton::Right => ils::BTN_RIGHT,
+        MouseButton::Middle => ils::BTN_MIDDLE,
+        MouseButton::Back => ils::BTN_SIDE,
+        MouseButton::Forward => ils::BTN_EXTRA,
+    }
+}
+
+fn linux_key(key: KbKey) -> i32 {
+    use input_linux::sys as ils;
+
+    match key {
+        KbKey::A => ils::KEY_A,
+        KbKey::B => ils::KEY_B,
+        KbKey::C => ils::KEY_C,
+        KbKey::D => ils::KEY_D,
+        KbKey::E => ils::KEY_E,
+        KbKey::F => ils::KEY_F,
+        KbKey::G => ils::KEY_G,
+        KbKey::H => ils::KEY_H,
+        KbKey::I => ils::KEY_I,
+        KbKey::J => ils::KEY_J,
+        KbKey::K => ils::KEY_K,
+        KbKey::L => ils::KEY_L,
+        KbKey::M => ils::KEY_M,
+        KbKey::N => ils::KEY_N,
+        KbKey::O => ils::KEY_O,
+        KbKey::P => ils::KEY_P,
+        KbKey::Q => ils::KEY_Q,
+        KbKey::R => ils::KEY_R,
+        KbKey::S => ils::KEY_S,
+        KbKey::T => ils::KEY_T,
+        KbKey::U => ils::KEY_U,
+        KbKey::V => ils::KEY_V,
+        KbKey::W => ils::KEY_W,
+        KbKey::X => ils::KEY_X,
+        KbKey::Y => ils::KEY_Y,
+        KbKey::Z => ils::KEY_Z,
+        KbKey::Num1 => ils::KEY_1,
+        KbKey::Num2 => ils::KEY_2,
+        KbKey::Num3 => ils::KEY_3,
+        KbKey::Num4 => ils::KEY_4,
+        KbKey::Num5 => ils::KEY_5,
+        KbKey::Num6 => ils::KEY_6,
+        KbKey::Num7 => ils::KEY_7,
+        KbKey::Num8 => ils::KEY_8,
+        KbKey::Num9 => ils::KEY_9,
+        KbKey::Num0 => ils::KEY_0,
+        KbKey::Enter => ils::KEY_ENTER,
+        KbKey::Escape => ils::KEY_ESC,
+        KbKey::Backspace => ils::KEY_BACKSPACE,
+        KbKey::Tab => ils::KEY_TAB,
+        KbKey::Space => ils::KEY_SPACE,
+        KbKey::Minus => ils::KEY_MINUS,
+        KbKey::Equal => ils::KEY_EQUAL,
+        KbKey::LeftBracket => ils::KEY_LEFTBRACE,
+        KbKey::RightBracket => ils::KEY_RIGHTBRACE,
+        KbKey::Backslash => ils::KEY_BACKSLASH,
+        KbKey::Semicolon => ils::KEY_SEMICOLON,
+        KbKey::Apostrophe => ils::KEY_APOSTROPHE,
+        KbKey::Grave => ils::KEY_GRAVE,
+        KbKey::Comma => ils::KEY_COMMA,
+        KbKey::Period => ils::KEY_DOT,
+        KbKey::Slash => ils::KEY_SLASH,
+        KbKey::CapsLock => ils::KEY_CAPSLOCK,
+        KbKey::F1 => ils::KEY_F1,
+        KbKey::F2 => ils::KEY_F2,
+        KbKey::F3 => ils::KEY_F3,
+        KbKey::F4 => ils::KEY_F4,
+        KbKey::F5 => ils::KEY_F5,
+        KbKey::F6 => ils::KEY_F6,
+        KbKey::F7 => ils::KEY_F7,
+        KbKey::F8 => ils::KEY_F8,
+        KbKey::F9 => ils::KEY_F9,
+        KbKey::F10 => ils::KEY_F10,
+        KbKey::F11 => ils::KEY_F11,
+        KbKey::F12 => ils::KEY_F12,
+        KbKey::PrintScreen => ils::KEY_SYSRQ,
+        KbKey::ScrollLock => ils::KEY_SCROLLLOCK,
+        KbKey::Pause => ils::KEY_PAUSE,
+        KbKey::Insert => ils::KEY_INSERT,
+        KbKey::Home => ils::KEY_HOME,
+        KbKey::PageUp => ils::KEY_PAGEUP,
+        KbKey::Delete => ils::KEY_DELETE,
+        KbKey::End => ils::KEY_END,
+        KbKey::PageDown => ils::KEY_PAGEDOWN,
+        KbKey::Right => ils::KEY_RIGHT,
+        KbKey::Left => ils::KEY_LEFT,
+        KbKey::Down => ils::KEY_DOWN,
+        KbKey::Up => ils::KEY_UP,
+        KbKey::LeftCtrl => ils::KEY_LEFTCTRL,
+        KbKey::LeftShift => ils::KEY_LEFTSHIFT,
+        KbKey::LeftAlt => ils::KEY_LEFTALT,
+        KbKey::LeftMeta => ils::KEY_LEFTMETA,
+        KbKey::RightCtrl => ils::KEY_RIGHTCTRL,
+        KbKey::RightShift => ils::KEY_RIGHTSHIFT,
+        KbKey::RightAlt => ils::KEY_RIGHTALT,
+        KbKey::RightMeta => ils::KEY_RIGHTMETA,
     }
 }
 
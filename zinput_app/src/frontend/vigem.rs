@@ -14,7 +14,13 @@ use vigem_client::{
     Client, DS4Report, DualShock4Wired, TargetId, XButtons, XGamepad, Xbox360Wired,
 };
 use zinput_engine::{
-    device::component::controller::{Button, Controller},
+    device::{
+        component::{
+            controller::{narrow_axis, signed_axis, Button, Controller},
+            player_leds::PlayerLeds,
+        },
+        feedback::{Feedback, Rumble},
+    },
     eframe::{self, egui},
     plugin::{Plugin, PluginKind, PluginStatus},
     util::Uuid,
@@ -23,6 +29,11 @@ use zinput_engine::{
 
 const T: &'static str = "frontend:vigem";
 
+/// Slots below this are Xbox 360 controllers, the ones after are DualShock 4 controllers.
+/// Every slot has its own virtual controller, which is plugged in while a device is bound to it.
+const XBOX_SLOTS: usize = 4;
+const DS4_SLOTS: usize = 4;
+
 pub struct Vigem {
     inner: Mutex<Inner>,
 }
@@ -59,6 +70,18 @@ impl Plugin for Vigem {
     fn update_gui(&self, ctx: &egui::Context, frame: &mut eframe::Frame, ui: &mut egui::Ui) {
         self.inner.lock().update_gui(ctx, frame, ui)
     }
+
+    fn slots(&self) -> usize {
+        XBOX_SLOTS + DS4_SLOTS
+    }
+
+    fn bind_slot(&self, slot: usize, device: Option<Uuid>) {
+        self.inner.lock().bind_slot(slot, device);
+    }
+
+    fn slot_device(&self, slot: usize) -> Option<Uuid> {
+        self.inner.lock().slot_device(slot)
+    }
 }
 
 enum Inner {
@@ -69,11 +92,11 @@ enum Inner {
         stop: Arc<AtomicBool>,
         handle: JoinHandle<()>,
 
-        xbox_send: Sender<Vec<Uuid>>,
-        selected_xbox: Vec<Uuid>,
+        xbox_send: Sender<Vec<Option<Uuid>>>,
+        selected_xbox: Vec<Option<Uuid>>,
 
-        ds4_send: Sender<Vec<Uuid>>,
-        selected_ds4: Vec<Uuid>,
+        ds4_send: Sender<Vec<Option<Uuid>>>,
+        selected_ds4: Vec<Option<Uuid>>,
     },
 }
 
@@ -110,10 +133,10 @@ impl Inner {
             handle,
 
             xbox_send,
-            selected_xbox: Vec::new(),
+            selected_xbox: vec![None; XBOX_SLOTS],
 
             ds4_send,
-            selected_ds4: Vec::new(),
+            selected_ds4: vec![None; DS4_SLOTS],
         };
     }
 
@@ -145,145 +168,98 @@ impl Inner {
         }
     }
 
-    fn update_gui(&mut self, _ctx: &egui::Context, _frame: &mut eframe::Frame, ui: &mut egui::Ui) {
+    fn bind_slot(&mut self, slot: usize, device: Option<Uuid>) {
         let Inner::Init {
-            engine,
             xbox_send,
             selected_xbox,
             ds4_send,
             selected_ds4,
             ..
         } = self
-        else { return };
+        else {
+            log::warn!(target: T, "cannot bind slot {} while stopped", slot + 1);
+            return;
+        };
 
-        #[derive(PartialEq, Eq)]
-        enum Action {
-            Remove(usize),
-            Change(usize, Uuid),
-            Add(Uuid),
-        }
+        let (send, selected, index) = if slot < XBOX_SLOTS {
+            (xbox_send, selected_xbox, slot)
+        } else {
+            (ds4_send, selected_ds4, slot - XBOX_SLOTS)
+        };
 
-        let mut action = None;
+        let Some(selected_slot) = selected.get_mut(index)
+        else {
+            log::warn!(target: T, "slot {} does not exist", slot + 1);
+            return;
+        };
 
-        // XBox Controllers
+        // binding the same device again retries a device that was missing
+        *selected_slot = device;
+        send.send(selected.clone()).unwrap();
+    }
 
-        for i in 0..selected_xbox.len() {
-            if action.is_some() {
-                break;
-            }
-            egui::ComboBox::from_label(format!("ViGEm XBox Controller {}", i + 1))
-                .selected_text(match engine.get_device(&selected_xbox[i]) {
-                    Some(view) => view.info().name.clone(),
-                    None => {
-                        action = Some(Action::Remove(i));
-                        break;
-                    }
-                })
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut action, Some(Action::Remove(i)), "[None]");
-                    for entry in engine.devices() {
-                        ui.selectable_value(
-                            &mut action,
-                            Some(Action::Change(i, *entry.uuid())),
-                            &entry.info().name,
-                        );
-                    }
-                });
-        }
+    fn slot_device(&self, slot: usize) -> Option<Uuid> {
+        let Inner::Init {
+            selected_xbox,
+            selected_ds4,
+            ..
+        } = self
+        else { return None; };
 
-        if selected_xbox.len() < 4 && action.is_none() {
-            egui::ComboBox::from_label(format!(
-                "ViGEm XBox Controller {}",
-                selected_xbox.len() + 1
-            ))
-            .selected_text("[None]")
-            .show_ui(ui, |ui| {
-                ui.selectable_value(&mut action, None, "[None]");
-                for entry in engine.devices() {
-                    ui.selectable_value(
-                        &mut action,
-                        Some(Action::Add(*entry.uuid())),
-                        &entry.info().name,
-                    );
-                }
-            });
+        if slot < XBOX_SLOTS {
+            selected_xbox.get(slot).copied().flatten()
+        } else {
+            selected_ds4.get(slot - XBOX_SLOTS).copied().flatten()
         }
+    }
 
-        if let Some(action) = action {
-            match action {
-                Action::Remove(i) => {
-                    selected_xbox.remove(i);
-                }
-                Action::Change(i, id) => selected_xbox[i] = id,
-                Action::Add(id) => selected_xbox.push(id),
-            }
+    fn update_gui(&mut self, _ctx: &egui::Context, _frame: &mut eframe::Frame, ui: &mut egui::Ui) {
+        let Inner::Init {
+            engine,
+            xbox_send,
+            selected_xbox,
+            ds4_send,
+            selected_ds4,
+            ..
+        } = self
+        else { return };
 
+        if Self::slot_selects(ui, engine, "ViGEm XBox Controller", selected_xbox) {
             xbox_send.send(selected_xbox.clone()).unwrap();
         }
 
         ui.separator();
 
-        // DS4 Controllers
-
-        action = None;
-
-        for i in 0..selected_ds4.len() {
-            if action.is_some() {
-                break;
-            }
-            egui::ComboBox::from_label(format!("ViGEm DS4 Controller {}", i + 1))
-                .selected_text(match engine.get_device(&selected_ds4[i]) {
-                    Some(view) => view.info().name.clone(),
-                    None => {
-                        action = Some(Action::Remove(i));
-                        break;
-                    }
-                })
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut action, Some(Action::Remove(i)), "[None]");
-                    for entry in engine.devices() {
-                        ui.selectable_value(
-                            &mut action,
-                            Some(Action::Change(i, *entry.uuid())),
-                            &entry.info().name,
-                        );
-                    }
-                });
+        if Self::slot_selects(ui, engine, "ViGEm DS4 Controller", selected_ds4) {
+            ds4_send.send(selected_ds4.clone()).unwrap();
         }
+    }
 
-        if selected_ds4.len() < u16::MAX as usize - 1 && action.is_none() {
-            egui::ComboBox::from_label(format!("ViGEm DS4 Controller {}", selected_ds4.len() + 1))
-                .selected_text("[None]")
+    /// Returns whether a device was picked for any slot
+    fn slot_selects(
+        ui: &mut egui::Ui,
+        engine: &Engine,
+        label: &str,
+        selected: &mut [Option<Uuid>],
+    ) -> bool {
+        let mut changed = false;
+
+        for (i, slot) in selected.iter_mut().enumerate() {
+            egui::ComboBox::from_label(format!("{label} {}", i + 1))
+                .selected_text(super::slot_device_name(engine, *slot))
                 .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut action, None, "[None]");
-                    for entry in engine.devices() {
-                        ui.selectable_value(
-                            &mut action,
-                            Some(Action::Add(*entry.uuid())),
-                            &entry.info().name,
-                        );
-                    }
+                    changed |= super::slot_device_options(ui, engine, slot).is_some();
                 });
         }
 
-        if let Some(action) = action {
-            match action {
-                Action::Remove(i) => {
-                    selected_ds4.remove(i);
-                }
-                Action::Change(i, id) => selected_ds4[i] = id,
-                Action::Add(id) => selected_ds4.push(id),
-            }
-
-            ds4_send.send(selected_ds4.clone()).unwrap();
-        }
+        changed
     }
 }
 
 struct Thread {
     engine: Arc<Engine>,
-    xbox_recv: Receiver<Vec<Uuid>>,
-    ds4_recv: Receiver<Vec<Uuid>>,
+    xbox_recv: Receiver<Vec<Option<Uuid>>>,
+    ds4_recv: Receiver<Vec<Option<Uuid>>>,
     status: Arc<Mutex<PluginStatus>>,
     stop: Arc<AtomicBool>,
 }
@@ -317,8 +293,13 @@ fn vigem_thread(thread: Thread) -> Result<()> {
 
     let (update_send, update_recv) = crossbeam_channel::bounded(10);
 
-    let mut ds4_targets = Vec::<(DeviceView, DualShock4Wired<_>)>::new();
-    let mut xbox_targets = Vec::<(DeviceView, Xbox360Wired<_>)>::new();
+    // indexed by slot, so unbinding one slot leaves the others on their virtual controllers
+    let mut ds4_targets = (0..DS4_SLOTS)
+        .map(|_| None)
+        .collect::<Vec<Option<(DeviceView, DualShock4Wired<_>)>>>();
+    let mut xbox_targets = (0..XBOX_SLOTS)
+        .map(|_| None)
+        .collect::<Vec<Option<(DeviceView, Xbox360Wired<_>)>>>();
 
     loop {
         crossbeam_channel::select! {
@@ -326,57 +307,82 @@ fn vigem_thread(thread: Thread) -> Result<()> {
                 let Ok(xbox_ids) = xbox_recv
                 else { return Ok(()); }; // Sender dropped which means plugin is uninitialized
 
-                if xbox_ids.len() < xbox_targets.len() {
-                    for (_, xbox_target) in &mut xbox_targets[xbox_ids.len()..] {
-                        xbox_target.unplug().context("failed to unplug xbox target")?;
+                for (slot, (id, target)) in xbox_ids.into_iter().zip(&mut xbox_targets).enumerate() {
+                    if target.as_ref().map(|(view, _)| *view.uuid()) == id {
+                        continue;
                     }
-                    for _ in xbox_ids.len()..xbox_targets.len() {
-                        xbox_targets.pop();
-                    }
-                } else if xbox_ids.len() > xbox_targets.len() {
-                    for i in xbox_targets.len()..xbox_ids.len() {
-                        let mut xbox = Xbox360Wired::new(&vigem, TargetId::XBOX360_WIRED);
-                        xbox.plugin().context("failed to plugin xbox target")?;
-                        xbox.wait_ready().context("xbox target failed to ready")?;
-                        xbox_targets.push((match engine.get_device(&xbox_ids[i]) {
-                            Some(mut dev) => {
-                                dev.register_channel(update_send.clone());
-                                dev
-                            }
-                            None => anyhow::bail!("tried to get device with invalid uuid for xbox"),
-                        }, xbox));
+
+                    if let Some((_, mut xbox)) = target.take() {
+                        xbox.unplug().context("failed to unplug xbox target")?;
                     }
+
+                    let Some(id) = id else { continue };
+                    let Some(mut view) = engine.get_device(&id)
+                    else {
+                        log::warn!(target: T, "tried to bind a missing device to xbox slot {}", slot + 1);
+                        continue;
+                    };
+                    view.register_channel(update_send.clone());
+
+                    let mut xbox = Xbox360Wired::new(&vigem, TargetId::XBOX360_WIRED);
+                    xbox.plugin().context("failed to plugin xbox target")?;
+                    xbox.wait_ready().context("xbox target failed to ready")?;
+
+                    let notification = xbox.request_notification().context("failed to request xbox notifications")?;
+                    let feedback_view = view.clone();
+                    let mut led_number = None;
+                    // the thread ends when the target is unplugged
+                    notification.spawn_thread(move |_, data| {
+                        // the device may not support rumble
+                        let _ = feedback_view.send_feedback(Feedback::Rumble(Rumble {
+                            strong: data.large_motor,
+                            weak: data.small_motor,
+                            duration: None,
+                        }));
+
+                        if led_number != Some(data.led_number) {
+                            led_number = Some(data.led_number);
+                            // the device may not have player leds
+                            let _ = feedback_view.send_feedback(Feedback::PlayerLeds(
+                                PlayerLeds::player(data.led_number as usize),
+                            ));
+                        }
+                    });
+
+                    *target = Some((view, xbox));
                 }
             },
             recv(ds4_recv) -> ds4_recv => {
                 let Ok(ds4_ids) = ds4_recv
                 else { return Ok(()); }; // Sender dropped which means plugin is uninitialized
 
-                if ds4_ids.len() < ds4_targets.len() {
-                    for (_, ds4_target) in &mut ds4_targets[ds4_ids.len()..] {
-                        ds4_target.unplug().context("failed to unplug ds4 target")?;
-                    }
-                    for _ in ds4_ids.len()..ds4_targets.len() {
-                        ds4_targets.pop();
+                for (slot, (id, target)) in ds4_ids.into_iter().zip(&mut ds4_targets).enumerate() {
+                    if target.as_ref().map(|(view, _)| *view.uuid()) == id {
+                        continue;
                     }
-                } else if ds4_ids.len() > ds4_targets.len() {
-                    for i in ds4_targets.len()..ds4_ids.len() {
-                        let mut ds4 = DualShock4Wired::new(&vigem, TargetId::DUALSHOCK4_WIRED);
-                        ds4.plugin().context("failed to plugin ds4 target")?;
-                        ds4.wait_ready().context("ds4 target failed to ready")?;
-                        ds4_targets.push((match engine.get_device(&ds4_ids[i]) {
-                            Some(mut dev) => {
-                                dev.register_channel(update_send.clone());
-                                dev
-                            }
-                            None => anyhow::bail!("tried to get device with invalid uuid for ds4"),
-                        }, ds4));
+
+                    if let Some((_, mut ds4)) = target.take() {
+                        ds4.unplug().context("failed to unplug ds4 target")?;
                     }
+
+                    let Some(id) = id else { continue };
+                    let Some(mut view) = engine.get_device(&id)
+                    else {
+                        log::warn!(target: T, "tried to bind a missing device to ds4 slot {}", slot + 1);
+                        continue;
+                    };
+                    view.register_channel(update_send.clone());
+
+                    let mut ds4 = DualShock4Wired::new(&vigem, TargetId::DUALSHOCK4_WIRED);
+                    ds4.plugin().context("failed to plugin ds4 target")?;
+                    ds4.wait_ready().context("ds4 target failed to ready")?;
+
+                    *target = Some((view, ds4));
                 }
             },
             recv(update_recv) -> rid => {
                 if let Ok(rid) = rid {
-                    for (view, target) in &mut xbox_targets {
+                    for (view, target) in xbox_targets.iter_mut().flatten() {
                         if view.uuid() != &rid { continue; }
                         let device = view.device();
                         let controller = match device.controllers.get(0) {
@@ -387,7 +393,7 @@ fn vigem_thread(thread: Thread) -> Result<()> {
                         update_xbox_target(target, controller).with_context(|| "failed to update xbox target")?;
                     }
 
-                    for (view, target) in &mut ds4_targets {
+                    for (view, target) in ds4_targets.iter_mut().flatten() {
                         if view.uuid() != &rid { continue; }
                         let device = view.device();
                         let controller = match device.controllers.get(0) {
@@ -440,17 +446,17 @@ fn update_xbox_target(target: &mut Xbox360Wired<&Client>, data: &Controller) ->
         left_trigger: if Button::L2.is_pressed(data.buttons) {
             255
         } else {
-            data.l2_analog
+            narrow_axis(data.l2_analog)
         },
         right_trigger: if Button::R2.is_pressed(data.buttons) {
             255
         } else {
-            data.r2_analog
+            narrow_axis(data.r2_analog)
         },
-        thumb_lx: (((data.left_stick_x as i32) - 128) * 256) as i16,
-        thumb_ly: (((data.left_stick_y as i32) - 128) * 256) as i16,
-        thumb_rx: (((data.right_stick_x as i32) - 128) * 256) as i16,
-        thumb_ry: (((data.right_stick_y as i32) - 128) * 256) as i16,
+        thumb_lx: signed_axis(data.left_stick_x),
+        thumb_ly: signed_axis(data.left_stick_y),
+        thumb_rx: signed_axis(data.right_stick_x),
+        thumb_ry: signed_axis(data.right_stick_y),
     })?;
 
     Ok(())
@@ -537,17 +543,17 @@ fn update_ds4_target(target: &mut DualShock4Wired<&Client>, data: &Controller) -
         trigger_l: if Button::L2.is_pressed(data.buttons) {
             255
         } else {
-            data.l2_analog
+            narrow_axis(data.l2_analog)
         },
         trigger_r: if Button::R2.is_pressed(data.buttons) {
             255
         } else {
-            data.r2_analog
+            narrow_axis(data.r2_analog)
         },
-        thumb_lx: data.left_stick_x,
-        thumb_ly: data.left_stick_y,
-        thumb_rx: data.right_stick_x,
-        thumb_ry: data.right_stick_y,
+        thumb_lx: narrow_axis(data.left_stick_x),
+        thumb_ly: narrow_axis(data.left_stick_y),
+        thumb_rx: narrow_axis(data.right_stick_x),
+        thumb_ry: narrow_axis(data.right_stick_y),
     })?;
 
     Ok(())
@@ -0,0 +1,115 @@
+use zinput_engine::{
+    device::component::{
+        buttons::{AnalogTarget, ButtonRoute, ButtonsConfig, RouteTarget},
+        controller::Button,
+    },
+    eframe::egui,
+    DeviceView,
+};
+
+use super::ComponentView;
+
+pub struct ButtonsView {
+    view: DeviceView,
+    index: usize,
+}
+
+impl ButtonsView {
+    pub fn new(view: DeviceView, index: usize) -> Self {
+        ButtonsView { view, index }
+    }
+
+    fn remap_select(ui: &mut egui::Ui, bit: usize, available: u64, cfg: &mut ButtonsConfig) {
+        egui::ComboBox::new(format!("devices/buttons/remap/{bit}"), "")
+            .selected_text(format!("Button {}", cfg.remap[bit]))
+            .show_ui(ui, |ui| {
+                for new_bit in (0..64).filter(|new_bit| available & (1 << new_bit) != 0) {
+                    ui.selectable_value(&mut cfg.remap[bit], new_bit, format!("Button {new_bit}"));
+                }
+            });
+    }
+
+    /// Routes are keyed by the remapped bit, so this edits the route of `cfg.remap[bit]`
+    fn route_select(ui: &mut egui::Ui, bit: usize, cfg: &mut ButtonsConfig) {
+        let from = cfg.remap[bit];
+        let route = cfg.routes.iter().position(|route| route.bit == from);
+
+        let name = |target: Option<RouteTarget>| match target {
+            None => "[None]".to_owned(),
+            Some(RouteTarget::Button(button)) => Button::try_from_bit(button)
+                .map_or(format!("Bit {button}"), |button| format!("{button}")),
+            Some(RouteTarget::Analog(analog)) => format!("{analog}"),
+        };
+
+        let mut target = route.map(|i| cfg.routes[i].target);
+        let selected = target;
+
+        egui::ComboBox::new(format!("devices/buttons/route/{bit}"), "")
+            .selected_text(name(selected))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut target, None, name(None));
+                for button in Button::BUTTONS {
+                    let new_target = Some(RouteTarget::Button(button.bit() as u8));
+                    ui.selectable_value(&mut target, new_target, name(new_target));
+                }
+                for analog in AnalogTarget::TARGETS {
+                    let new_target = Some(RouteTarget::Analog(analog));
+                    ui.selectable_value(&mut target, new_target, name(new_target));
+                }
+            });
+
+        if target == selected {
+            return;
+        }
+
+        match (route, target) {
+            (Some(i), Some(target)) => cfg.routes[i].target = target,
+            (Some(i), None) => {
+                cfg.routes.remove(i);
+            }
+            (None, Some(target)) => cfg.routes.push(ButtonRoute { bit: from, target }),
+            (None, None) => {}
+        }
+    }
+}
+
+impl ComponentView for ButtonsView {
+    fn update(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let Some(available) = self.view.info().buttons.get(self.index).map(|info| info.buttons)
+            else { return; };
+
+            let Some(pressed) = self.view.device().buttons.get(self.index).map(|buttons| buttons.buttons)
+            else { return; };
+
+            let mut cfg_write = self.view.config_mut();
+            let Some(cfg) = cfg_write.get().buttons.get_mut(self.index)
+            else { return; };
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("devices/buttons/grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.label("Remap");
+                        ui.label("Controller");
+                        ui.end_row();
+
+                        for bit in (0..64).filter(|bit| available & (1 << bit) != 0) {
+                            // the device is configured, so the pressed state is shown at the remapped bit
+                            let mut text = egui::RichText::new(format!("Button {bit}"));
+                            if pressed & (1 << cfg.remap[bit]) != 0 {
+                                text = text.color(egui::Color32::LIGHT_GREEN).strong();
+                            }
+                            ui.label(text);
+
+                            Self::remap_select(ui, bit, available, cfg);
+                            Self::route_select(ui, bit, cfg);
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+    }
+}
@@ -1,5 +1,5 @@
 use zinput_engine::{
-    device::component::controller::{Button, Controller, ControllerConfig},
+    device::component::controller::{axis_to_tilt, Button, Controller, ControllerConfig},
     eframe::{
         egui,
         emath::{pos2, Rect},
@@ -163,15 +163,16 @@ impl ControllerView {
         controller: &Controller,
         mut sample: &mut SampleStick,
         mut concfg: Option<&mut ControllerConfig>,
+        available: u64,
     ) {
-        let lx = (controller.left_stick_x as f32 - 127.5) / 127.5;
-        let ly = (controller.left_stick_y as f32 - 127.5) / 127.5;
-        let rx = (controller.right_stick_x as f32 - 127.5) / 127.5;
-        let ry = (controller.right_stick_y as f32 - 127.5) / 127.5;
-        let l1 = controller.l1_analog as f32 / 255.0;
-        let r1 = controller.r1_analog as f32 / 255.0;
-        let l2 = controller.l2_analog as f32 / 255.0;
-        let r2 = controller.r2_analog as f32 / 255.0;
+        let lx = axis_to_tilt(controller.left_stick_x);
+        let ly = axis_to_tilt(controller.left_stick_y);
+        let rx = axis_to_tilt(controller.right_stick_x);
+        let ry = axis_to_tilt(controller.right_stick_y);
+        let l1 = controller.l1_analog as f32 / 65535.0;
+        let r1 = controller.r1_analog as f32 / 65535.0;
+        let l2 = controller.l2_analog as f32 / 65535.0;
+        let r2 = controller.r2_analog as f32 / 65535.0;
 
         match &mut sample {
             SampleStick::Left(sampler) => {
@@ -378,7 +379,11 @@ impl ControllerView {
                             )
                             .selected_text(format!("{}", Button::try_from_bit(selected).unwrap()))
                             .show_ui(ui, |ui| {
-                                for new_button in Button::BUTTONS {
+                                // the config is rejected if a button is remapped to one the device doesn't have
+                                for new_button in Button::BUTTONS
+                                    .into_iter()
+                                    .filter(|new_button| new_button.is_pressed(available))
+                                {
                                     ui.selectable_value(
                                         &mut cfg.remap[button.bit() as usize],
                                         new_button.bit() as u8,
@@ -408,6 +413,12 @@ impl ComponentView for ControllerView {
             ui.set_min_width(550.0);
 
             let rects = Self::get_rects(ui);
+            let available = self
+                .view
+                .info()
+                .controllers
+                .get(self.index)
+                .map_or(0, |info| info.buttons);
 
             if self.configure {
                 let device = self.view.device_raw();
@@ -418,13 +429,27 @@ impl ComponentView for ControllerView {
                 let Some(cfg) = cfg_write.get().controllers.get_mut(self.index)
                 else { return; };
 
-                Self::draw_view(ui, rects, controller, &mut self.sample_stick, Some(cfg));
+                Self::draw_view(
+                    ui,
+                    rects,
+                    controller,
+                    &mut self.sample_stick,
+                    Some(cfg),
+                    available,
+                );
             } else {
                 let device = self.view.device();
                 let Some(controller) = device.controllers.get(self.index)
                 else { return; };
 
-                Self::draw_view(ui, rects, controller, &mut SampleStick::None, None);
+                Self::draw_view(
+                    ui,
+                    rects,
+                    controller,
+                    &mut SampleStick::None,
+                    None,
+                    available,
+                );
             }
         });
     }
@@ -490,13 +515,13 @@ impl Sampler {
         Sampler { samples: [0.0; 32] }
     }
 
-    fn add(&mut self, x: u8, y: u8) {
+    fn add(&mut self, x: u16, y: u16) {
         fn index_to_angle(index: usize) -> f32 {
             (index as f32) * (std::f32::consts::PI * 2.0 / 32.0)
         }
 
-        let x = (x as f32 - 127.5) / 127.5;
-        let y = (y as f32 - 127.5) / 127.5;
+        let x = axis_to_tilt(x);
+        let y = axis_to_tilt(y);
         let scalar = f32::sqrt(x.powi(2) + y.powi(2));
         let mut angle = f32::atan2(y, x);
         if angle < 0.0 {
@@ -1,19 +1,28 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::SystemTime};
 
 use paste::paste;
 use zinput_engine::{
     device::{component::ComponentKind, components},
     eframe::{self, egui},
+    recording::{self, Recorder},
     util::Uuid,
-    DeviceView, Engine,
+    device::component::controller::Button,
+    ConfigLayer, ConfigSource, ConfigStore, DeviceView, Engine, Hotkey, HotkeyAction, Profiles,
 };
 
-use self::{controller_view::ControllerView, motion_view::MotionView};
+use self::{
+    buttons_view::ButtonsView, controller_view::ControllerView, motion_view::MotionView, output_view::OutputView,
+    status_view::StatusView, touch_pad_view::TouchPadView,
+};
 
 use super::Screen;
 
+mod buttons_view;
 mod controller_view;
 mod motion_view;
+mod output_view;
+mod status_view;
+mod touch_pad_view;
 
 pub struct DevicesTab {
     engine: Arc<Engine>,
@@ -25,6 +34,12 @@ pub struct DevicesTab {
 
     configs: Vec<String>,
     config_save: Option<String>,
+    nickname: Option<String>,
+    show_layers: bool,
+    /// Edited copy of the selected device's profiles
+    profiles: Option<Profiles>,
+
+    recorder: Option<(Uuid, Recorder)>,
 }
 
 impl DevicesTab {
@@ -39,6 +54,11 @@ impl DevicesTab {
 
             configs: Vec::new(),
             config_save: None,
+            nickname: None,
+            show_layers: false,
+            profiles: None,
+
+            recorder: None,
         }
     }
 
@@ -50,12 +70,14 @@ impl DevicesTab {
                     let last_selected = self.selected;
 
                     for entry in self.engine.devices() {
+                        let name = self
+                            .engine
+                            .known_device(entry.uuid())
+                            .and_then(|known| known.nickname)
+                            .unwrap_or_else(|| entry.info().name.clone());
+
                         if ui
-                            .selectable_value(
-                                &mut self.selected,
-                                Some(*entry.uuid()),
-                                &entry.info().name,
-                            )
+                            .selectable_value(&mut self.selected, Some(*entry.uuid()), name)
                             .clicked()
                         {
                             if last_selected != Some(*entry.uuid()) {
@@ -64,6 +86,16 @@ impl DevicesTab {
                             }
                         }
                     }
+
+                    let disconnected = self.engine.disconnected_devices();
+                    if !disconnected.is_empty() {
+                        ui.separator();
+                        ui.label("Disconnected");
+
+                        for device in disconnected {
+                            ui.weak(device.display_name());
+                        }
+                    }
                 });
         });
     }
@@ -76,6 +108,9 @@ impl DevicesTab {
         else { return; };
 
         Self::show_save_window(ctx, &mut self.config_save, &view);
+        Self::show_nickname_window(ctx, &mut self.nickname, &self.engine, &view);
+        Self::show_layers_window(ctx, &mut self.show_layers, &view);
+        Self::show_profiles_window(ctx, &mut self.profiles, &view);
 
         if self.component.is_none() && view.info().controllers.len() > 0 {
             self.component = Some(Default::default());
@@ -127,6 +162,25 @@ impl DevicesTab {
                         self.config_save = Some(String::new());
                     }
 
+                    for (layer, text) in [
+                        (ConfigLayer::Device, "Device Default"),
+                        (ConfigLayer::Preset, "Kind Preset"),
+                        (ConfigLayer::Global, "Global Default"),
+                    ] {
+                        if layer == ConfigLayer::Device && view.info().id.is_none() {
+                            continue;
+                        }
+
+                        if ui.selectable_label(false, text).clicked() {
+                            match view.save_config_layer(layer) {
+                                Ok(()) => {}
+                                Err(err) => {
+                                    log::error!("failed to save {layer} config layer: {err:?}");
+                                }
+                            }
+                        }
+                    }
+
                     ui.separator();
 
                     for config in configs {
@@ -181,6 +235,44 @@ impl DevicesTab {
                 if ui.button("Reset").clicked() {
                     view.reset_config();
                 }
+
+                if ui.button("Reload Layers").clicked() {
+                    match view.reload_config_layers() {
+                        Ok(()) => {}
+                        Err(err) => {
+                            log::error!("failed to reload config layers: {err:?}");
+                        }
+                    }
+                }
+
+                if ui.button("Layers").clicked() {
+                    self.show_layers = !self.show_layers;
+                }
+
+                if view.info().id.is_some() {
+                    ui.separator();
+
+                    if ui.button("Rename").clicked() {
+                        self.nickname = Some(
+                            self.engine
+                                .known_device(view.uuid())
+                                .and_then(|known| known.nickname)
+                                .unwrap_or_default(),
+                        );
+                    }
+
+                    if ui.button("Profiles").clicked() {
+                        self.profiles = Some(view.profiles());
+                    }
+
+                    if let Some(active) = view.active_profile() {
+                        ui.label(format!("Profile: {active}"));
+                    }
+                }
+
+                ui.separator();
+
+                Self::record_button(ui, &mut self.recorder, self.engine.config_store(), &view);
             });
         });
 
@@ -192,11 +284,11 @@ impl DevicesTab {
 
     fn add_components(&mut self, ui: &mut egui::Ui, view: DeviceView) {
         macro_rules! add_comps {
-            ($($cname:ident : $ckind:expr),* $(,)?) => {
+            ($($cname:ident ($plural:ident) : $ckind:expr),* $(,)?) => {
                 let last_component = self.component;
                 paste! {
                     $(
-                        for i in 0..view.info().[< $cname s >].len() {
+                        for i in 0..view.info().$plural.len() {
                             let selection = ComponentSelection {
                                 kind: $ckind,
                                 index: i,
@@ -254,6 +346,291 @@ impl DevicesTab {
             });
     }
 
+    fn show_nickname_window(
+        ctx: &egui::Context,
+        nickname: &mut Option<String>,
+        engine: &Engine,
+        view: &DeviceView,
+    ) {
+        let Some(name) = nickname
+        else { return; };
+
+        let mut close = false;
+
+        egui::Window::new("Rename")
+            .resizable(false)
+            .collapsible(false)
+            .auto_sized()
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(name);
+
+                    if ui.button("Save").clicked() {
+                        match engine.set_nickname(view.uuid(), Some(name.trim().to_owned())) {
+                            Ok(()) => {}
+                            Err(err) => {
+                                log::error!("failed to rename device: {err:?}");
+                            }
+                        }
+
+                        close = true;
+                    }
+
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if close {
+            *nickname = None;
+        }
+    }
+
+    fn show_layers_window(ctx: &egui::Context, open: &mut bool, view: &DeviceView) {
+        if !*open {
+            return;
+        }
+
+        egui::Window::new("Config Layers")
+            .open(open)
+            .default_height(300.0)
+            .show(ctx, |ui| {
+                let sources = match view.config_sources() {
+                    Ok(sources) => sources,
+                    Err(err) => {
+                        ui.label(format!("{err:#}"));
+                        return;
+                    }
+                };
+
+                let mut sources = sources
+                    .into_iter()
+                    .filter(|(_, source)| *source != ConfigSource::Default)
+                    .peekable();
+
+                if sources.peek().is_none() {
+                    ui.label("Every value is a default");
+                    return;
+                }
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("devices/layers/grid")
+                        .num_columns(2)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (field, source) in sources {
+                                ui.monospace(field);
+                                match source {
+                                    ConfigSource::Edited => ui.strong(format!("{source}")),
+                                    _ => ui.label(format!("{source}")),
+                                };
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+    }
+
+    fn show_profiles_window(
+        ctx: &egui::Context,
+        profiles: &mut Option<Profiles>,
+        view: &DeviceView,
+    ) {
+        let Some(edited) = profiles
+        else { return; };
+
+        let mut close = false;
+
+        egui::Window::new("Profiles")
+            .collapsible(false)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                let mut remove = None;
+                for (i, name) in edited.profiles.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("x").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.label(name);
+                    });
+                }
+                if let Some(i) = remove {
+                    edited.profiles.remove(i);
+                }
+
+                egui::ComboBox::new("devices/profiles/add", "")
+                    .selected_text("Add Profile")
+                    .show_ui(ui, |ui| {
+                        let configs = match view.saved_configs() {
+                            Ok(configs) => configs,
+                            Err(err) => {
+                                log::error!("failed to list configs: {err:?}");
+                                Vec::new()
+                            }
+                        };
+
+                        for config in configs {
+                            if !edited.profiles.contains(&config)
+                                && ui.selectable_label(false, &config).clicked()
+                            {
+                                edited.profiles.push(config);
+                            }
+                        }
+                    });
+
+                ui.separator();
+                ui.label("Hotkeys");
+
+                let mut remove = None;
+                for (i, hotkey) in edited.hotkeys.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("x").clicked() {
+                            remove = Some(i);
+                        }
+                        Self::chord_select(ui, i, &mut hotkey.chord);
+                        Self::action_select(ui, i, &edited.profiles, &mut hotkey.action);
+                    });
+                }
+                if let Some(i) = remove {
+                    edited.hotkeys.remove(i);
+                }
+
+                if ui.button("Add Hotkey").clicked() {
+                    edited.hotkeys.push(Hotkey {
+                        chord: vec![Button::Home],
+                        action: HotkeyAction::Next,
+                    });
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        match view.set_profiles(edited.clone()) {
+                            Ok(()) => {}
+                            Err(err) => {
+                                log::error!("failed to save profiles: {err:?}");
+                            }
+                        }
+
+                        close = true;
+                    }
+
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if close {
+            *profiles = None;
+        }
+    }
+
+    fn chord_select(ui: &mut egui::Ui, index: usize, chord: &mut Vec<Button>) {
+        let text = match chord.is_empty() {
+            true => "[None]".to_owned(),
+            false => chord
+                .iter()
+                .map(|button| format!("{button}"))
+                .collect::<Vec<_>>()
+                .join("+"),
+        };
+
+        egui::ComboBox::new(format!("devices/profiles/chord/{index}"), "")
+            .selected_text(text)
+            .show_ui(ui, |ui| {
+                for button in Button::BUTTONS {
+                    let mut held = chord.contains(&button);
+                    if ui.checkbox(&mut held, format!("{button}")).changed() {
+                        match held {
+                            true => chord.push(button),
+                            false => chord.retain(|other| *other != button),
+                        }
+                    }
+                }
+            });
+    }
+
+    fn action_select(
+        ui: &mut egui::Ui,
+        index: usize,
+        profiles: &[String],
+        action: &mut HotkeyAction,
+    ) {
+        egui::ComboBox::new(format!("devices/profiles/action/{index}"), "")
+            .selected_text(format!("{action}"))
+            .show_ui(ui, |ui| {
+                for new_action in [HotkeyAction::Next, HotkeyAction::Previous]
+                    .into_iter()
+                    .chain(profiles.iter().cloned().map(HotkeyAction::Select))
+                {
+                    let text = format!("{new_action}");
+                    ui.selectable_value(action, new_action, text);
+                }
+            });
+    }
+
+    fn record_button(
+        ui: &mut egui::Ui,
+        recorder: &mut Option<(Uuid, Recorder)>,
+        store: &ConfigStore,
+        view: &DeviceView,
+    ) {
+        let recording = matches!(recorder, Some((uuid, _)) if uuid == view.uuid());
+
+        if !recording {
+            if ui.button("Record").clicked() {
+                let name: String = view
+                    .info()
+                    .name
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                    .collect();
+                let time = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .map_or(0, |time| time.as_secs());
+                let path = recording::recording_path(
+                    &recording::recording_dir(store),
+                    &format!("{name}_{time}"),
+                );
+
+                match Recorder::start(view.clone(), path) {
+                    Ok(new_recorder) => {
+                        *recorder = Some((*view.uuid(), new_recorder));
+                    }
+                    Err(err) => {
+                        log::error!("failed to start recording: {err:?}");
+                    }
+                }
+            }
+
+            return;
+        }
+
+        let Some((_, current)) = recorder
+        else { return; };
+
+        ui.label(format!("{} frames", current.frames()));
+
+        if ui.button("Stop Recording").clicked() {
+            let Some((_, current)) = recorder.take()
+            else { return; };
+
+            let path = current.path().to_owned();
+
+            match current.stop() {
+                Ok(()) => {
+                    log::info!("saved recording to '{}'", path.display());
+                }
+                Err(err) => {
+                    log::error!("failed to save recording: {err:?}");
+                }
+            }
+        }
+    }
+
     fn config_button(
         ui: &mut egui::Ui,
         name: impl Into<String>,
@@ -325,7 +702,14 @@ fn get_component_view(
 ) -> Option<Box<dyn ComponentView>> {
     match kind {
         ComponentKind::Controller => Some(Box::new(ControllerView::new(device, index))),
+        ComponentKind::Buttons => Some(Box::new(ButtonsView::new(device, index))),
         ComponentKind::Motion => Some(Box::new(MotionView::new(device, index))),
+        ComponentKind::Status => Some(Box::new(StatusView::new(device, index))),
+        ComponentKind::TouchPad => Some(Box::new(TouchPadView::new(device, index))),
+        ComponentKind::Motor
+        | ComponentKind::PlayerLeds
+        | ComponentKind::Lightbar
+        | ComponentKind::AdaptiveTrigger => Some(Box::new(OutputView::new(device, kind, index))),
         _ => None,
     }
 }
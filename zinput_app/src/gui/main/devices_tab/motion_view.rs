@@ -1,4 +1,5 @@
 use zinput_engine::{
+    device::component::motion::{GyroCalibration, MotionConfig, GYRO_AXES},
     eframe::{
         egui,
     },
@@ -12,6 +13,8 @@ pub struct MotionView {
     index: usize,
 
     angle: String,
+    calibration: Option<GyroCalibration>,
+    last_sequence: u64,
 }
 
 impl MotionView {
@@ -21,6 +24,104 @@ impl MotionView {
             index,
 
             angle: String::new(),
+            calibration: None,
+            last_sequence: 0,
+        }
+    }
+
+    fn calibrate(&mut self, ui: &mut egui::Ui) {
+        let Some(info) = self.view.info().motions.get(self.index)
+        else { return; };
+
+        if !info.has_gyro {
+            return;
+        }
+
+        let Some(calibration) = &mut self.calibration
+        else {
+            if ui.button("Calibrate Gyro").clicked() {
+                self.calibration = Some(GyroCalibration::new());
+            }
+            return;
+        };
+
+        // Samples come from the raw device so the old bias doesn't end up in the new one
+        let device = self.view.device_raw();
+        if device.sequence() != self.last_sequence {
+            self.last_sequence = device.sequence();
+            if let Some(motion) = device.motions.get(self.index) {
+                calibration.add(motion, info);
+            }
+        }
+
+        ui.label("Leave the device still on a flat surface");
+        ui.add(egui::ProgressBar::new(calibration.progress()).show_percentage());
+
+        let cancel = ui.button("Cancel").clicked();
+
+        if let Some(bias) = calibration.bias() {
+            let mut cfg_write = self.view.config_mut();
+            if let Some(cfg) = cfg_write.get().motions.get_mut(self.index) {
+                cfg.gyro_bias = bias;
+            }
+        }
+
+        if cancel || calibration.bias().is_some() {
+            self.calibration = None;
+        }
+
+        ui.ctx().request_repaint();
+    }
+
+    fn configure(cfg: &mut MotionConfig, ui: &mut egui::Ui) {
+        egui::Grid::new("devices/motion/gyro").show(ui, |ui| {
+            ui.label("");
+            ui.label("Axis");
+            ui.label("Invert");
+            ui.label("Sensitivity");
+            ui.label("Bias");
+            ui.end_row();
+
+            for i in 0..3 {
+                ui.label(GYRO_AXES[i]);
+
+                egui::ComboBox::new(format!("devices/motion/gyro/axis{i}"), "")
+                    .selected_text(GYRO_AXES[cfg.gyro_axes[i] as usize % 3])
+                    .show_ui(ui, |ui| {
+                        for (axis, name) in GYRO_AXES.iter().enumerate() {
+                            ui.selectable_value(&mut cfg.gyro_axes[i], axis as u8, *name);
+                        }
+                    });
+
+                ui.checkbox(&mut cfg.gyro_invert[i], "");
+                ui.add(
+                    egui::DragValue::new(&mut cfg.gyro_sensitivity[i])
+                        .clamp_range(0.0..=10.0)
+                        .speed(0.01),
+                );
+                ui.add(egui::DragValue::new(&mut cfg.gyro_bias[i]).speed(0.01));
+                ui.end_row();
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.label("Accelerometer Orientation");
+
+        egui::Grid::new("devices/motion/accel").show(ui, |ui| {
+            for row in &mut cfg.accel_orientation {
+                for value in row {
+                    ui.add(
+                        egui::DragValue::new(value)
+                            .clamp_range(-1.0..=1.0)
+                            .speed(0.01),
+                    );
+                }
+                ui.end_row();
+            }
+        });
+
+        if ui.button("Reset").clicked() {
+            *cfg = MotionConfig::default();
         }
     }
 }
@@ -67,6 +168,29 @@ impl ComponentView for MotionView {
             }
 
             ui.label(&self.angle);
+
+            let [w, x, y, z] = motion.orientation();
+            ui.label(format!("Orientation: {w:.3} {x:.3} {y:.3} {z:.3}"));
+            let [gx, gy, gz] = motion.gravity();
+            ui.label(format!("Gravity: {gx:.3} {gy:.3} {gz:.3}"));
+
+            drop(device);
+
+            if ui.button("Recenter").clicked() {
+                self.view.recenter_motion(self.index);
+            }
+
+            ui.add_space(10.0);
+            ui.separator();
+
+            self.calibrate(ui);
+
+            ui.add_space(10.0);
+
+            let mut cfg_write = self.view.config_mut();
+            if let Some(cfg) = cfg_write.get().motions.get_mut(self.index) {
+                Self::configure(cfg, ui);
+            }
         });
     }
 }
\ No newline at end of file
@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use zinput_engine::{
+    device::{
+        component::{lightbar::Lightbar, player_leds::PlayerLeds, ComponentKind},
+        feedback::{Feedback, Rumble},
+    },
+    eframe::egui,
+    DeviceView,
+};
+
+use super::ComponentView;
+
+/// How long the test button rumbles for
+const TEST_RUMBLE: Duration = Duration::from_millis(500);
+
+/// Shows the state of a motor, player LEDs, lightbar or adaptive trigger and lets the user change it
+pub struct OutputView {
+    view: DeviceView,
+    kind: ComponentKind,
+    index: usize,
+}
+
+impl OutputView {
+    pub fn new(view: DeviceView, kind: ComponentKind, index: usize) -> Self {
+        OutputView { view, kind, index }
+    }
+
+    fn send(&self, feedback: Feedback) {
+        match self.view.send_feedback(feedback) {
+            Ok(()) => {}
+            Err(err) => log::warn!("failed to send feedback: {err}"),
+        }
+    }
+
+    fn motor(&self, ui: &mut egui::Ui) {
+        let device = self.view.device();
+        let Some(motor) = device.motors.get(self.index)
+        else { return; };
+
+        ui.label(format!("Amplitude: {}", motor.amplitude));
+
+        if ui.button("Test").clicked() {
+            self.send(Feedback::Rumble(Rumble {
+                strong: 255,
+                weak: 255,
+                duration: Some(TEST_RUMBLE),
+            }));
+        }
+    }
+
+    fn player_leds(&self, ui: &mut egui::Ui) {
+        let Some(info) = self.view.info().player_leds.get(self.index)
+        else { return; };
+
+        let device = self.view.device();
+        let Some(leds) = device.player_leds.get(self.index)
+        else { return; };
+
+        let mut new_leds = *leds;
+
+        ui.horizontal(|ui| {
+            for led in 0..info.count.min(8) {
+                let mut lit = leds.is_lit(led);
+                if ui.checkbox(&mut lit, "").changed() {
+                    new_leds.leds ^= 1 << led;
+                }
+            }
+        });
+
+        if &new_leds != leds {
+            self.send(Feedback::PlayerLeds(new_leds));
+        }
+    }
+
+    fn lightbar(&self, ui: &mut egui::Ui) {
+        let device = self.view.device();
+        let Some(lightbar) = device.lightbars.get(self.index)
+        else { return; };
+
+        let mut color = [lightbar.red, lightbar.green, lightbar.blue];
+        if ui.color_edit_button_srgb(&mut color).changed() {
+            let [red, green, blue] = color;
+            self.send(Feedback::Lightbar(Lightbar::new(red, green, blue)));
+        }
+    }
+
+    fn adaptive_trigger(&self, ui: &mut egui::Ui) {
+        let device = self.view.device();
+        let Some(trigger) = device.adaptive_triggers.get(self.index)
+        else { return; };
+
+        ui.label(format!("Effect: {:?}", trigger.effect()));
+        ui.label(format!("Start: {}", trigger.start));
+        ui.label(format!("End: {}", trigger.end));
+        ui.label(format!("Strength: {}", trigger.strength));
+    }
+}
+
+impl ComponentView for OutputView {
+    fn update(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| match self.kind {
+            ComponentKind::Motor => self.motor(ui),
+            ComponentKind::PlayerLeds => self.player_leds(ui),
+            ComponentKind::Lightbar => self.lightbar(ui),
+            ComponentKind::AdaptiveTrigger => self.adaptive_trigger(ui),
+            _ => {}
+        });
+    }
+}
@@ -0,0 +1,38 @@
+use zinput_engine::{eframe::egui, DeviceView};
+
+use super::ComponentView;
+
+pub struct StatusView {
+    view: DeviceView,
+    index: usize,
+}
+
+impl StatusView {
+    pub fn new(view: DeviceView, index: usize) -> Self {
+        StatusView { view, index }
+    }
+}
+
+impl ComponentView for StatusView {
+    fn update(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let Some(info) = self.view.info().statuses.get(self.index)
+            else { return; };
+
+            let device = self.view.device();
+            let Some(status) = device.statuses.get(self.index)
+            else { return; };
+
+            ui.label(format!("Connection: {}", status.connection()));
+
+            if info.has_battery {
+                let charging = if status.charging { " (charging)" } else { "" };
+                ui.label(format!("Battery: {}%{charging}", status.battery));
+            }
+
+            if info.has_signal {
+                ui.label(format!("Signal: {}%", status.signal));
+            }
+        });
+    }
+}
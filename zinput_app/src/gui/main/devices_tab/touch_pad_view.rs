@@ -0,0 +1,290 @@
+use zinput_engine::{
+    device::component::{
+        controller::{Button, ControllerInfo},
+        touch_pad::{Regions, StickSide, TouchPadConfig, TouchPadMode},
+    },
+    eframe::{egui, emath::vec2},
+    DeviceView,
+};
+
+use super::ComponentView;
+
+pub struct TouchPadView {
+    view: DeviceView,
+    index: usize,
+}
+
+impl TouchPadView {
+    pub fn new(view: DeviceView, index: usize) -> Self {
+        TouchPadView { view, index }
+    }
+
+    fn mode(ui: &mut egui::Ui, cfg: &mut TouchPadConfig, controller: &ControllerInfo) {
+        let selected = match &cfg.mode {
+            TouchPadMode::None => "None",
+            TouchPadMode::Stick {
+                side: StickSide::Left,
+                ..
+            } => "Left Stick",
+            TouchPadMode::Stick {
+                side: StickSide::Right,
+                ..
+            } => "Right Stick",
+            TouchPadMode::Scroll { .. } => "Scroll Wheel",
+            TouchPadMode::Mouse { .. } => "Mouse",
+        };
+
+        egui::ComboBox::new("devices/touch_pad/mode", "Mode")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                let stick = |side| TouchPadMode::Stick {
+                    side,
+                    relative: false,
+                    sensitivity: 0.05,
+                };
+
+                // the config is rejected if the pad moves a stick the controller doesn't have
+                for (name, mode) in [
+                    ("None", TouchPadMode::None),
+                    ("Left Stick", stick(StickSide::Left)),
+                    ("Right Stick", stick(StickSide::Right)),
+                    ("Scroll Wheel", TouchPadMode::Scroll { sensitivity: 20.0 }),
+                    ("Mouse", TouchPadMode::Mouse { sensitivity: 1000.0 }),
+                ]
+                .into_iter()
+                .filter(|(_, mode)| match mode {
+                    TouchPadMode::Stick {
+                        side: StickSide::Left,
+                        ..
+                    } => controller.analogs & (1 << 0) != 0,
+                    TouchPadMode::Stick {
+                        side: StickSide::Right,
+                        ..
+                    } => controller.analogs & (1 << 1) != 0,
+                    _ => true,
+                }) {
+                    if ui.selectable_label(selected == name, name).clicked() && selected != name {
+                        cfg.mode = mode;
+                    }
+                }
+            });
+
+        match &mut cfg.mode {
+            TouchPadMode::None => {}
+            TouchPadMode::Stick {
+                relative,
+                sensitivity,
+                ..
+            } => {
+                ui.checkbox(relative, "Relative");
+                if *relative {
+                    ui.add(
+                        egui::DragValue::new(sensitivity)
+                            .prefix("Full tilt at: ")
+                            .clamp_range(0.001..=1.0)
+                            .speed(0.001),
+                    );
+                }
+            }
+            TouchPadMode::Scroll { sensitivity } | TouchPadMode::Mouse { sensitivity } => {
+                ui.add(
+                    egui::DragValue::new(sensitivity)
+                        .prefix("Sensitivity: ")
+                        .clamp_range(1.0..=10000.0),
+                );
+            }
+        }
+    }
+
+    fn regions(ui: &mut egui::Ui, cfg: &mut TouchPadConfig, available: u64) {
+        let selected = match &cfg.regions {
+            Regions::None => "None",
+            Regions::Grid { .. } => "Grid",
+            Regions::Radial { .. } => "Radial",
+            Regions::Custom(_) => "Custom",
+        };
+
+        egui::ComboBox::new("devices/touch_pad/regions", "Regions")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(selected == "None", "None").clicked() {
+                    cfg.regions = Regions::None;
+                }
+                if ui.selectable_label(selected == "Grid", "Grid").clicked() && selected != "Grid" {
+                    cfg.regions = Regions::Grid {
+                        columns: 2,
+                        rows: 2,
+                        buttons: vec![None; 4],
+                    };
+                }
+                if ui.selectable_label(selected == "Radial", "Radial").clicked()
+                    && selected != "Radial"
+                {
+                    cfg.regions = Regions::Radial {
+                        rotation: -45.0,
+                        inner_radius: 0.0,
+                        center: None,
+                        buttons: vec![None; 4],
+                    };
+                }
+                // custom regions are only edited in the config file
+                if ui.selectable_label(selected == "Custom", "Custom").clicked()
+                    && selected != "Custom"
+                {
+                    cfg.regions = Regions::Custom(Vec::new());
+                }
+            });
+
+        ui.checkbox(&mut cfg.regions_need_press, "Only while clicked");
+
+        match &mut cfg.regions {
+            Regions::None | Regions::Custom(_) => {}
+            Regions::Grid {
+                columns,
+                rows,
+                buttons,
+            } => {
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(columns).prefix("Columns: ").clamp_range(1..=8));
+                    ui.add(egui::DragValue::new(rows).prefix("Rows: ").clamp_range(1..=8));
+                });
+                buttons.resize(*columns as usize * *rows as usize, None);
+
+                egui::Grid::new("devices/touch_pad/grid").show(ui, |ui| {
+                    for (i, button) in buttons.iter_mut().enumerate() {
+                        if i > 0 && i % *columns as usize == 0 {
+                            ui.end_row();
+                        }
+                        Self::button_select(
+                            ui,
+                            format!("devices/touch_pad/grid/{i}"),
+                            button,
+                            available,
+                        );
+                    }
+                });
+            }
+            Regions::Radial {
+                rotation,
+                inner_radius,
+                center,
+                buttons,
+            } => {
+                let mut slices = buttons.len();
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut slices).prefix("Slices: ").clamp_range(1..=16));
+                    ui.add(
+                        egui::DragValue::new(rotation)
+                            .prefix("Rotation: ")
+                            .suffix("°")
+                            .clamp_range(-180.0..=180.0),
+                    );
+                    ui.add(
+                        egui::DragValue::new(inner_radius)
+                            .prefix("Center: ")
+                            .clamp_range(0.0..=1.0)
+                            .speed(0.01),
+                    );
+                });
+                buttons.resize(slices, None);
+
+                Self::button_select(
+                    ui,
+                    "devices/touch_pad/radial/center".to_owned(),
+                    center,
+                    available,
+                );
+                for (i, button) in buttons.iter_mut().enumerate() {
+                    Self::button_select(
+                        ui,
+                        format!("devices/touch_pad/radial/{i}"),
+                        button,
+                        available,
+                    );
+                }
+            }
+        }
+    }
+
+    fn button_select(ui: &mut egui::Ui, id: String, button: &mut Option<u8>, available: u64) {
+        let name = |button: Option<u8>| {
+            button
+                .and_then(Button::try_from_bit)
+                .map_or("[None]".to_owned(), |button| format!("{button}"))
+        };
+
+        egui::ComboBox::new(id, "")
+            .selected_text(name(*button))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(button, None, "[None]");
+                // the config is rejected if a region presses a button the controller doesn't have
+                for new_button in Button::BUTTONS
+                    .into_iter()
+                    .filter(|new_button| new_button.is_pressed(available))
+                {
+                    let bit = Some(new_button.bit() as u8);
+                    ui.selectable_value(button, bit, name(bit));
+                }
+            });
+    }
+}
+
+impl ComponentView for TouchPadView {
+    fn update(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // touch pads write to the first controller
+            let controller = self
+                .view
+                .info()
+                .controllers
+                .first()
+                .cloned()
+                .unwrap_or_default();
+
+            let Some(info) = self.view.info().touch_pads.get(self.index)
+            else { return; };
+
+            let device = self.view.device();
+            let Some(pad) = device.touch_pads.get(self.index)
+            else { return; };
+
+            let size = f32::min(200.0, ui.available_width());
+            let (rect, _) = ui.allocate_exact_size(vec2(size, size), egui::Sense::hover());
+            let painter = ui.painter();
+            painter.rect_stroke(rect, 0.0, ui.visuals().widgets.noninteractive.fg_stroke);
+
+            for touch in pad.active() {
+                let pos = rect.min
+                    + vec2(
+                        touch.x as f32 / info.resolution[0].max(1) as f32,
+                        touch.y as f32 / info.resolution[1].max(1) as f32,
+                    ) * size;
+                let color = match pad.pressed {
+                    true => egui::Color32::LIGHT_GREEN,
+                    false => egui::Color32::WHITE,
+                };
+                painter.circle_filled(pos, 5.0, color);
+                painter.text(
+                    pos + vec2(8.0, 0.0),
+                    egui::Align2::LEFT_CENTER,
+                    touch.id,
+                    egui::TextStyle::Small.resolve(ui.style()),
+                    color,
+                );
+            }
+
+            drop(device);
+
+            ui.add_space(10.0);
+            ui.separator();
+
+            let mut cfg_write = self.view.config_mut();
+            let Some(cfg) = cfg_write.get().touch_pads.get_mut(self.index)
+            else { return; };
+
+            Self::mode(ui, cfg, &controller);
+            ui.add_space(10.0);
+            Self::regions(ui, cfg, controller.buttons);
+        });
+    }
+}
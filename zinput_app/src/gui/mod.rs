@@ -1,24 +1,46 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use zinput_engine::{
     eframe::{self, egui},
+    event::Subscription,
     plugin::Plugin,
     Engine,
 };
 
+use crate::zinput::settings::Settings;
+
 mod main;
 mod util;
 
 pub struct Gui {
     main_ui: main::MainUi,
 
+    engine: Arc<Engine>,
+    plugins: Vec<Arc<dyn Plugin + Send + Sync>>,
+    subscriptions: Vec<Subscription>,
+    session: Option<PathBuf>,
+    /// The session restored on startup
+    restored: Settings,
+
     first_update: bool,
 }
 
 impl Gui {
-    pub fn new(engine: Arc<Engine>, plugins: Vec<Arc<dyn Plugin + Send + Sync>>) -> Self {
+    pub fn new(
+        engine: Arc<Engine>,
+        plugins: Vec<Arc<dyn Plugin + Send + Sync>>,
+        subscriptions: Vec<Subscription>,
+        session: Option<PathBuf>,
+        restored: Settings,
+    ) -> Self {
         Gui {
-            main_ui: main::MainUi::new(engine, plugins),
+            main_ui: main::MainUi::new(engine.clone(), plugins.clone()),
+
+            engine,
+            plugins,
+            subscriptions,
+            session,
+            restored,
 
             first_update: true,
         }
@@ -36,4 +58,23 @@ impl eframe::App for Gui {
 
         ctx.request_repaint();
     }
+
+    fn on_exit_event(&mut self) -> bool {
+        if let Some(path) = &self.session {
+            match Settings::capture(&self.engine, &self.plugins, &self.restored).save(path) {
+                Ok(()) => log::info!("saved session to '{}'", path.display()),
+                Err(err) => log::error!("failed to save session: {err:#}"),
+            }
+        }
+
+        // the process ends with the window, so the plugins stop here
+        // with no events reaching them while they stop
+        self.subscriptions.clear();
+
+        for plugin in &self.plugins {
+            plugin.stop();
+        }
+
+        true
+    }
 }
@@ -2,7 +2,13 @@
 #![feature(generic_associated_types)]
 #![feature(maybe_uninit_uninit_array)]
 
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
+
+use zinput_engine::{plugin::loader, recording, ConfigStore};
+
+use crate::zinput::settings::{Settings, SESSION_FILE, SETTINGS_FILE};
+
+const PLUGIN_DIR: &'static str = "plugins";
 
 mod backend;
 mod frontend;
@@ -12,12 +18,67 @@ mod zinput;
 fn main() {
     simple_logger::SimpleLogger::new().init().unwrap();
 
-    let mut zinput = zinput::ZInput::new();
+    let mut store = None;
+    let mut headless = None;
+    let mut plugin_dir = None;
+
+    let mut args = std::env::args().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config-dir" => {
+                let Some(dir) = args.next()
+                else {
+                    log::error!("--config-dir needs a directory");
+                    return;
+                };
+
+                store = Some(ConfigStore::new(dir));
+            }
+            "--plugin-dir" => {
+                let Some(dir) = args.next()
+                else {
+                    log::error!("--plugin-dir needs a directory");
+                    return;
+                };
+
+                plugin_dir = Some(PathBuf::from(dir));
+            }
+            "--headless" => {
+                let settings = args.next_if(|arg| !arg.starts_with("--"));
+                headless = Some(settings.map(PathBuf::from));
+            }
+            _ => log::warn!("unknown argument '{arg}'"),
+        }
+    }
+
+    let store = store.unwrap_or_default();
+    log::info!("config directory: {}", store.root().display());
+
+    let headless =
+        headless.map(|settings| settings.unwrap_or_else(|| store.root().join(SETTINGS_FILE)));
+    let session_path = store.root().join(SESSION_FILE);
+    let session = match &headless {
+        Some(_) => None,
+        None if session_path.exists() => match Settings::load(&session_path) {
+            Ok(session) => Some(session),
+            Err(err) => {
+                log::warn!("failed to load session: {err:#}");
+                None
+            }
+        },
+        None => None,
+    };
+
+    // the settings file or the saved session decide which plugins start
+    let autostart = headless.is_none() && session.is_none();
+
+    let recording_dir = recording::recording_dir(&store);
+    let mut zinput = zinput::ZInput::new(store);
 
     #[cfg(target_os = "windows")]
     {
-        zinput.add_plugin(Arc::new(backend::raw_input::RawInput::new()), true);
-        zinput.add_plugin(Arc::new(backend::xinput::XInput::new()), true);
+        zinput.add_plugin(Arc::new(backend::raw_input::RawInput::new()), autostart);
+        zinput.add_plugin(Arc::new(backend::xinput::XInput::new()), autostart);
         zinput.add_plugin(Arc::new(frontend::vigem::Vigem::new()), false);
     }
 
@@ -26,12 +87,58 @@ fn main() {
         zinput.add_plugin(Arc::new(frontend::uinput::UInput::new()), false);
     }
 
-    zinput.add_plugin(Arc::new(backend::joycon::Joycon::new()), true);
+    zinput.add_plugin(Arc::new(backend::joycon::Joycon::new()), autostart);
     zinput.add_plugin(Arc::new(backend::swi_recv::Swi::new()), false);
-    zinput.add_plugin(Arc::new(backend::usb_devices::UsbDevices::new()), true);
+    zinput.add_plugin(Arc::new(backend::replay::Replay::new(recording_dir)), false);
+    zinput.add_plugin(Arc::new(backend::usb_devices::UsbDevices::new()), autostart);
 
     zinput.add_plugin(Arc::new(frontend::dsus::Dsus::new()), false);
     zinput.add_plugin(Arc::new(frontend::swi_send::Swi::new()), false);
 
-    zinput.run();
+    match plugin_dir.or_else(default_plugin_dir) {
+        Some(dir) if dir.exists() => {
+            log::info!("plugin directory: {}", dir.display());
+
+            // SAFETY: the plugin directory is chosen by the user
+            match unsafe { loader::load_dir(&dir) } {
+                Ok(plugins) => {
+                    for plugin in plugins {
+                        zinput.add_plugin(plugin, false);
+                    }
+                }
+                Err(err) => log::error!("failed to load plugins: {err:#}"),
+            }
+        }
+        _ => {}
+    }
+
+    match headless {
+        Some(path) => {
+            let settings = match Settings::load(&path) {
+                Ok(settings) => settings,
+                Err(err) => {
+                    log::error!("failed to load settings: {err:#}");
+                    return;
+                }
+            };
+
+            if let Err(err) = zinput.run_headless(settings) {
+                log::error!("headless mode failed: {err:#}");
+            }
+        }
+        None => {
+            if let Some(session) = &session {
+                log::info!("restoring session from '{}'", session_path.display());
+                zinput.restore(session);
+            }
+
+            zinput.run(Some(session_path), session.unwrap_or_default());
+        }
+    }
+}
+
+/// `plugins` next to the executable
+fn default_plugin_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.parent()?.join(PLUGIN_DIR))
 }
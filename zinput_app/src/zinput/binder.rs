@@ -0,0 +1,113 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use zinput_engine::{device::DeviceInfo, plugin::Plugin, util::Uuid, Engine};
+
+use super::settings::Settings;
+
+/// Binds devices to frontend slots by device id as soon as the devices connect
+///
+/// Every slot is only bound once, so a slot changed by hand is left alone when its device reconnects.
+pub struct SlotBinder {
+    engine: Arc<Engine>,
+    bindings: Vec<Binding>,
+    configs: HashMap<String, String>,
+}
+
+struct Binding {
+    plugin: Arc<dyn Plugin + Send + Sync>,
+    slot: usize,
+    id: String,
+    bound: AtomicBool,
+}
+
+impl SlotBinder {
+    pub fn new(
+        engine: Arc<Engine>,
+        plugins: &[Arc<dyn Plugin + Send + Sync>],
+        settings: &Settings,
+    ) -> Self {
+        let mut bindings = Vec::new();
+
+        for (name, slots) in &settings.slots {
+            let Some(plugin) = plugins.iter().find(|plugin| plugin.name() == name)
+            else {
+                log::warn!("cannot bind slots of unknown plugin '{name}'");
+                continue;
+            };
+
+            for (slot, id) in slots.iter().enumerate() {
+                let Some(id) = id
+                else { continue; };
+
+                if slot >= plugin.slots() {
+                    log::warn!(
+                        "cannot bind '{id}' to slot {} of '{name}', it only has {} slots",
+                        slot + 1,
+                        plugin.slots()
+                    );
+                    continue;
+                }
+
+                bindings.push(Binding {
+                    plugin: plugin.clone(),
+                    slot,
+                    id: id.clone(),
+                    bound: AtomicBool::new(false),
+                });
+            }
+        }
+
+        SlotBinder {
+            engine,
+            bindings,
+            configs: settings.configs.clone(),
+        }
+    }
+
+    /// Binds the devices that are already connected
+    pub fn bind_connected(&self) {
+        let devices = self
+            .engine
+            .devices()
+            .map(|entry| (*entry.uuid(), entry.info().clone()))
+            .collect::<Vec<_>>();
+
+        for (uuid, info) in devices {
+            self.device_added(&uuid, &info);
+        }
+    }
+
+    pub fn device_added(&self, uuid: &Uuid, info: &DeviceInfo) {
+        let Some(id) = &info.id
+        else { return; };
+
+        if let Some(config) = self.configs.get(id) {
+            if let Some(view) = self.engine.get_device(uuid) {
+                match view.load_config(config) {
+                    Ok(()) => log::info!("loaded config '{config}' for '{id}'"),
+                    Err(err) => log::warn!("failed to load config '{config}' for '{id}': {err:?}"),
+                }
+            }
+        }
+
+        for binding in self.bindings.iter().filter(|binding| &binding.id == id) {
+            if binding.bound.swap(true, Ordering::AcqRel) {
+                continue;
+            }
+
+            log::info!(
+                "binding '{id}' to slot {} of '{}'",
+                binding.slot + 1,
+                binding.plugin.name()
+            );
+
+            binding.plugin.bind_slot(binding.slot, Some(*uuid));
+        }
+    }
+}
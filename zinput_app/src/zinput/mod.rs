@@ -1,23 +1,40 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
-use zinput_engine::{eframe, plugin::Plugin, Engine};
+use anyhow::{Context, Result};
+use zinput_engine::{
+    eframe,
+    event::{Event, EventKind, Subscription},
+    plugin::{Plugin, PluginStatus},
+    ConfigStore, Engine,
+};
 
 use crate::gui::Gui;
 
+use self::{binder::SlotBinder, settings::Settings};
+
+mod binder;
+pub mod settings;
+
 pub struct ZInput {
     plugins: Vec<Arc<dyn Plugin + Send + Sync>>,
+    subscriptions: Vec<Subscription>,
     engine: Arc<Engine>,
 }
 
 impl ZInput {
-    pub fn new() -> Self {
+    pub fn new(store: ConfigStore) -> Self {
         ZInput {
             plugins: Vec::new(),
-            engine: Arc::new(Engine::new()),
+            subscriptions: Vec::new(),
+            engine: Arc::new(Engine::new(store)),
         }
     }
 
     pub fn add_plugin(&mut self, plugin: Arc<dyn Plugin + Send + Sync>, init: bool) {
+        if let Some(subscription) = self.engine.subscribe(plugin.clone()) {
+            self.subscriptions.push(subscription);
+        }
+
         if init {
             plugin.init(self.engine.clone());
         }
@@ -25,28 +42,119 @@ impl ZInput {
         self.plugins.push(plugin);
     }
 
-    pub fn run(&mut self) {
-        let app = Gui::new(self.engine.clone(), self.plugins.clone());
-        let options = eframe::NativeOptions::default();
+    /// Runs the gui, saving the session to `session` when it closes
+    ///
+    /// `restored` is the session the gui started with, see [`Settings::capture`].
+    /// The gui stops the plugins when it closes, see [`ZInput::shutdown`].
+    pub fn run(&mut self, session: Option<PathBuf>, restored: Settings) {
+        self.watch_reserved_slots();
 
-        // TODO: make sure program stops cleanly
+        let app = Gui::new(
+            self.engine.clone(),
+            self.plugins.clone(),
+            std::mem::take(&mut self.subscriptions),
+            session,
+            restored,
+        );
+        let options = eframe::NativeOptions::default();
 
         eframe::run_native("zinput", options, Box::new(|_| Box::new(app)));
+    }
+
+    /// Runs without a window until the process gets a termination signal
+    pub fn run_headless(&mut self, settings: Settings) -> Result<()> {
+        let (stop_send, stop_recv) = crossbeam_channel::bounded(1);
+        ctrlc::set_handler(move || {
+            let _ = stop_send.try_send(());
+        })
+        .context("failed to set signal handler")?;
+
+        self.restore(&settings);
+        self.watch_reserved_slots();
+
+        log::info!("running headless");
+        let _ = stop_recv.recv();
+        log::info!("stopping");
+
+        self.shutdown();
+
+        Ok(())
+    }
+
+    /// Starts the plugins in `settings` and binds their slots
+    pub fn restore(&mut self, settings: &Settings) {
+        for plugin in &self.plugins {
+            if let Some(plugin_settings) = settings.plugin_settings.get(plugin.name()) {
+                plugin.load_settings(plugin_settings.clone());
+            }
+        }
 
-        /*
-        for frontend in &self.frontends {
-            frontend.stop();
+        for name in &settings.plugins {
+            let Some(plugin) = self.plugins.iter().find(|plugin| plugin.name() == name)
+            else {
+                log::warn!("cannot start unknown plugin '{name}'");
+                continue;
+            };
+
+            if plugin.status() != PluginStatus::Running {
+                plugin.init(self.engine.clone());
+            }
         }
 
-        for backend in &self.backends {
-            backend.stop();
+        self.bind_slots(settings);
+    }
+
+    /// Stops every plugin and waits for their threads to finish
+    pub fn shutdown(&mut self) {
+        // no events reach the plugins while they stop
+        self.subscriptions.clear();
+
+        for plugin in &self.plugins {
+            plugin.stop();
         }
+    }
+
+    fn bind_slots(&mut self, settings: &Settings) {
+        let binder = Arc::new(SlotBinder::new(
+            self.engine.clone(),
+            &self.plugins,
+            settings,
+        ));
 
-        for handle in std::mem::replace(&mut self.event_thread_handler, None) {
-            match handle.join() {
-                Ok(()) => {},
-                Err(_) => log::error!("event handler thread crashed"),
+        // subscribe first so no device is missed between binding and subscribing
+        let subscription = self.engine.events().subscribe(&[EventKind::DeviceAdded], {
+            let binder = binder.clone();
+            move |event| {
+                if let Event::DeviceAdded(uuid, info) = event {
+                    binder.device_added(uuid, info);
+                }
             }
-        }*/
+        });
+        self.subscriptions.push(subscription);
+
+        binder.bind_connected();
+    }
+
+    /// Binds slots kept for a disconnected device again when it connects,
+    /// since the frontend could not output the device while it was missing
+    fn watch_reserved_slots(&mut self) {
+        let plugins = self.plugins.clone();
+
+        let subscription = self
+            .engine
+            .events()
+            .subscribe(&[EventKind::DeviceAdded], move |event| {
+                let Event::DeviceAdded(uuid, _) = event
+                else { return; };
+
+                for plugin in &plugins {
+                    for slot in 0..plugin.slots() {
+                        if plugin.slot_device(slot) == Some(*uuid) {
+                            plugin.bind_slot(slot, Some(*uuid));
+                        }
+                    }
+                }
+            });
+        self.subscriptions.push(subscription);
     }
 }
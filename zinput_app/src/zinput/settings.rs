@@ -0,0 +1,104 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use zinput_engine::{
+    plugin::{Plugin, PluginStatus},
+    util::write_atomic,
+    Engine,
+};
+
+/// Default settings file name for headless mode, relative to the config root
+pub const SETTINGS_FILE: &'static str = "headless.json";
+
+/// Session file name, relative to the config root
+pub const SESSION_FILE: &'static str = "session.json";
+
+/// Which plugins to run and what they output
+///
+/// Headless mode reads this from a settings file.
+/// The gui saves it as the session when it closes and restores it on the next start.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Names of the plugins to start
+    pub plugins: Vec<String>,
+    /// Ids of the devices to output in each slot of a frontend, by frontend name
+    pub slots: HashMap<String, Vec<Option<String>>>,
+    /// Name of the saved config to load for a device when it connects, by device id
+    pub configs: HashMap<String, String>,
+    /// Settings of each plugin, by plugin name
+    pub plugin_settings: HashMap<String, serde_json::Value>,
+}
+
+impl Settings {
+    pub fn load(path: &Path) -> Result<Self> {
+        let string = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read file '{}'", path.display()))?;
+
+        serde_json::from_str(&string)
+            .with_context(|| format!("failed to deserialize file '{}'", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let string = serde_json::to_string_pretty(self).context("failed to serialize settings")?;
+
+        write_atomic(path, string.as_bytes())
+            .with_context(|| format!("failed to write file '{}'", path.display()))
+    }
+
+    /// Records the running plugins, their settings and the devices in their slots
+    ///
+    /// Slots and configs from `previous` are kept for devices that are not connected,
+    /// so they are still restored when those devices connect after the next start.
+    pub fn capture(
+        engine: &Engine,
+        plugins: &[Arc<dyn Plugin + Send + Sync>],
+        previous: &Settings,
+    ) -> Self {
+        let mut settings = Settings {
+            configs: previous.configs.clone(),
+            ..Settings::default()
+        };
+
+        for plugin in plugins {
+            let name = plugin.name().to_owned();
+
+            if plugin.status() == PluginStatus::Running {
+                settings.plugins.push(name.clone());
+            }
+
+            if let Some(plugin_settings) = plugin.settings() {
+                settings
+                    .plugin_settings
+                    .insert(name.clone(), plugin_settings);
+            }
+
+            let previous_slots = previous.slots.get(&name);
+
+            let slots = (0..plugin.slots())
+                .map(|slot| match plugin.slot_device(slot) {
+                    // the registry also knows devices that disconnected after being bound
+                    Some(uuid) => engine.known_device(&uuid).map(|known| known.id),
+                    // a connected device was unbound by hand, an absent one was never bound
+                    None => previous_slots
+                        .and_then(|slots| slots.get(slot).cloned().flatten())
+                        .filter(|id| !is_connected(engine, id)),
+                })
+                .collect::<Vec<_>>();
+
+            if slots.iter().any(Option::is_some) {
+                settings.slots.insert(name, slots);
+            }
+        }
+
+        settings
+    }
+}
+
+fn is_connected(engine: &Engine, id: &str) -> bool {
+    engine
+        .known_devices()
+        .iter()
+        .any(|known| known.id == id && engine.is_connected(&known.uuid))
+}
@@ -0,0 +1,98 @@
+use std::sync::LazyLock;
+
+use bindlang::{ty::{BLType, Type}, to_struct};
+use serde::{Deserialize, Serialize};
+
+use super::ComponentData;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TriggerSide {
+    Left,
+    Right,
+}
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AdaptiveTriggerInfo {
+    pub side: TriggerSide,
+}
+
+impl AdaptiveTriggerInfo {
+    pub fn new(side: TriggerSide) -> Self {
+        AdaptiveTriggerInfo { side }
+    }
+}
+
+pub type AdaptiveTriggerConfig = ();
+
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TriggerEffect {
+    Off = 0,
+    /// Constant resistance from `start` to the end of the trigger's travel
+    Resistance = 1,
+    /// Resistance from `start` to `end` that gives way past `end`, like a gun trigger
+    Weapon = 2,
+    /// Vibrates from `start` to the end of the trigger's travel
+    Vibration = 3,
+}
+
+impl TriggerEffect {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => TriggerEffect::Resistance,
+            2 => TriggerEffect::Weapon,
+            3 => TriggerEffect::Vibration,
+            _ => TriggerEffect::Off,
+        }
+    }
+}
+
+/// The effect an adaptive trigger is playing, set by the backend as it applies feedback
+///
+/// Positions are along the trigger's travel, from 0 (released) to 255 (fully pressed).
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AdaptiveTrigger {
+    /// A [`TriggerEffect`] stored as its `u8` value
+    pub effect: u8,
+    pub start: u8,
+    pub end: u8,
+    pub strength: u8,
+}
+
+impl AdaptiveTrigger {
+    pub fn effect(&self) -> TriggerEffect {
+        TriggerEffect::from_u8(self.effect)
+    }
+
+    pub fn set_effect(&mut self, effect: TriggerEffect) {
+        self.effect = effect as u8;
+    }
+}
+
+unsafe impl BLType for AdaptiveTrigger {
+    fn bl_type() -> Type {
+        static TYPE: LazyLock<Type> = LazyLock::new(|| {
+            to_struct! {
+                name = AdaptiveTrigger;
+                0:  effect:   u8;
+                1:  start:    u8;
+                2:  end:      u8;
+                3:  strength: u8;
+            }
+        });
+
+        TYPE.clone()
+    }
+}
+
+impl ComponentData for AdaptiveTrigger {
+    type Config = AdaptiveTriggerConfig;
+    type Info = AdaptiveTriggerInfo;
+
+    fn update(&mut self, from: &Self) {
+        self.clone_from(from);
+    }
+
+    fn configure(&mut self, _: &Self::Config, _: &Self) {}
+}
@@ -3,9 +3,9 @@ use std::collections::HashMap;
 use bindlang::{ty::{BLType, Type, Struct}, to_struct};
 use serde::{Deserialize, Serialize};
 
-use super::ComponentData;
+use super::{validate_range, ComponentData};
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct AnalogsInfo {
     pub analogs: u8,
 }
@@ -16,7 +16,8 @@ impl Default for AnalogsInfo {
     }
 }
 
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
 pub struct AnalogsConfig {
     pub ranges: [[u8; 2]; 8],
 }
@@ -30,7 +31,7 @@ impl Default for AnalogsConfig {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Analogs {
     pub analogs: [u8; 8],
 }
@@ -65,7 +66,7 @@ impl ComponentData for Analogs {
         self.clone_from(from);
     }
 
-    fn configure(&mut self, config: &AnalogsConfig) {
+    fn configure(&mut self, config: &AnalogsConfig, _: &Self) {
         for i in 0..8 {
             let min = config.ranges[i][0] as f32;
             let max = config.ranges[i][1] as f32;
@@ -74,4 +75,12 @@ impl ComponentData for Analogs {
                 (((f32::clamp(self.analogs[i] as f32, min, max) - min) / range) * 255.0) as u8;
         }
     }
+
+    fn validate(config: &AnalogsConfig, _: &AnalogsInfo) -> Result<(), String> {
+        for (i, range) in config.ranges.iter().enumerate() {
+            validate_range(*range).map_err(|err| format!("analog {i}: {err}"))?;
+        }
+
+        Ok(())
+    }
 }
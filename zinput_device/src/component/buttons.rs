@@ -1,8 +1,13 @@
 use bindlang::{ty::{BLType, Type, BitNames}, util::Width};
+use serde::{Deserialize, Serialize};
+use serde_big_array::BigArray;
 
-use super::ComponentData;
+use super::{
+    controller::{Button, Controller},
+    ComponentData,
+};
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ButtonsInfo {
     pub buttons: u64,
 }
@@ -13,10 +18,129 @@ impl Default for ButtonsInfo {
     }
 }
 
-pub type ButtonsConfig = ();
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ButtonsConfig {
+    /// Bit `i` is moved to bit `remap[i]`
+    #[serde(with = "BigArray")]
+    pub remap: [u8; 64],
+    /// Sent to the device's first controller, from the remapped bits
+    pub routes: Vec<ButtonRoute>,
+}
+
+impl ButtonsConfig {
+    /// Presses the controller buttons and analogs routed from pressed bits.
+    /// Runs after every component is configured, so the controller's remap is not applied to them.
+    pub(crate) fn apply(&self, buttons: &Buttons, controller: Option<&mut Controller>) {
+        let Some(controller) = controller
+        else { return; };
+
+        for route in &self.routes {
+            if buttons.buttons & (1 << route.bit) == 0 {
+                continue;
+            }
+
+            match route.target {
+                RouteTarget::Button(button) => controller.buttons |= 1 << button,
+                RouteTarget::Analog(analog) => analog.press(controller),
+            }
+        }
+    }
+}
+
+impl Default for ButtonsConfig {
+    fn default() -> Self {
+        let mut remap = [0; 64];
+        for i in 0..64 {
+            remap[i] = i as u8;
+        }
+
+        ButtonsConfig {
+            remap,
+            routes: Vec::new(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct ButtonRoute {
+    pub bit: u8,
+    pub target: RouteTarget,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum RouteTarget {
+    /// A controller button bit
+    Button(u8),
+    Analog(AnalogTarget),
+}
+
+/// A controller analog value, or one direction of a stick
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum AnalogTarget {
+    LeftStickUp,
+    LeftStickDown,
+    LeftStickLeft,
+    LeftStickRight,
+    RightStickUp,
+    RightStickDown,
+    RightStickLeft,
+    RightStickRight,
+    L1,
+    R1,
+    L2,
+    R2,
+}
+
+impl AnalogTarget {
+    pub const TARGETS: [AnalogTarget; 12] = {
+        use AnalogTarget::*;
+        [
+            LeftStickUp, LeftStickDown, LeftStickLeft, LeftStickRight, RightStickUp,
+            RightStickDown, RightStickLeft, RightStickRight, L1, R1, L2, R2,
+        ]
+    };
+
+    /// Moves the analog all the way
+    pub fn press(&self, controller: &mut Controller) {
+        match self {
+            AnalogTarget::LeftStickUp => controller.left_stick_y = u16::MAX,
+            AnalogTarget::LeftStickDown => controller.left_stick_y = 0,
+            AnalogTarget::LeftStickLeft => controller.left_stick_x = 0,
+            AnalogTarget::LeftStickRight => controller.left_stick_x = u16::MAX,
+            AnalogTarget::RightStickUp => controller.right_stick_y = u16::MAX,
+            AnalogTarget::RightStickDown => controller.right_stick_y = 0,
+            AnalogTarget::RightStickLeft => controller.right_stick_x = 0,
+            AnalogTarget::RightStickRight => controller.right_stick_x = u16::MAX,
+            AnalogTarget::L1 => controller.l1_analog = u16::MAX,
+            AnalogTarget::R1 => controller.r1_analog = u16::MAX,
+            AnalogTarget::L2 => controller.l2_analog = u16::MAX,
+            AnalogTarget::R2 => controller.r2_analog = u16::MAX,
+        }
+    }
+}
+
+impl std::fmt::Display for AnalogTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalogTarget::LeftStickUp => write!(f, "Left Stick Up"),
+            AnalogTarget::LeftStickDown => write!(f, "Left Stick Down"),
+            AnalogTarget::LeftStickLeft => write!(f, "Left Stick Left"),
+            AnalogTarget::LeftStickRight => write!(f, "Left Stick Right"),
+            AnalogTarget::RightStickUp => write!(f, "Right Stick Up"),
+            AnalogTarget::RightStickDown => write!(f, "Right Stick Down"),
+            AnalogTarget::RightStickLeft => write!(f, "Right Stick Left"),
+            AnalogTarget::RightStickRight => write!(f, "Right Stick Right"),
+            AnalogTarget::L1 => write!(f, "L1 Analog"),
+            AnalogTarget::R1 => write!(f, "R1 Analog"),
+            AnalogTarget::L2 => write!(f, "L2 Analog"),
+            AnalogTarget::R2 => write!(f, "R2 Analog"),
+        }
+    }
+}
 
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Buttons {
     pub buttons: u64,
 }
@@ -41,5 +165,47 @@ impl ComponentData for Buttons {
         self.clone_from(from);
     }
 
-    fn configure(&mut self, _: &Self::Config) {}
+    fn configure(&mut self, config: &Self::Config, _: &Self) {
+        let mut output_buttons = 0;
+        for i in 0..64 {
+            if self.buttons & (1 << i) != 0 {
+                output_buttons |= 1 << config.remap[i];
+            }
+        }
+
+        self.buttons = output_buttons;
+    }
+
+    fn validate(config: &Self::Config, info: &Self::Info) -> Result<(), String> {
+        if let Some(i) = config.remap.iter().position(|bit| *bit >= 64) {
+            return Err(format!("button {i} is remapped to bit {}", config.remap[i]));
+        }
+
+        for (from, to) in config.remap.iter().enumerate() {
+            // buttons the device doesn't have are never pressed, so where they go doesn't matter
+            if info.buttons & (1 << from) != 0 && info.buttons & (1 << to) == 0 {
+                return Err(format!(
+                    "button {from} is remapped to bit {to}, which the device does not have"
+                ));
+            }
+        }
+
+        for route in &config.routes {
+            if route.bit >= 64 || info.buttons & (1 << route.bit) == 0 {
+                return Err(format!(
+                    "route {route:?} is from a button the device does not have"
+                ));
+            }
+
+            if let RouteTarget::Button(button) = route.target {
+                if Button::try_from_bit(button).is_none() {
+                    return Err(format!(
+                        "route {route:?} is to a controller button that does not exist"
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -4,9 +4,54 @@ use bindlang::{ty::{BLType, Type, BitNames}, to_struct, to_bitfield, util::Width
 use serde::{Deserialize, Serialize};
 use serde_big_array::BigArray;
 
-use super::ComponentData;
+use super::{validate_range, ComponentData};
 
-#[derive(Clone, PartialEq, Eq)]
+#[cfg(test)]
+mod tests;
+
+/// Center of a stick axis. Sticks and analogs range over every `u16` value.
+pub const STICK_CENTER: u16 = 0x8000;
+
+/// Scales an axis from 8 bit hardware to the full range
+pub const fn widen_axis(value: u8) -> u16 {
+    value as u16 * 0x101
+}
+
+/// Scales an axis down for outputs that only take 8 bits
+pub const fn narrow_axis(value: u16) -> u8 {
+    (value >> 8) as u8
+}
+
+/// Converts an axis centered on 0, like XInput's sticks
+pub const fn unsigned_axis(value: i16) -> u16 {
+    (value as u16) ^ 0x8000
+}
+
+/// Converts an axis to one centered on 0
+pub const fn signed_axis(value: u16) -> i16 {
+    (value ^ 0x8000) as i16
+}
+
+/// How far an axis is tilted from [`STICK_CENTER`], from -1 to 1
+pub fn axis_to_tilt(value: u16) -> f32 {
+    let offset = value as f32 - STICK_CENTER as f32;
+    match offset < 0.0 {
+        true => offset / STICK_CENTER as f32,
+        false => offset / (u16::MAX - STICK_CENTER) as f32,
+    }
+}
+
+/// The axis value of a tilt from -1 to 1, so a tilt of 0 is [`STICK_CENTER`]
+pub fn tilt_to_axis(tilt: f32) -> u16 {
+    let tilt = tilt.clamp(-1.0, 1.0);
+    let offset = match tilt < 0.0 {
+        true => tilt * STICK_CENTER as f32,
+        false => tilt * (u16::MAX - STICK_CENTER) as f32,
+    };
+    (STICK_CENTER as f32 + offset).round() as u16
+}
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ControllerInfo {
     pub buttons: u64,
     pub analogs: u8,
@@ -53,7 +98,8 @@ impl Default for ControllerInfo {
     }
 }
 
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
 pub struct ControllerConfig {
     pub left_stick: StickConfig,
     pub right_stick: StickConfig,
@@ -61,6 +107,10 @@ pub struct ControllerConfig {
     pub r1_range: [u8; 2],
     pub l2_range: [u8; 2],
     pub r2_range: [u8; 2],
+    pub l1_trigger: TriggerConfig,
+    pub r1_trigger: TriggerConfig,
+    pub l2_trigger: TriggerConfig,
+    pub r2_trigger: TriggerConfig,
     #[serde(with = "BigArray")]
     pub remap: [u8; 64],
 }
@@ -79,55 +129,301 @@ impl Default for ControllerConfig {
             r1_range: [0, 255],
             l2_range: [0, 255],
             r2_range: [0, 255],
+            l1_trigger: Default::default(),
+            r1_trigger: Default::default(),
+            l2_trigger: Default::default(),
+            r2_trigger: Default::default(),
             remap,
         }
     }
 }
 
-#[derive(Clone, Deserialize, Serialize)]
+/// Links a trigger's analog value and its button
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TriggerConfig {
+    /// Presses the button from the analog value, after `l1_range`..`r2_range` is applied
+    pub threshold: Option<Threshold>,
+    /// Sets the analog value from the button, for triggers that are only digital
+    pub emulate: bool,
+    /// How far the emulated analog value moves each update out of 255, or instantly if `None`
+    pub ramp: Option<u8>,
+}
+
+impl TriggerConfig {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(threshold) = &self.threshold {
+            if threshold.press == 0 {
+                return Err("threshold presses at 0, so the button is never released".to_owned());
+            }
+            if threshold.release > threshold.press {
+                return Err(format!(
+                    "threshold releases at {}, above where it presses at {}",
+                    threshold.release, threshold.press
+                ));
+            }
+        }
+
+        if self.ramp == Some(0) {
+            return Err("ramp never moves the analog value".to_owned());
+        }
+
+        Ok(())
+    }
+
+    /// `was_pressed` is whether the button was pressed in the update before
+    fn configure(
+        &self,
+        analog: &mut u16,
+        button: Button,
+        buttons: &mut u64,
+        was_pressed: bool,
+        previous: u16,
+    ) {
+        if let Some(threshold) = &self.threshold {
+            let pressed = match was_pressed {
+                true => *analog > widen_axis(threshold.release),
+                false => *analog >= widen_axis(threshold.press),
+            };
+
+            if pressed {
+                button.set_pressed(buttons);
+            }
+        }
+
+        if self.emulate {
+            let target = match button.is_pressed(*buttons) {
+                true => u16::MAX,
+                false => 0,
+            };
+
+            let emulated = match self.ramp.map(widen_axis) {
+                Some(ramp) if target > previous => previous.saturating_add(ramp).min(target),
+                Some(ramp) => previous.saturating_sub(ramp).max(target),
+                None => target,
+            };
+
+            *analog = (*analog).max(emulated);
+        }
+    }
+}
+
+impl Default for TriggerConfig {
+    fn default() -> Self {
+        TriggerConfig {
+            threshold: None,
+            emulate: false,
+            ramp: None,
+        }
+    }
+}
+
+/// The button is pressed at `press` and stays pressed until the analog value drops to `release`.
+/// Both are out of 255, like the analog ranges.
+#[derive(Copy, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Threshold {
+    pub press: u8,
+    pub release: u8,
+}
+
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
 pub struct StickConfig {
+    /// Inner deadzone, out of 255
     pub deadzone: u8,
+    pub deadzone_mode: DeadzoneMode,
+    /// How far from the edge the stick reaches full tilt, out of 255
+    pub outer_deadzone: u8,
+    /// Smallest tilt sent once the stick leaves the deadzone, out of 255.
+    /// Cancels out the deadzone of a game.
+    pub anti_deadzone: u8,
+    pub curve: ResponseCurve,
+
+    pub invert_x: bool,
+    pub invert_y: bool,
+    /// Counterclockwise, in degrees
+    pub rotation: f32,
+    /// Maps a square gate onto a circle, for sticks that reach the corners
+    pub square_to_circle: bool,
 
     pub samples: Option<[f32; 32]>,
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum DeadzoneMode {
+    /// Applied to the distance from the center
+    Radial,
+    /// Applied to each axis on its own
+    Axial,
+}
+
+/// Maps a tilt from 0 to 1 onto the tilt that is sent
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub enum ResponseCurve {
+    Linear,
+    /// `tilt.powf(exponent)`
+    Exponent(f32),
+    /// `[input, output]` pairs with increasing inputs, linearly interpolated.
+    /// `[0, 0]` and `[1, 1]` are implied.
+    Points(Vec<[f32; 2]>),
+}
+
+impl ResponseCurve {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            ResponseCurve::Linear => {}
+            ResponseCurve::Exponent(exponent) => {
+                if !exponent.is_finite() || *exponent <= 0.0 {
+                    return Err(format!("curve exponent is {exponent}"));
+                }
+            }
+            ResponseCurve::Points(points) => {
+                let mut last = 0.0;
+                for (i, [input, output]) in points.iter().enumerate() {
+                    if !(0.0..=1.0).contains(input) || !(0.0..=1.0).contains(output) {
+                        return Err(format!("curve point {i} is outside of 0 to 1"));
+                    }
+                    if *input <= last {
+                        return Err(format!("curve point {i} does not come after the point before it"));
+                    }
+                    last = *input;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn apply(&self, tilt: f32) -> f32 {
+        match self {
+            ResponseCurve::Linear => tilt,
+            ResponseCurve::Exponent(exponent) => tilt.powf(*exponent),
+            ResponseCurve::Points(points) => {
+                let mut from = [0.0, 0.0];
+                for to in points.iter().copied().chain([[1.0, 1.0]]) {
+                    if tilt <= to[0] {
+                        let influence = (tilt - from[0]) / (to[0] - from[0]);
+                        return from[1] + (to[1] - from[1]) * influence;
+                    }
+                    from = to;
+                }
+
+                1.0
+            }
+        }
+    }
+}
+
 impl StickConfig {
-    fn configure(&self, x: u8, y: u8) -> [u8; 2] {
-        if self.deadzone == 0 && self.samples.is_none() {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(samples) = &self.samples {
+            if let Some(i) = samples.iter().position(|s| !s.is_finite() || *s <= 0.0) {
+                return Err(format!("calibration sample {i} is {}", samples[i]));
+            }
+        }
+
+        if self.deadzone as u16 + self.outer_deadzone as u16 >= 255 {
+            return Err("deadzone and outer deadzone cover the whole stick".to_owned());
+        }
+
+        if !self.rotation.is_finite() {
+            return Err(format!("rotation is {}", self.rotation));
+        }
+
+        self.curve.validate()
+    }
+
+    fn is_identity(&self) -> bool {
+        self.deadzone == 0
+            && self.outer_deadzone == 0
+            && self.anti_deadzone == 0
+            && self.curve == ResponseCurve::Linear
+            && !self.invert_x
+            && !self.invert_y
+            && self.rotation == 0.0
+            && !self.square_to_circle
+            && self.samples.is_none()
+    }
+
+    fn configure(&self, x: u16, y: u16) -> [u16; 2] {
+        if self.is_identity() {
             return [x, y];
         }
 
-        let dzf = self.deadzone as f32 / 255.0;
-        let xf = (x as f32 - 127.5) / 127.5;
-        let yf = (y as f32 - 127.5) / 127.5;
-        let scalar = f32::sqrt(xf.powi(2) + yf.powi(2));
-
-        let max = match &self.samples {
-            Some(samples) => {
-                let mut angle = f32::atan2(yf, xf);
-                if angle < 0.0 {
-                    angle = 2.0 * std::f32::consts::PI + angle;
-                }
-                Self::sample(samples, angle)
+        let mut xf = axis_to_tilt(x);
+        let mut yf = axis_to_tilt(y);
+
+        // the gate was sampled from the raw stick, so it is scaled to a circle before anything moves it
+        if let Some(samples) = &self.samples {
+            let mut angle = f32::atan2(yf, xf);
+            if angle < 0.0 {
+                angle = 2.0 * std::f32::consts::PI + angle;
+            }
+
+            let gate = Self::sample(samples, angle);
+            if gate <= 0.0 {
+                return [STICK_CENTER, STICK_CENTER];
             }
-            None => 1.0,
-        };
 
-        let range = max - dzf;
+            xf /= gate;
+            yf /= gate;
+        }
+
+        if self.square_to_circle {
+            let (xs, ys) = (xf.clamp(-1.0, 1.0), yf.clamp(-1.0, 1.0));
+            xf = xs * f32::sqrt(1.0 - ys * ys / 2.0);
+            yf = ys * f32::sqrt(1.0 - xs * xs / 2.0);
+        }
+
+        if self.invert_x {
+            xf = -xf;
+        }
+        if self.invert_y {
+            yf = -yf;
+        }
+
+        if self.rotation != 0.0 {
+            let (sin, cos) = self.rotation.to_radians().sin_cos();
+            [xf, yf] = [xf * cos - yf * sin, xf * sin + yf * cos];
+        }
+
+        let dzf = self.deadzone as f32 / 255.0;
+        let outer = 1.0 - self.outer_deadzone as f32 / 255.0;
+        let range = outer - dzf;
 
         if range <= 0.0 {
-            return [128, 128];
+            return [STICK_CENTER, STICK_CENTER];
         }
 
-        let new_scalar = (scalar.clamp(dzf, max) - dzf) / range;
+        // tilt from 0 to 1 after the deadzones, curve and anti-deadzone
+        let shape = |tilt: f32| {
+            let tilt = (tilt.clamp(dzf, outer) - dzf) / range;
+            if tilt <= 0.0 {
+                return 0.0;
+            }
 
-        let xf = (xf / scalar) * new_scalar;
-        let yf = (yf / scalar) * new_scalar;
+            let anti = self.anti_deadzone as f32 / 255.0;
+            anti + (1.0 - anti) * self.curve.apply(tilt)
+        };
 
-        let x = (xf * 127.5 + 127.5) as u8;
-        let y = (yf * 127.5 + 127.5) as u8;
+        let [xf, yf] = match self.deadzone_mode {
+            DeadzoneMode::Radial => {
+                let scalar = f32::sqrt(xf.powi(2) + yf.powi(2));
+                match scalar > 0.0 {
+                    true => {
+                        let new_scalar = shape(scalar);
+                        [(xf / scalar) * new_scalar, (yf / scalar) * new_scalar]
+                    }
+                    false => [0.0, 0.0],
+                }
+            }
+            DeadzoneMode::Axial => [
+                shape(xf.abs()).copysign(xf),
+                shape(yf.abs()).copysign(yf),
+            ],
+        };
 
-        [x, y]
+        [tilt_to_axis(xf), tilt_to_axis(yf)]
     }
 
     fn sample(samples: &[f32; 32], angle: f32) -> f32 {
@@ -160,6 +456,15 @@ impl Default for StickConfig {
     fn default() -> Self {
         StickConfig {
             deadzone: 0,
+            deadzone_mode: DeadzoneMode::Radial,
+            outer_deadzone: 0,
+            anti_deadzone: 0,
+            curve: ResponseCurve::Linear,
+
+            invert_x: false,
+            invert_y: false,
+            rotation: 0.0,
+            square_to_circle: false,
 
             samples: None,
         }
@@ -167,17 +472,19 @@ impl Default for StickConfig {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Controller {
     pub buttons: u64,
-    pub left_stick_x: u8,
-    pub left_stick_y: u8,
-    pub right_stick_x: u8,
-    pub right_stick_y: u8,
-    pub l1_analog: u8,
-    pub r1_analog: u8,
-    pub l2_analog: u8,
-    pub r2_analog: u8,
+    /// Positive = Right
+    pub left_stick_x: u16,
+    /// Positive = Up
+    pub left_stick_y: u16,
+    pub right_stick_x: u16,
+    pub right_stick_y: u16,
+    pub l1_analog: u16,
+    pub r1_analog: u16,
+    pub l2_analog: u16,
+    pub r2_analog: u16,
 }
 
 unsafe impl BLType for Controller {
@@ -218,14 +525,14 @@ unsafe impl BLType for Controller {
             to_struct! {
                 name = Controller;
                 0:  buttons:       ButtonType;
-                8:  left_stick_x:  u8;
-                9:  left_stick_y:  u8;
-                10: right_stick_x: u8;
-                11: right_stick_y: u8;
-                12: l1_analog:     u8;
-                13: r1_analog:     u8;
-                14: l2_analog:     u8;
-                15: r2_analog:     u8;
+                8:  left_stick_x:  u16;
+                10: left_stick_y:  u16;
+                12: right_stick_x: u16;
+                14: right_stick_y: u16;
+                16: l1_analog:     u16;
+                18: r1_analog:     u16;
+                20: l2_analog:     u16;
+                22: r2_analog:     u16;
             }
         });
         
@@ -237,10 +544,10 @@ impl Default for Controller {
     fn default() -> Self {
         Controller {
             buttons: 0,
-            left_stick_x: 127,
-            left_stick_y: 127,
-            right_stick_x: 127,
-            right_stick_y: 127,
+            left_stick_x: STICK_CENTER,
+            left_stick_y: STICK_CENTER,
+            right_stick_x: STICK_CENTER,
+            right_stick_y: STICK_CENTER,
             l1_analog: 0,
             r1_analog: 0,
             l2_analog: 0,
@@ -257,7 +564,7 @@ impl ComponentData for Controller {
         self.clone_from(from);
     }
 
-    fn configure(&mut self, config: &Self::Config) {
+    fn configure(&mut self, config: &Self::Config, previous: &Self) {
         let [lx, ly] = config
             .left_stick
             .configure(self.left_stick_x, self.left_stick_y);
@@ -273,6 +580,17 @@ impl ComponentData for Controller {
         self.l2_analog = configure_analog(self.l2_analog, config.l2_range);
         self.r2_analog = configure_analog(self.r2_analog, config.r2_range);
 
+        for (trigger, analog, previous_analog, button) in [
+            (&config.l1_trigger, &mut self.l1_analog, previous.l1_analog, Button::L1),
+            (&config.r1_trigger, &mut self.r1_analog, previous.r1_analog, Button::R1),
+            (&config.l2_trigger, &mut self.l2_analog, previous.l2_analog, Button::L2),
+            (&config.r2_trigger, &mut self.r2_analog, previous.r2_analog, Button::R2),
+        ] {
+            // the previous buttons are already remapped
+            let was_pressed = previous.buttons & (1 << config.remap[button.bit() as usize]) != 0;
+            trigger.configure(analog, button, &mut self.buttons, was_pressed, previous_analog);
+        }
+
         let mut output_buttons = 0;
         for i in 0..64 {
             if self.buttons & (1 << i) != 0 {
@@ -282,9 +600,55 @@ impl ComponentData for Controller {
 
         self.buttons = output_buttons;
     }
+
+    fn validate(config: &Self::Config, info: &Self::Info) -> Result<(), String> {
+        config
+            .left_stick
+            .validate()
+            .map_err(|err| format!("left stick: {err}"))?;
+        config
+            .right_stick
+            .validate()
+            .map_err(|err| format!("right stick: {err}"))?;
+
+        for (name, range) in [
+            ("l1", config.l1_range),
+            ("r1", config.r1_range),
+            ("l2", config.l2_range),
+            ("r2", config.r2_range),
+        ] {
+            validate_range(range).map_err(|err| format!("{name}: {err}"))?;
+        }
+
+        for (name, trigger) in [
+            ("l1", &config.l1_trigger),
+            ("r1", &config.r1_trigger),
+            ("l2", &config.l2_trigger),
+            ("r2", &config.r2_trigger),
+        ] {
+            trigger.validate().map_err(|err| format!("{name}: {err}"))?;
+        }
+
+        for (from, to) in config.remap.iter().enumerate() {
+            if *to >= 64 {
+                return Err(format!(
+                    "button {from} is remapped to {to}, which is out of range"
+                ));
+            }
+
+            // buttons the device doesn't have are never pressed, so where they go doesn't matter
+            if info.buttons & (1 << from) != 0 && info.buttons & (1 << to) == 0 {
+                return Err(format!(
+                    "button {from} is remapped to {to}, which the controller does not have"
+                ));
+            }
+        }
+
+        Ok(())
+    }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Button {
     A,
     B,
@@ -409,9 +773,9 @@ impl std::fmt::Display for Button {
     }
 }
 
-fn configure_analog(analog: u8, range: [u8; 2]) -> u8 {
-    let min = range[0] as f32;
-    let max = range[1] as f32;
+fn configure_analog(analog: u16, range: [u8; 2]) -> u16 {
+    let min = widen_axis(range[0]) as f32;
+    let max = widen_axis(range[1]) as f32;
     let range = max - min;
-    (((f32::clamp(analog as f32, min, max) - min) / range) * 255.0) as u8
+    (((f32::clamp(analog as f32, min, max) - min) / range) * 65535.0) as u16
 }
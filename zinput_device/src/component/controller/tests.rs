@@ -0,0 +1,450 @@
+use super::{
+    axis_to_tilt, narrow_axis, signed_axis, tilt_to_axis, unsigned_axis, widen_axis, Button,
+    ComponentData, Controller, ControllerConfig, DeadzoneMode, ResponseCurve, StickConfig,
+    Threshold, TriggerConfig, STICK_CENTER,
+};
+
+#[test]
+fn widen_and_narrow() {
+    assert_eq!(widen_axis(0), 0);
+    assert_eq!(widen_axis(0x80), 0x8080);
+    assert_eq!(widen_axis(255), u16::MAX);
+
+    for value in 0..=255 {
+        assert_eq!(narrow_axis(widen_axis(value)), value);
+    }
+    assert_eq!(narrow_axis(STICK_CENTER), 0x80);
+}
+
+#[test]
+fn signed_and_unsigned() {
+    assert_eq!(unsigned_axis(i16::MIN), 0);
+    assert_eq!(unsigned_axis(0), STICK_CENTER);
+    assert_eq!(unsigned_axis(i16::MAX), u16::MAX);
+
+    for value in [i16::MIN, -1, 0, 1, i16::MAX] {
+        assert_eq!(signed_axis(unsigned_axis(value)), value);
+    }
+}
+
+#[test]
+fn tilt() {
+    assert_eq!(axis_to_tilt(0), -1.0);
+    assert_eq!(axis_to_tilt(STICK_CENTER), 0.0);
+    assert_eq!(axis_to_tilt(u16::MAX), 1.0);
+
+    assert_eq!(tilt_to_axis(-1.0), 0);
+    assert_eq!(tilt_to_axis(0.0), STICK_CENTER);
+    assert_eq!(tilt_to_axis(1.0), u16::MAX);
+    // out of range tilts are clamped
+    assert_eq!(tilt_to_axis(-2.0), 0);
+    assert_eq!(tilt_to_axis(2.0), u16::MAX);
+
+    for value in [
+        0,
+        1,
+        STICK_CENTER - 1,
+        STICK_CENTER,
+        STICK_CENTER + 1,
+        u16::MAX,
+    ] {
+        assert_eq!(tilt_to_axis(axis_to_tilt(value)), value);
+    }
+}
+
+const FULL: u16 = u16::MAX;
+
+/// Stick values are converted to floats and back, which can be off by one
+fn assert_near(actual: [u16; 2], expected: [u16; 2]) {
+    assert!(
+        actual
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| actual.abs_diff(expected) <= 1),
+        "{actual:?} is not {expected:?}"
+    );
+}
+
+/// The center and full tilt in every direction are kept
+fn edges(config: &StickConfig) {
+    assert_near(
+        config.configure(STICK_CENTER, STICK_CENTER),
+        [STICK_CENTER; 2],
+    );
+    assert_near(config.configure(0, STICK_CENTER), [0, STICK_CENTER]);
+    assert_near(config.configure(FULL, STICK_CENTER), [FULL, STICK_CENTER]);
+    assert_near(config.configure(STICK_CENTER, 0), [STICK_CENTER, 0]);
+    assert_near(config.configure(STICK_CENTER, FULL), [STICK_CENTER, FULL]);
+}
+
+#[test]
+fn identity() {
+    let config = StickConfig::default();
+
+    for value in [
+        0,
+        1,
+        STICK_CENTER - 1,
+        STICK_CENTER,
+        tilt_to_axis(0.5),
+        FULL,
+    ] {
+        assert_eq!(config.configure(value, FULL - value), [value, FULL - value]);
+    }
+}
+
+#[test]
+fn radial_deadzone() {
+    let config = StickConfig {
+        deadzone: 51,
+        ..Default::default()
+    };
+
+    edges(&config);
+    assert_near(
+        config.configure(tilt_to_axis(0.1), tilt_to_axis(0.1)),
+        [STICK_CENTER; 2],
+    );
+    // 0.2 to 1.0 is spread over the whole range
+    assert_near(
+        config.configure(tilt_to_axis(0.6), STICK_CENTER),
+        [tilt_to_axis(0.5), STICK_CENTER],
+    );
+    // the distance from the center decides, so a small y is kept
+    assert_ne!(
+        config.configure(tilt_to_axis(0.6), tilt_to_axis(0.1))[1],
+        STICK_CENTER
+    );
+}
+
+#[test]
+fn axial_deadzone() {
+    let config = StickConfig {
+        deadzone: 51,
+        deadzone_mode: DeadzoneMode::Axial,
+        ..Default::default()
+    };
+
+    edges(&config);
+    assert_near(
+        config.configure(tilt_to_axis(0.6), tilt_to_axis(0.1)),
+        [tilt_to_axis(0.5), STICK_CENTER],
+    );
+    assert_near(
+        config.configure(tilt_to_axis(0.1), tilt_to_axis(0.6)),
+        [STICK_CENTER, tilt_to_axis(0.5)],
+    );
+}
+
+#[test]
+fn outer_deadzone() {
+    let config = StickConfig {
+        outer_deadzone: 51,
+        ..Default::default()
+    };
+
+    edges(&config);
+    assert_near(
+        config.configure(tilt_to_axis(0.8), STICK_CENTER),
+        [FULL, STICK_CENTER],
+    );
+    assert_near(
+        config.configure(tilt_to_axis(0.4), STICK_CENTER),
+        [tilt_to_axis(0.5), STICK_CENTER],
+    );
+}
+
+#[test]
+fn anti_deadzone() {
+    let config = StickConfig {
+        anti_deadzone: 51,
+        ..Default::default()
+    };
+
+    edges(&config);
+    assert_near(
+        config.configure(tilt_to_axis(0.01), STICK_CENTER),
+        [tilt_to_axis(0.208), STICK_CENTER],
+    );
+    assert_near(
+        config.configure(tilt_to_axis(0.5), STICK_CENTER),
+        [tilt_to_axis(0.6), STICK_CENTER],
+    );
+}
+
+#[test]
+fn curves() {
+    let exponent = StickConfig {
+        curve: ResponseCurve::Exponent(2.0),
+        ..Default::default()
+    };
+
+    edges(&exponent);
+    assert_near(
+        exponent.configure(tilt_to_axis(0.5), STICK_CENTER),
+        [tilt_to_axis(0.25), STICK_CENTER],
+    );
+
+    let points = StickConfig {
+        curve: ResponseCurve::Points(vec![[0.5, 0.25]]),
+        ..Default::default()
+    };
+
+    edges(&points);
+    assert_near(
+        points.configure(tilt_to_axis(0.5), STICK_CENTER),
+        [tilt_to_axis(0.25), STICK_CENTER],
+    );
+    assert_near(
+        points.configure(tilt_to_axis(0.75), STICK_CENTER),
+        [tilt_to_axis(0.625), STICK_CENTER],
+    );
+}
+
+#[test]
+fn inversion() {
+    let config = StickConfig {
+        invert_x: true,
+        ..Default::default()
+    };
+
+    assert_near(
+        config.configure(STICK_CENTER, STICK_CENTER),
+        [STICK_CENTER; 2],
+    );
+    assert_near(config.configure(0, STICK_CENTER), [FULL, STICK_CENTER]);
+    assert_near(config.configure(FULL, STICK_CENTER), [0, STICK_CENTER]);
+
+    let config = StickConfig {
+        invert_y: true,
+        ..Default::default()
+    };
+
+    assert_near(
+        config.configure(tilt_to_axis(0.5), tilt_to_axis(0.5)),
+        [tilt_to_axis(0.5), tilt_to_axis(-0.5)],
+    );
+}
+
+#[test]
+fn rotation() {
+    let config = StickConfig {
+        rotation: 90.0,
+        ..Default::default()
+    };
+
+    assert_near(
+        config.configure(STICK_CENTER, STICK_CENTER),
+        [STICK_CENTER; 2],
+    );
+    // counterclockwise, from right to up
+    assert_near(config.configure(FULL, STICK_CENTER), [STICK_CENTER, FULL]);
+    assert_near(config.configure(STICK_CENTER, FULL), [0, STICK_CENTER]);
+}
+
+#[test]
+fn square_to_circle() {
+    let config = StickConfig {
+        square_to_circle: true,
+        ..Default::default()
+    };
+
+    assert_near(
+        config.configure(STICK_CENTER, STICK_CENTER),
+        [STICK_CENTER; 2],
+    );
+    assert_near(config.configure(FULL, STICK_CENTER), [FULL, STICK_CENTER]);
+    assert_near(
+        config.configure(FULL, FULL),
+        [tilt_to_axis(std::f32::consts::FRAC_1_SQRT_2); 2],
+    );
+}
+
+#[test]
+fn calibration_samples() {
+    let config = StickConfig {
+        samples: Some([0.5; 32]),
+        ..Default::default()
+    };
+
+    assert_near(
+        config.configure(STICK_CENTER, STICK_CENTER),
+        [STICK_CENTER; 2],
+    );
+    // a stick that only reaches halfway gets full tilt there
+    assert_near(
+        config.configure(tilt_to_axis(0.5), STICK_CENTER),
+        [FULL, STICK_CENTER],
+    );
+    assert_near(
+        config.configure(tilt_to_axis(0.25), STICK_CENTER),
+        [tilt_to_axis(0.5), STICK_CENTER],
+    );
+}
+
+#[test]
+fn calibration_before_transforms() {
+    // the stick only reaches halfway to the right
+    let mut samples = [1.0; 32];
+    samples[0] = 0.5;
+
+    let config = StickConfig {
+        invert_x: true,
+        rotation: 90.0,
+        samples: Some(samples),
+        ..Default::default()
+    };
+
+    // full tilt to the right, inverted to the left and rotated down
+    assert_near(
+        config.configure(tilt_to_axis(0.5), STICK_CENTER),
+        [STICK_CENTER, 0],
+    );
+    // up is fully calibrated, rotated to the left
+    assert_near(
+        config.configure(STICK_CENTER, tilt_to_axis(0.5)),
+        [tilt_to_axis(-0.5), STICK_CENTER],
+    );
+}
+
+/// Configures a controller with L2 set to `l2` and `buttons` held for each update,
+/// returning the configured controllers
+fn updates(config: &ControllerConfig, inputs: &[(u16, u64)]) -> Vec<Controller> {
+    let mut previous = Controller::default();
+
+    inputs
+        .iter()
+        .map(|&(l2, buttons)| {
+            let mut controller = Controller {
+                l2_analog: l2,
+                buttons,
+                ..Default::default()
+            };
+            controller.configure(config, &previous);
+            previous = controller;
+            controller
+        })
+        .collect()
+}
+
+#[test]
+fn threshold_hysteresis() {
+    let config = ControllerConfig {
+        l2_trigger: TriggerConfig {
+            threshold: Some(Threshold {
+                press: 200,
+                release: 100,
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let pressed = updates(
+        &config,
+        &[
+            (widen_axis(150), 0),
+            (widen_axis(200), 0),
+            // between release and press, so it stays pressed
+            (widen_axis(150), 0),
+            (widen_axis(101), 0),
+            (widen_axis(100), 0),
+            // ... and stays released
+            (widen_axis(150), 0),
+            (widen_axis(255), 0),
+        ],
+    )
+    .iter()
+    .map(|controller| Button::L2.is_pressed(controller.buttons))
+    .collect::<Vec<_>>();
+
+    assert_eq!(pressed, [false, true, true, true, false, false, true]);
+}
+
+#[test]
+fn threshold_hysteresis_remapped() {
+    let mut config = ControllerConfig {
+        l2_trigger: TriggerConfig {
+            threshold: Some(Threshold {
+                press: 200,
+                release: 100,
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    config.remap[Button::L2.bit() as usize] = Button::A.bit() as u8;
+
+    let pressed = updates(&config, &[(widen_axis(200), 0), (widen_axis(150), 0)])
+        .iter()
+        .map(|controller| Button::A.is_pressed(controller.buttons))
+        .collect::<Vec<_>>();
+
+    assert_eq!(pressed, [true, true]);
+}
+
+#[test]
+fn emulated_ramp() {
+    let config = ControllerConfig {
+        l2_trigger: TriggerConfig {
+            emulate: true,
+            ramp: Some(64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let l2 = 1 << Button::L2.bit();
+
+    let analogs = updates(
+        &config,
+        &[
+            (0, l2),
+            (0, l2),
+            (0, l2),
+            (0, l2),
+            (0, l2),
+            (0, 0),
+            (0, 0),
+            // a real analog value above the emulated one is kept
+            (widen_axis(200), 0),
+            (0, 0),
+        ],
+    )
+    .iter()
+    .map(|controller| controller.l2_analog)
+    .collect::<Vec<_>>();
+
+    assert_eq!(
+        analogs,
+        [
+            widen_axis(64),
+            widen_axis(128),
+            widen_axis(192),
+            u16::MAX,
+            u16::MAX,
+            widen_axis(191),
+            widen_axis(127),
+            widen_axis(200),
+            widen_axis(136),
+        ]
+    );
+}
+
+#[test]
+fn emulated_without_ramp() {
+    let config = ControllerConfig {
+        l2_trigger: TriggerConfig {
+            emulate: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let l2 = 1 << Button::L2.bit();
+
+    let analogs = updates(&config, &[(0, l2), (0, 0), (widen_axis(100), 0)])
+        .iter()
+        .map(|controller| controller.l2_analog)
+        .collect::<Vec<_>>();
+
+    assert_eq!(analogs, [u16::MAX, 0, widen_axis(100)]);
+}
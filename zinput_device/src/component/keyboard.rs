@@ -0,0 +1,230 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use bindlang::{
+    to_struct,
+    ty::{BLType, BitNames, Type},
+    util::Width,
+};
+use serde::{Deserialize, Serialize};
+
+use super::ComponentData;
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct KeyboardInfo {
+    /// The keys the keyboard has, laid out like [`Keyboard::keys`]
+    pub keys: [u64; 4],
+}
+
+impl Default for KeyboardInfo {
+    fn default() -> Self {
+        KeyboardInfo {
+            keys: [u64::MAX; 4],
+        }
+    }
+}
+
+pub type KeyboardConfig = ();
+
+#[repr(C)]
+#[derive(Copy, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Keyboard {
+    /// Bit `n` is set while the key with USB HID usage `n` is held
+    pub keys: [u64; 4],
+}
+
+impl Keyboard {
+    pub fn is_pressed(&self, key: Key) -> bool {
+        let usage = key.usage() as usize;
+        self.keys[usage / 64] & (1 << (usage % 64)) != 0
+    }
+
+    pub fn set_pressed(&mut self, key: Key, pressed: bool) {
+        let usage = key.usage() as usize;
+        if pressed {
+            self.keys[usage / 64] |= 1 << (usage % 64);
+        } else {
+            self.keys[usage / 64] &= !(1 << (usage % 64));
+        }
+    }
+}
+
+unsafe impl BLType for Keyboard {
+    fn bl_type() -> Type {
+        static TYPE: LazyLock<Type> = LazyLock::new(|| {
+            macro_rules! key_words {
+                ($($word:ident = $index:literal),*) => {
+                    $(
+                        struct $word;
+                        unsafe impl BLType for $word {
+                            fn bl_type() -> Type {
+                                let mut names = HashMap::new();
+                                for key in Key::KEYS {
+                                    let usage = key.usage();
+                                    if usage / 64 == $index {
+                                        names.insert(key.name(), usage % 64);
+                                    }
+                                }
+
+                                Type::Bitfield(stringify!($word), Width::W64, BitNames(names))
+                            }
+                        }
+                    )*
+                };
+            }
+
+            key_words!(KeyboardKeys0 = 0, KeyboardKeys1 = 1, KeyboardKeys2 = 2, KeyboardKeys3 = 3);
+
+            to_struct! {
+                name = Keyboard;
+                0:  keys0: KeyboardKeys0;
+                8:  keys1: KeyboardKeys1;
+                16: keys2: KeyboardKeys2;
+                24: keys3: KeyboardKeys3;
+            }
+        });
+
+        TYPE.clone()
+    }
+}
+
+impl ComponentData for Keyboard {
+    type Config = KeyboardConfig;
+    type Info = KeyboardInfo;
+
+    fn update(&mut self, from: &Self) {
+        self.clone_from(from);
+    }
+
+    fn configure(&mut self, _: &Self::Config, _: &Self) {}
+}
+
+macro_rules! keys {
+    ($($key:ident = $usage:literal => $name:literal),* $(,)?) => {
+        /// A keyboard key, numbered by its USB HID usage
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Key {
+            $($key,)*
+        }
+
+        impl Key {
+            pub const KEYS: &'static [Key] = &[$(Key::$key,)*];
+
+            pub const fn usage(&self) -> u8 {
+                match self {
+                    $(Key::$key => $usage,)*
+                }
+            }
+
+            pub fn try_from_usage(usage: u8) -> Option<Self> {
+                match usage {
+                    $($usage => Some(Key::$key),)*
+                    _ => None,
+                }
+            }
+
+            /// The key's name in bindlang
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $(Key::$key => $name,)*
+                }
+            }
+        }
+
+        impl std::fmt::Display for Key {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $(Key::$key => write!(f, stringify!($key)),)*
+                }
+            }
+        }
+    };
+}
+
+keys! {
+    A            = 0x04 => "a",
+    B            = 0x05 => "b",
+    C            = 0x06 => "c",
+    D            = 0x07 => "d",
+    E            = 0x08 => "e",
+    F            = 0x09 => "f",
+    G            = 0x0A => "g",
+    H            = 0x0B => "h",
+    I            = 0x0C => "i",
+    J            = 0x0D => "j",
+    K            = 0x0E => "k",
+    L            = 0x0F => "l",
+    M            = 0x10 => "m",
+    N            = 0x11 => "n",
+    O            = 0x12 => "o",
+    P            = 0x13 => "p",
+    Q            = 0x14 => "q",
+    R            = 0x15 => "r",
+    S            = 0x16 => "s",
+    T            = 0x17 => "t",
+    U            = 0x18 => "u",
+    V            = 0x19 => "v",
+    W            = 0x1A => "w",
+    X            = 0x1B => "x",
+    Y            = 0x1C => "y",
+    Z            = 0x1D => "z",
+    Num1         = 0x1E => "num1",
+    Num2         = 0x1F => "num2",
+    Num3         = 0x20 => "num3",
+    Num4         = 0x21 => "num4",
+    Num5         = 0x22 => "num5",
+    Num6         = 0x23 => "num6",
+    Num7         = 0x24 => "num7",
+    Num8         = 0x25 => "num8",
+    Num9         = 0x26 => "num9",
+    Num0         = 0x27 => "num0",
+    Enter        = 0x28 => "enter",
+    Escape       = 0x29 => "escape",
+    Backspace    = 0x2A => "backspace",
+    Tab          = 0x2B => "tab",
+    Space        = 0x2C => "space",
+    Minus        = 0x2D => "minus",
+    Equal        = 0x2E => "equal",
+    LeftBracket  = 0x2F => "left_bracket",
+    RightBracket = 0x30 => "right_bracket",
+    Backslash    = 0x31 => "backslash",
+    Semicolon    = 0x33 => "semicolon",
+    Apostrophe   = 0x34 => "apostrophe",
+    Grave        = 0x35 => "grave",
+    Comma        = 0x36 => "comma",
+    Period       = 0x37 => "period",
+    Slash        = 0x38 => "slash",
+    CapsLock     = 0x39 => "caps_lock",
+    F1           = 0x3A => "f1",
+    F2           = 0x3B => "f2",
+    F3           = 0x3C => "f3",
+    F4           = 0x3D => "f4",
+    F5           = 0x3E => "f5",
+    F6           = 0x3F => "f6",
+    F7           = 0x40 => "f7",
+    F8           = 0x41 => "f8",
+    F9           = 0x42 => "f9",
+    F10          = 0x43 => "f10",
+    F11          = 0x44 => "f11",
+    F12          = 0x45 => "f12",
+    PrintScreen  = 0x46 => "print_screen",
+    ScrollLock   = 0x47 => "scroll_lock",
+    Pause        = 0x48 => "pause",
+    Insert       = 0x49 => "insert",
+    Home         = 0x4A => "home",
+    PageUp       = 0x4B => "page_up",
+    Delete       = 0x4C => "delete",
+    End          = 0x4D => "end",
+    PageDown     = 0x4E => "page_down",
+    Right        = 0x4F => "right",
+    Left         = 0x50 => "left",
+    Down         = 0x51 => "down",
+    Up           = 0x52 => "up",
+    LeftCtrl     = 0xE0 => "left_ctrl",
+    LeftShift    = 0xE1 => "left_shift",
+    LeftAlt      = 0xE2 => "left_alt",
+    LeftMeta     = 0xE3 => "left_meta",
+    RightCtrl    = 0xE4 => "right_ctrl",
+    RightShift   = 0xE5 => "right_shift",
+    RightAlt     = 0xE6 => "right_alt",
+    RightMeta    = 0xE7 => "right_meta",
+}
@@ -0,0 +1,51 @@
+use std::sync::LazyLock;
+
+use bindlang::{ty::{BLType, Type}, to_struct};
+use serde::{Deserialize, Serialize};
+
+use super::ComponentData;
+
+pub type LightbarInfo = ();
+
+pub type LightbarConfig = ();
+
+/// The colour of an RGB lightbar, set by the backend as it applies feedback
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Lightbar {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Lightbar {
+    pub fn new(red: u8, green: u8, blue: u8) -> Self {
+        Lightbar { red, green, blue }
+    }
+}
+
+unsafe impl BLType for Lightbar {
+    fn bl_type() -> Type {
+        static TYPE: LazyLock<Type> = LazyLock::new(|| {
+            to_struct! {
+                name = Lightbar;
+                0:  red:   u8;
+                1:  green: u8;
+                2:  blue:  u8;
+            }
+        });
+
+        TYPE.clone()
+    }
+}
+
+impl ComponentData for Lightbar {
+    type Config = LightbarConfig;
+    type Info = LightbarInfo;
+
+    fn update(&mut self, from: &Self) {
+        self.clone_from(from);
+    }
+
+    fn configure(&mut self, _: &Self::Config, _: &Self) {}
+}
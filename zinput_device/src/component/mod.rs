@@ -1,39 +1,87 @@
 use serde::{Deserialize, Serialize};
 
+pub mod adaptive_trigger;
 pub mod analogs;
 pub mod buttons;
 pub mod controller;
+pub mod keyboard;
+pub mod lightbar;
 pub mod motion;
+pub mod motor;
+pub mod mouse;
+pub mod player_leds;
+pub mod status;
 pub mod touch_pad;
 
-pub trait ComponentConfig: Default + Deserialize<'static> + Serialize {}
+pub trait ComponentConfig: Default + PartialEq + Deserialize<'static> + Serialize {}
 
-impl<T> ComponentConfig for T where T: Default + Deserialize<'static> + Serialize {}
+impl<T> ComponentConfig for T where T: Default + PartialEq + Deserialize<'static> + Serialize {}
 
 pub trait ComponentData: Default {
     type Config: ComponentConfig;
     type Info;
 
     fn update(&mut self, from: &Self);
-    fn configure(&mut self, config: &Self::Config);
+    /// Resets the values that are relative to the previous update, before the next one is applied
+    fn clear_relative(&mut self) {}
+    /// `previous` is this component as configured in the update before
+    fn configure(&mut self, config: &Self::Config, previous: &Self);
+
+    /// Checks that `config` can be used with a component described by `info`
+    fn validate(_config: &Self::Config, _info: &Self::Info) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Checks that an analog range's minimum is below its maximum, so the range is not empty
+pub(crate) fn validate_range(range: [u8; 2]) -> Result<(), String> {
+    if range[0] >= range[1] {
+        return Err(format!(
+            "range minimum {} is not below its maximum {}",
+            range[0], range[1]
+        ));
+    }
+
+    Ok(())
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum ComponentKind {
+    AdaptiveTrigger,
     Analogs,
     Buttons,
     Controller,
+    Keyboard,
+    Lightbar,
     Motion,
+    Motor,
+    Mouse,
+    PlayerLeds,
+    Status,
     TouchPad,
 }
 
+/// One component of a device
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ComponentId {
+    pub kind: ComponentKind,
+    pub index: usize,
+}
+
 impl std::fmt::Display for ComponentKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            ComponentKind::AdaptiveTrigger => write!(f, "Adaptive Trigger"),
             ComponentKind::Analogs => write!(f, "Analogs"),
             ComponentKind::Buttons => write!(f, "Buttons"),
             ComponentKind::Controller => write!(f, "Controller"),
+            ComponentKind::Keyboard => write!(f, "Keyboard"),
+            ComponentKind::Lightbar => write!(f, "Lightbar"),
             ComponentKind::Motion => write!(f, "Motion"),
+            ComponentKind::Motor => write!(f, "Rumble Motor"),
+            ComponentKind::Mouse => write!(f, "Mouse"),
+            ComponentKind::PlayerLeds => write!(f, "Player LEDs"),
+            ComponentKind::Status => write!(f, "Status"),
             ComponentKind::TouchPad => write!(f, "Touch Pad"),
         }
     }
@@ -1,10 +1,11 @@
 use std::sync::LazyLock;
 
 use bindlang::{ty::{Type, BLType}, to_struct};
+use serde::{Deserialize, Serialize};
 
 use super::ComponentData;
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct MotionInfo {
     pub has_gyro: bool,
     pub has_accel: bool,
@@ -19,12 +20,68 @@ impl MotionInfo {
     }
 }
 
-pub type MotionConfig = ();
+/// Gyro axes in the order they are stored in [`Motion`]
+pub const GYRO_AXES: [&str; 3] = ["Pitch", "Roll", "Yaw"];
+
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MotionConfig {
+    /// Subtracted from the raw pitch, roll and yaw to remove drift
+    pub gyro_bias: [f32; 3],
+    /// Pitch, roll and yaw are read from these raw axes, after the bias is removed
+    pub gyro_axes: [u8; 3],
+    pub gyro_invert: [bool; 3],
+    /// Multiplies pitch, roll and yaw after remapping
+    pub gyro_sensitivity: [f32; 3],
+    /// Rotates the acceleration vector, each row gives one output axis
+    pub accel_orientation: [[f32; 3]; 3],
+}
+
+impl MotionConfig {
+    fn configure_gyro(&self, raw: [f32; 3]) -> [f32; 3] {
+        let unbiased = [
+            raw[0] - self.gyro_bias[0],
+            raw[1] - self.gyro_bias[1],
+            raw[2] - self.gyro_bias[2],
+        ];
+
+        let mut out = [0.0; 3];
+        for i in 0..3 {
+            out[i] = unbiased[self.gyro_axes[i] as usize] * self.gyro_sensitivity[i];
+            if self.gyro_invert[i] {
+                out[i] = -out[i];
+            }
+        }
+
+        out
+    }
+
+    fn configure_accel(&self, raw: [f32; 3]) -> [f32; 3] {
+        self.accel_orientation
+            .map(|row| row[0] * raw[0] + row[1] * raw[1] + row[2] * raw[2])
+    }
+}
+
+impl Default for MotionConfig {
+    fn default() -> Self {
+        MotionConfig {
+            gyro_bias: [0.0; 3],
+            gyro_axes: [0, 1, 2],
+            gyro_invert: [false; 3],
+            gyro_sensitivity: [1.0; 3],
+            accel_orientation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+}
 
 /// Gyro values are degrees per second
 /// Acceleration is in g (9.8m/s^2)
+///
+/// The orientation and gravity are filled in by the engine from the configured gyro and acceleration.
+/// Backends leave them alone, and the raw device always has the identity orientation.
 #[repr(C)]
-#[derive(Clone, Default)]
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Motion {
     /// Negative = Pitch forward
     pub gyro_pitch: f32,
@@ -41,6 +98,55 @@ pub struct Motion {
     /// -1.0 = Controller is placed triggers down
     /// 1.0  = Controller is placed grips down
     pub accel_z: f32,
+    /// Orientation quaternion relative to the world.
+    /// The world's z axis points up and its yaw is 0 where the controller was last recentered.
+    pub quat_w: f32,
+    pub quat_x: f32,
+    pub quat_y: f32,
+    pub quat_z: f32,
+    /// Direction of gravity in the controller's axes, with the same signs as the acceleration at rest
+    pub gravity_x: f32,
+    pub gravity_y: f32,
+    pub gravity_z: f32,
+}
+
+impl Motion {
+    /// The orientation as `[w, x, y, z]`
+    pub fn orientation(&self) -> [f32; 4] {
+        [self.quat_w, self.quat_x, self.quat_y, self.quat_z]
+    }
+
+    pub fn set_orientation(&mut self, [w, x, y, z]: [f32; 4]) {
+        [self.quat_w, self.quat_x, self.quat_y, self.quat_z] = [w, x, y, z];
+    }
+
+    pub fn gravity(&self) -> [f32; 3] {
+        [self.gravity_x, self.gravity_y, self.gravity_z]
+    }
+
+    pub fn set_gravity(&mut self, [x, y, z]: [f32; 3]) {
+        [self.gravity_x, self.gravity_y, self.gravity_z] = [x, y, z];
+    }
+}
+
+impl Default for Motion {
+    fn default() -> Self {
+        Motion {
+            gyro_pitch: 0.0,
+            gyro_roll: 0.0,
+            gyro_yaw: 0.0,
+            accel_x: 0.0,
+            accel_y: 0.0,
+            accel_z: 0.0,
+            quat_w: 1.0,
+            quat_x: 0.0,
+            quat_y: 0.0,
+            quat_z: 0.0,
+            gravity_x: 0.0,
+            gravity_y: 0.0,
+            gravity_z: 0.0,
+        }
+    }
 }
 
 unsafe impl BLType for Motion {
@@ -54,6 +160,13 @@ unsafe impl BLType for Motion {
                 12:  accel_x: f32;
                 16:  accel_y: f32;
                 20:  accel_z: f32;
+                24:  quat_w:    f32;
+                28:  quat_x:    f32;
+                32:  quat_y:    f32;
+                36:  quat_z:    f32;
+                40:  gravity_x: f32;
+                44:  gravity_y: f32;
+                48:  gravity_z: f32;
             }
         });
         
@@ -69,5 +182,95 @@ impl ComponentData for Motion {
         self.clone_from(from);
     }
 
-    fn configure(&mut self, _: &Self::Config) {}
+    fn configure(&mut self, config: &Self::Config, _: &Self) {
+        [self.gyro_pitch, self.gyro_roll, self.gyro_yaw] =
+            config.configure_gyro([self.gyro_pitch, self.gyro_roll, self.gyro_yaw]);
+        [self.accel_x, self.accel_y, self.accel_z] =
+            config.configure_accel([self.accel_x, self.accel_y, self.accel_z]);
+    }
+
+    fn validate(config: &Self::Config, _: &Self::Info) -> Result<(), String> {
+        for (i, axis) in config.gyro_axes.iter().enumerate() {
+            if *axis >= 3 {
+                return Err(format!("{} is mapped to unknown axis {axis}", GYRO_AXES[i]));
+            }
+        }
+
+        let values = config
+            .gyro_bias
+            .iter()
+            .chain(&config.gyro_sensitivity)
+            .chain(config.accel_orientation.iter().flatten());
+        for value in values {
+            if !value.is_finite() {
+                return Err(format!("motion config contains {value}"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the gyro bias by averaging samples taken while the device is at rest
+///
+/// Samples should be taken from the raw device, before the current bias is applied.
+/// Any movement restarts the calibration.
+#[derive(Default)]
+pub struct GyroCalibration {
+    sum: [f64; 3],
+    count: u32,
+}
+
+impl GyroCalibration {
+    /// Samples needed before [`bias`](Self::bias) returns a value
+    pub const SAMPLES: u32 = 500;
+    /// Largest difference from the running average, in degrees per second, that still counts as at rest
+    const MAX_DEVIATION: f32 = 5.0;
+    /// Largest difference from 1g that still counts as at rest
+    const MAX_ACCEL_DEVIATION: f32 = 0.05;
+
+    pub fn new() -> Self {
+        GyroCalibration {
+            sum: [0.0; 3],
+            count: 0,
+        }
+    }
+
+    pub fn add(&mut self, motion: &Motion, info: &MotionInfo) {
+        let gyro = [motion.gyro_pitch, motion.gyro_roll, motion.gyro_yaw];
+
+        let accel_mag =
+            f32::sqrt(motion.accel_x.powi(2) + motion.accel_y.powi(2) + motion.accel_z.powi(2));
+        let moving_accel =
+            info.has_accel && (accel_mag - 1.0).abs() > Self::MAX_ACCEL_DEVIATION;
+
+        let moving_gyro = self.count > 0
+            && gyro.iter().zip(self.average()).any(|(value, average)| {
+                (value - average).abs() > Self::MAX_DEVIATION
+            });
+
+        if moving_accel || moving_gyro {
+            *self = Self::new();
+            return;
+        }
+
+        for i in 0..3 {
+            self.sum[i] += gyro[i] as f64;
+        }
+        self.count += 1;
+    }
+
+    /// Fraction of the needed samples taken so far
+    pub fn progress(&self) -> f32 {
+        (self.count as f32 / Self::SAMPLES as f32).min(1.0)
+    }
+
+    /// The bias to put in [`MotionConfig::gyro_bias`] once enough samples were taken
+    pub fn bias(&self) -> Option<[f32; 3]> {
+        (self.count >= Self::SAMPLES).then(|| self.average())
+    }
+
+    fn average(&self) -> [f32; 3] {
+        self.sum.map(|sum| (sum / self.count as f64) as f32)
+    }
 }
@@ -0,0 +1,58 @@
+use std::sync::LazyLock;
+
+use bindlang::{ty::{BLType, Type}, to_struct};
+use serde::{Deserialize, Serialize};
+
+use super::ComponentData;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MotorKind {
+    /// Eccentric rotating mass, as found in most gamepads
+    Erm,
+    /// Linear resonant actuator, as found in Switch controllers
+    Linear,
+}
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MotorInfo {
+    pub kind: MotorKind,
+}
+
+impl MotorInfo {
+    pub fn new(kind: MotorKind) -> Self {
+        MotorInfo { kind }
+    }
+}
+
+pub type MotorConfig = ();
+
+/// What a rumble motor is currently doing, set by the backend as it applies feedback
+#[repr(C)]
+#[derive(Copy, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Motor {
+    pub amplitude: u8,
+}
+
+unsafe impl BLType for Motor {
+    fn bl_type() -> Type {
+        static TYPE: LazyLock<Type> = LazyLock::new(|| {
+            to_struct! {
+                name = Motor;
+                0:  amplitude: u8;
+            }
+        });
+
+        TYPE.clone()
+    }
+}
+
+impl ComponentData for Motor {
+    type Config = MotorConfig;
+    type Info = MotorInfo;
+
+    fn update(&mut self, from: &Self) {
+        self.clone_from(from);
+    }
+
+    fn configure(&mut self, _: &Self::Config, _: &Self) {}
+}
@@ -0,0 +1,162 @@
+use std::sync::LazyLock;
+
+use bindlang::{
+    to_bitfield, to_struct,
+    ty::{BLType, Type},
+    util::Width,
+};
+use serde::{Deserialize, Serialize};
+
+use super::ComponentData;
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MouseInfo {
+    pub buttons: u8,
+    pub has_wheel: bool,
+}
+
+impl MouseInfo {
+    pub fn new(buttons: u8, has_wheel: bool) -> Self {
+        MouseInfo { buttons, has_wheel }
+    }
+}
+
+impl Default for MouseInfo {
+    fn default() -> Self {
+        use MouseButton::*;
+
+        MouseInfo {
+            buttons: Left | Right | Middle,
+            has_wheel: true,
+        }
+    }
+}
+
+pub type MouseConfig = ();
+
+/// Motion and wheel values are relative to the previous update
+#[repr(C)]
+#[derive(Copy, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Mouse {
+    /// Positive = Right
+    pub move_x: i32,
+    /// Positive = Down
+    pub move_y: i32,
+    /// Positive = Right
+    pub wheel_x: i32,
+    /// Positive = Away from the user
+    pub wheel_y: i32,
+    pub buttons: u8,
+}
+
+unsafe impl BLType for Mouse {
+    fn bl_type() -> Type {
+        static TYPE: LazyLock<Type> = LazyLock::new(|| {
+            struct ButtonType;
+            unsafe impl BLType for ButtonType {
+                fn bl_type() -> Type {
+                    to_bitfield! {
+                        name = MouseButtons;
+                        size = Width::W8;
+                        left = 0;
+                        right = 1;
+                        middle = 2;
+                        back = 3;
+                        forward = 4;
+                    }
+                }
+            }
+
+            to_struct! {
+                name = Mouse;
+                0:  move_x:  i32;
+                4:  move_y:  i32;
+                8:  wheel_x: i32;
+                12: wheel_y: i32;
+                16: buttons: ButtonType;
+            }
+        });
+
+        TYPE.clone()
+    }
+}
+
+impl ComponentData for Mouse {
+    type Config = MouseConfig;
+    type Info = MouseInfo;
+
+    fn update(&mut self, from: &Self) {
+        self.clone_from(from);
+    }
+
+    fn clear_relative(&mut self) {
+        self.move_x = 0;
+        self.move_y = 0;
+        self.wheel_x = 0;
+        self.wheel_y = 0;
+    }
+
+    fn configure(&mut self, _: &Self::Config, _: &Self) {}
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+}
+
+impl MouseButton {
+    pub const BUTTONS: [MouseButton; 5] = {
+        use MouseButton::*;
+        [Left, Right, Middle, Back, Forward]
+    };
+
+    pub const fn bit(&self) -> u8 {
+        match self {
+            MouseButton::Left => 0,
+            MouseButton::Right => 1,
+            MouseButton::Middle => 2,
+            MouseButton::Back => 3,
+            MouseButton::Forward => 4,
+        }
+    }
+
+    pub fn set_pressed(&self, buttons: &mut u8) {
+        *buttons |= 1 << self.bit();
+    }
+
+    pub fn is_pressed(&self, buttons: u8) -> bool {
+        buttons & (1 << self.bit()) != 0
+    }
+}
+
+impl std::ops::BitOr for MouseButton {
+    type Output = u8;
+
+    fn bitor(self, rhs: Self) -> u8 {
+        (1 << self.bit()) | (1 << rhs.bit())
+    }
+}
+
+impl std::ops::BitOr<MouseButton> for u8 {
+    type Output = u8;
+
+    fn bitor(self, rhs: MouseButton) -> u8 {
+        self | (1 << rhs.bit())
+    }
+}
+
+impl std::fmt::Display for MouseButton {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MouseButton::Left => write!(f, "Left"),
+            MouseButton::Right => write!(f, "Right"),
+            MouseButton::Middle => write!(f, "Middle"),
+            MouseButton::Back => write!(f, "Back"),
+            MouseButton::Forward => write!(f, "Forward"),
+        }
+    }
+}
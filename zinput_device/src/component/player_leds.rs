@@ -0,0 +1,64 @@
+use std::sync::LazyLock;
+
+use bindlang::{ty::{BLType, Type}, to_struct};
+use serde::{Deserialize, Serialize};
+
+use super::ComponentData;
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PlayerLedsInfo {
+    pub count: u8,
+}
+
+impl PlayerLedsInfo {
+    pub fn new(count: u8) -> Self {
+        PlayerLedsInfo { count }
+    }
+}
+
+pub type PlayerLedsConfig = ();
+
+/// The player indicator LEDs that are lit, set by the backend as it applies feedback
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PlayerLeds {
+    /// Bit `n` is set while LED `n` is lit
+    pub leds: u8,
+}
+
+impl PlayerLeds {
+    /// Lights one more LED for every player, so player 0 lights the first LED
+    pub fn player(index: usize) -> Self {
+        PlayerLeds {
+            leds: (1u16 << (index % 8 + 1)).wrapping_sub(1) as u8,
+        }
+    }
+
+    pub fn is_lit(&self, led: u8) -> bool {
+        self.leds & (1 << led) != 0
+    }
+}
+
+unsafe impl BLType for PlayerLeds {
+    fn bl_type() -> Type {
+        static TYPE: LazyLock<Type> = LazyLock::new(|| {
+            to_struct! {
+                name = PlayerLeds;
+                0:  leds: u8;
+            }
+        });
+
+        TYPE.clone()
+    }
+}
+
+impl ComponentData for PlayerLeds {
+    type Config = PlayerLedsConfig;
+    type Info = PlayerLedsInfo;
+
+    fn update(&mut self, from: &Self) {
+        self.clone_from(from);
+    }
+
+    fn configure(&mut self, _: &Self::Config, _: &Self) {}
+}
@@ -0,0 +1,104 @@
+use std::sync::LazyLock;
+
+use bindlang::{ty::{BLType, Type}, to_struct};
+use serde::{Deserialize, Serialize};
+
+use super::ComponentData;
+
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StatusInfo {
+    pub has_battery: bool,
+    pub has_signal: bool,
+}
+
+impl StatusInfo {
+    pub fn new(has_battery: bool, has_signal: bool) -> Self {
+        StatusInfo {
+            has_battery,
+            has_signal,
+        }
+    }
+}
+
+pub type StatusConfig = ();
+
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Connection {
+    Unknown = 0,
+    Usb = 1,
+    Bluetooth = 2,
+    /// A wireless receiver made for the device, such as the Wavebird's
+    Dongle = 3,
+}
+
+impl Connection {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Connection::Usb,
+            2 => Connection::Bluetooth,
+            3 => Connection::Dongle,
+            _ => Connection::Unknown,
+        }
+    }
+}
+
+impl std::fmt::Display for Connection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Connection::Unknown => write!(f, "Unknown"),
+            Connection::Usb => write!(f, "USB"),
+            Connection::Bluetooth => write!(f, "Bluetooth"),
+            Connection::Dongle => write!(f, "Wireless Receiver"),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Status {
+    /// Battery charge in percent
+    pub battery: u8,
+    pub charging: bool,
+    /// A [`Connection`] stored as its `u8` value
+    pub connection: u8,
+    /// Signal quality in percent, 0 when wired
+    pub signal: u8,
+}
+
+impl Status {
+    pub fn connection(&self) -> Connection {
+        Connection::from_u8(self.connection)
+    }
+
+    pub fn set_connection(&mut self, connection: Connection) {
+        self.connection = connection as u8;
+    }
+}
+
+unsafe impl BLType for Status {
+    fn bl_type() -> Type {
+        static TYPE: LazyLock<Type> = LazyLock::new(|| {
+            to_struct! {
+                name = Status;
+                0:  battery:    u8;
+                1:  charging:   bool;
+                2:  connection: u8;
+                3:  signal:     u8;
+            }
+        });
+
+        TYPE.clone()
+    }
+}
+
+impl ComponentData for Status {
+    type Config = StatusConfig;
+    type Info = StatusInfo;
+
+    fn update(&mut self, from: &Self) {
+        self.clone_from(from);
+    }
+
+    fn configure(&mut self, _: &Self::Config, _: &Self) {}
+}
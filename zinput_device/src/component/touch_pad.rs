@@ -1,36 +1,448 @@
-use std::{sync::LazyLock, collections::HashMap};
+use std::sync::LazyLock;
 
 use bindlang::{ty::{BLType, Type}, to_struct};
+use serde::{Deserialize, Serialize};
 
-use super::ComponentData;
+use super::{
+    controller::{tilt_to_axis, Controller, ControllerInfo},
+    mouse::Mouse,
+    ComponentData,
+};
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+/// Most contacts a [`TouchPad`] can track at once
+pub const MAX_TOUCHES: usize = 4;
+
+#[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum TouchPadShape {
     Circle,
     Rectangle,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct TouchPadInfo {
     pub shape: TouchPadShape,
     pub is_button: bool,
+    /// Largest x and y a touch can have
+    #[serde(default = "TouchPadInfo::default_resolution")]
+    pub resolution: [u16; 2],
+    /// How many contacts the pad tracks, at most [`MAX_TOUCHES`]
+    #[serde(default = "TouchPadInfo::default_max_touches")]
+    pub max_touches: u8,
 }
 
 impl TouchPadInfo {
-    pub fn new(shape: TouchPadShape, is_button: bool) -> Self {
-        TouchPadInfo { shape, is_button }
+    pub fn new(shape: TouchPadShape, is_button: bool, resolution: [u16; 2], max_touches: u8) -> Self {
+        TouchPadInfo {
+            shape,
+            is_button,
+            resolution,
+            max_touches: max_touches.min(MAX_TOUCHES as u8),
+        }
+    }
+
+    fn default_resolution() -> [u16; 2] {
+        [u16::MAX; 2]
+    }
+
+    fn default_max_touches() -> u8 {
+        1
+    }
+}
+
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TouchPadConfig {
+    pub regions: Regions,
+    /// Only press region buttons while the pad is clicked
+    pub regions_need_press: bool,
+    /// What the first touch controls
+    pub mode: TouchPadMode,
+}
+
+impl Default for TouchPadConfig {
+    fn default() -> Self {
+        TouchPadConfig {
+            regions: Regions::None,
+            regions_need_press: false,
+            mode: TouchPadMode::None,
+        }
+    }
+}
+
+/// Parts of the pad that press a controller button while touched.
+///
+/// Buttons are controller button bits, like [`ControllerConfig::remap`](super::controller::ControllerConfig::remap).
+/// Positions are fractions of the pad, with `[0.0, 0.0]` at the top left.
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+pub enum Regions {
+    None,
+    /// Equal cells, `buttons` is row by row from the top left
+    Grid {
+        columns: u8,
+        rows: u8,
+        buttons: Vec<Option<u8>>,
+    },
+    /// Equal slices around the center, `buttons` is clockwise from `rotation`
+    Radial {
+        /// Degrees clockwise from up where the first slice starts
+        rotation: f32,
+        /// Touches closer to the center than this, as a fraction of the pad's radius, press `center`
+        inner_radius: f32,
+        center: Option<u8>,
+        buttons: Vec<Option<u8>>,
+    },
+    Custom(Vec<Region>),
+}
+
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+pub struct Region {
+    /// `[left, top]`
+    pub min: [f32; 2],
+    /// `[right, bottom]`
+    pub max: [f32; 2],
+    pub button: u8,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum StickSide {
+    Left,
+    Right,
+}
+
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+pub enum TouchPadMode {
+    None,
+    /// Moves a controller stick.
+    /// Absolute sticks follow the touch's position, relative sticks follow how fast it moves.
+    Stick {
+        side: StickSide,
+        relative: bool,
+        /// Relative only, full tilt is reached when a touch moves this fraction of the pad in one update
+        sensitivity: f32,
+    },
+    /// Scrolls the mouse wheel, by `sensitivity` steps for the height or width of the pad
+    Scroll { sensitivity: f32 },
+    /// Moves the mouse, by `sensitivity` pixels for the width of the pad
+    Mouse { sensitivity: f32 },
+}
+
+impl TouchPadConfig {
+    /// Checks that the region buttons and stick this pad writes to are on `controller`,
+    /// the controller [`apply`](Self::apply) is given.
+    pub(crate) fn validate_targets(
+        &self,
+        controller: Option<&ControllerInfo>,
+    ) -> Result<(), String> {
+        let buttons = controller.map_or(0, |controller| controller.buttons);
+        if let Some(button) = self
+            .regions
+            .buttons()
+            .into_iter()
+            .find(|button| buttons & (1 << button) == 0)
+        {
+            return Err(format!(
+                "region presses button {button}, which the controller does not have"
+            ));
+        }
+
+        if let TouchPadMode::Stick { side, .. } = &self.mode {
+            let stick = match side {
+                StickSide::Left => 1 << 0,
+                StickSide::Right => 1 << 1,
+            };
+            if controller.map_or(true, |controller| controller.analogs & stick == 0) {
+                return Err(format!(
+                    "pad moves the {side:?} stick, which the controller does not have"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Presses region buttons and moves the stick or mouse.
+    /// Runs after every component is configured, so other configs don't change the output.
+    ///
+    /// Mouse motion is added to the motion of this update only,
+    /// as [`Mouse`] motion is cleared before every update and read from each one.
+    pub(crate) fn apply(
+        &self,
+        info: &TouchPadInfo,
+        pad: &TouchPad,
+        previous: Option<&TouchPad>,
+        mut controller: Option<&mut Controller>,
+        mouse: Option<&mut Mouse>,
+    ) {
+        let normalize = |touch: &Touch| {
+            [
+                touch.x as f32 / info.resolution[0].max(1) as f32,
+                touch.y as f32 / info.resolution[1].max(1) as f32,
+            ]
+        };
+
+        let first = pad.first();
+        // the first touch's previous position, if it was touching in the previous update
+        let moved_from = first.and_then(|first| {
+            previous?
+                .active()
+                .find(|touch| touch.id == first.id)
+                .map(normalize)
+        });
+
+        if let Some(controller) = &mut controller {
+            if pad.pressed || !self.regions_need_press {
+                for touch in pad.active() {
+                    if let Some(button) = self.regions.button_at(normalize(touch)) {
+                        controller.buttons |= 1 << button;
+                    }
+                }
+            }
+        }
+
+        let Some(first) = first.map(normalize)
+        else { return; };
+
+        match &self.mode {
+            TouchPadMode::None => {}
+            TouchPadMode::Stick {
+                side,
+                relative,
+                sensitivity,
+            } => {
+                let Some(controller) = controller
+                else { return; };
+
+                let [x, y] = match (relative, moved_from) {
+                    (false, _) => [first[0] * 2.0 - 1.0, first[1] * 2.0 - 1.0],
+                    (true, Some(from)) => [
+                        (first[0] - from[0]) / sensitivity,
+                        (first[1] - from[1]) / sensitivity,
+                    ],
+                    (true, None) => [0.0, 0.0],
+                };
+
+                // stick y is up while pad y is down
+                let (x, y) = (tilt_to_axis(x), tilt_to_axis(-y));
+
+                match side {
+                    StickSide::Left => {
+                        controller.left_stick_x = x;
+                        controller.left_stick_y = y;
+                    }
+                    StickSide::Right => {
+                        controller.right_stick_x = x;
+                        controller.right_stick_y = y;
+                    }
+                }
+            }
+            TouchPadMode::Scroll { sensitivity } => {
+                let (Some(mouse), Some(from)) = (mouse, moved_from)
+                else { return; };
+
+                // wheel y is positive away from the user, the opposite of pad y
+                mouse.wheel_x += steps(from[0], first[0], *sensitivity);
+                mouse.wheel_y -= steps(from[1], first[1], *sensitivity);
+            }
+            TouchPadMode::Mouse { sensitivity } => {
+                let (Some(mouse), Some(from)) = (mouse, moved_from)
+                else { return; };
+
+                let aspect = info.resolution[1] as f32 / info.resolution[0].max(1) as f32;
+                mouse.move_x += steps(from[0], first[0], *sensitivity);
+                mouse.move_y += steps(from[1], first[1], *sensitivity * aspect);
+            }
+        }
+    }
+}
+
+/// How many `1 / scale` lines were crossed moving from `from` to `to`.
+/// Movements smaller than one step add up over several updates instead of being lost.
+fn steps(from: f32, to: f32, scale: f32) -> i32 {
+    (to * scale).floor() as i32 - (from * scale).floor() as i32
+}
+
+impl Regions {
+    fn button_at(&self, [x, y]: [f32; 2]) -> Option<u8> {
+        match self {
+            Regions::None => None,
+            Regions::Grid {
+                columns,
+                rows,
+                buttons,
+            } => {
+                if *columns == 0 || *rows == 0 {
+                    return None;
+                }
+
+                let column = ((x * *columns as f32) as usize).min(*columns as usize - 1);
+                let row = ((y * *rows as f32) as usize).min(*rows as usize - 1);
+                *buttons.get(row * *columns as usize + column)?
+            }
+            Regions::Radial {
+                rotation,
+                inner_radius,
+                center,
+                buttons,
+            } => {
+                let (dx, dy) = (x * 2.0 - 1.0, y * 2.0 - 1.0);
+                if f32::sqrt(dx * dx + dy * dy) < *inner_radius || buttons.is_empty() {
+                    return *center;
+                }
+
+                // clockwise from up, as pad y is down
+                let angle = f32::atan2(dx, -dy).to_degrees() - rotation;
+                let slice = 360.0 / buttons.len() as f32;
+                let index = (angle.rem_euclid(360.0) / slice) as usize;
+                *buttons.get(index.min(buttons.len() - 1))?
+            }
+            Regions::Custom(regions) => regions
+                .iter()
+                .find(|region| {
+                    (region.min[0]..=region.max[0]).contains(&x)
+                        && (region.min[1]..=region.max[1]).contains(&y)
+                })
+                .map(|region| region.button),
+        }
+    }
+
+    fn buttons(&self) -> Vec<u8> {
+        match self {
+            Regions::None => Vec::new(),
+            Regions::Grid { buttons, .. } => buttons.iter().flatten().copied().collect(),
+            Regions::Radial {
+                center, buttons, ..
+            } => buttons.iter().chain([center]).flatten().copied().collect(),
+            Regions::Custom(regions) => regions.iter().map(|region| region.button).collect(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        fn check_button(button: u8) -> Result<(), String> {
+            if button >= 64 {
+                return Err(format!("button {button} does not exist"));
+            }
+            Ok(())
+        }
+
+        match self {
+            Regions::None => {}
+            Regions::Grid {
+                columns,
+                rows,
+                buttons,
+            } => {
+                if *columns == 0 || *rows == 0 {
+                    return Err("grid has no cells".to_owned());
+                }
+                if buttons.len() != *columns as usize * *rows as usize {
+                    return Err(format!(
+                        "grid has {} cells but {} buttons",
+                        *columns as usize * *rows as usize,
+                        buttons.len()
+                    ));
+                }
+                buttons.iter().flatten().try_for_each(|b| check_button(*b))?;
+            }
+            Regions::Radial {
+                rotation,
+                inner_radius,
+                center,
+                buttons,
+            } => {
+                if buttons.is_empty() {
+                    return Err("radial regions have no slices".to_owned());
+                }
+                if !rotation.is_finite() || !inner_radius.is_finite() {
+                    return Err("radial rotation or radius is not a number".to_owned());
+                }
+                buttons.iter().chain([center]).flatten().try_for_each(|b| check_button(*b))?;
+            }
+            Regions::Custom(regions) => {
+                for region in regions {
+                    if !(region.min[0] <= region.max[0] && region.min[1] <= region.max[1]) {
+                        return Err(format!(
+                            "region {:?} to {:?} has its minimum above its maximum",
+                            region.min, region.max
+                        ));
+                    }
+                    check_button(region.button)?;
+                }
+            }
+        }
+
+        Ok(())
     }
 }
 
-pub type TouchPadConfig = ();
+#[repr(C)]
+#[derive(Copy, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Touch {
+    /// 0 = Left
+    pub x: u16,
+    /// 0 = Top
+    pub y: u16,
+    /// Stays the same from when a finger touches the pad until it is lifted
+    pub id: u8,
+    pub active: bool,
+}
 
 #[repr(C)]
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
 pub struct TouchPad {
-    pub touch_x: u16,
-    pub touch_y: u16,
+    /// A finger keeps its slot until it is lifted, so active touches can have inactive ones before them
+    pub touches: [Touch; MAX_TOUCHES],
     pub pressed: bool,
-    pub touched: bool,
+    /// The id [`set_touch`](Self::set_touch) tries next
+    #[serde(skip)]
+    next_id: u8,
+}
+
+impl TouchPad {
+    pub fn active(&self) -> impl Iterator<Item = &Touch> {
+        self.touches.iter().filter(|touch| touch.active)
+    }
+
+    /// The active touch in the lowest slot
+    pub fn first(&self) -> Option<&Touch> {
+        self.active().next()
+    }
+
+    pub fn is_touched(&self) -> bool {
+        self.first().is_some()
+    }
+
+    /// Moves the touch in `slot` to `position`, or lifts it if `position` is `None`.
+    ///
+    /// A touch that was not active is given a new id.
+    pub fn set_touch(&mut self, slot: usize, position: Option<[u16; 2]>) {
+        let Some([x, y]) = position
+        else {
+            self.touches[slot].active = false;
+            return;
+        };
+
+        if !self.touches[slot].active {
+            self.touches[slot].id = self.new_id();
+        }
+
+        let touch = &mut self.touches[slot];
+        touch.x = x;
+        touch.y = y;
+        touch.active = true;
+    }
+
+    /// An id that no active touch has, counting up so a lifted finger's id is not reused right away
+    fn new_id(&mut self) -> u8 {
+        loop {
+            let id = self.next_id;
+            self.next_id = self.next_id.wrapping_add(1);
+
+            if !self.active().any(|touch| touch.id == id) {
+                return id;
+            }
+        }
+    }
 }
 
 unsafe impl BLType for TouchPad {
@@ -38,13 +450,32 @@ unsafe impl BLType for TouchPad {
         static TYPE: LazyLock<Type> = LazyLock::new(|| {
             to_struct! {
                 name = TouchPad;
-                0:  touch_x: u16;
-                2:  touch_y: u16;
-                4:  pressed: bool;
-                5:  touched: bool;
+                // the first slot keeps the names from when a pad had one touch.
+                // they only see slot 0, which is not the first active touch
+                // if a finger in it was lifted while another stayed down
+                0:  touch_x:  u16;
+                2:  touch_y:  u16;
+                5:  touched:  bool;
+                0:  touch0_x: u16;
+                2:  touch0_y: u16;
+                4:  touch0_id: u8;
+                5:  touch0_active: bool;
+                6:  touch1_x: u16;
+                8:  touch1_y: u16;
+                10: touch1_id: u8;
+                11: touch1_active: bool;
+                12: touch2_x: u16;
+                14: touch2_y: u16;
+                16: touch2_id: u8;
+                17: touch2_active: bool;
+                18: touch3_x: u16;
+                20: touch3_y: u16;
+                22: touch3_id: u8;
+                23: touch3_active: bool;
+                24: pressed:  bool;
             }
         });
-        
+
         TYPE.clone()
     }
 }
@@ -57,5 +488,25 @@ impl ComponentData for TouchPad {
         self.clone_from(from);
     }
 
-    fn configure(&mut self, _: &Self::Config) {}
+    fn configure(&mut self, _: &Self::Config, _: &Self) {}
+
+    fn validate(config: &Self::Config, _: &Self::Info) -> Result<(), String> {
+        config.regions.validate()?;
+
+        let sensitivity = match &config.mode {
+            TouchPadMode::None => return Ok(()),
+            TouchPadMode::Stick {
+                relative: false, ..
+            } => return Ok(()),
+            TouchPadMode::Stick { sensitivity, .. }
+            | TouchPadMode::Scroll { sensitivity }
+            | TouchPadMode::Mouse { sensitivity } => *sensitivity,
+        };
+
+        if !sensitivity.is_finite() || sensitivity <= 0.0 {
+            return Err(format!("sensitivity {sensitivity} is not above 0"));
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use crate::{
+    component::{adaptive_trigger::AdaptiveTrigger, lightbar::Lightbar, player_leds::PlayerLeds},
+    DeviceInfo,
+};
+
+/// An output command sent to the backend that owns a device
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Feedback {
+    Rumble(Rumble),
+    HdRumble(HdRumble),
+    PlayerLeds(PlayerLeds),
+    Lightbar(Lightbar),
+    /// Sets the effect of the adaptive trigger with this index
+    AdaptiveTrigger(usize, AdaptiveTrigger),
+}
+
+impl Feedback {
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            Feedback::Rumble(rumble) => rumble.duration,
+            Feedback::HdRumble(rumble) => rumble.duration,
+            _ => None,
+        }
+    }
+
+    /// Converts this command into a two-motor rumble, if it is a rumble command
+    pub fn rumble(&self) -> Option<Rumble> {
+        match self {
+            Feedback::Rumble(rumble) => Some(*rumble),
+            Feedback::HdRumble(rumble) => Some((*rumble).into()),
+            _ => None,
+        }
+    }
+
+    /// Converts this command into an HD rumble, if it is a rumble command
+    pub fn hd_rumble(&self) -> Option<HdRumble> {
+        match self {
+            Feedback::Rumble(rumble) => Some((*rumble).into()),
+            Feedback::HdRumble(rumble) => Some(*rumble),
+            _ => None,
+        }
+    }
+
+    /// Whether a device described by `info` has the outputs this command needs
+    pub fn is_supported_by(&self, info: &DeviceInfo) -> bool {
+        match self {
+            Feedback::Rumble(_) | Feedback::HdRumble(_) => !info.motors.is_empty(),
+            Feedback::PlayerLeds(_) => !info.player_leds.is_empty(),
+            Feedback::Lightbar(_) => !info.lightbars.is_empty(),
+            Feedback::AdaptiveTrigger(index, _) => *index < info.adaptive_triggers.len(),
+        }
+    }
+
+    /// Whether this command makes `other` obsolete if `other` has not been applied yet
+    pub fn replaces(&self, other: &Feedback) -> bool {
+        match (self, other) {
+            (
+                Feedback::Rumble(_) | Feedback::HdRumble(_),
+                Feedback::Rumble(_) | Feedback::HdRumble(_),
+            ) => true,
+            (Feedback::PlayerLeds(_), Feedback::PlayerLeds(_)) => true,
+            (Feedback::Lightbar(_), Feedback::Lightbar(_)) => true,
+            (Feedback::AdaptiveTrigger(index, _), Feedback::AdaptiveTrigger(other, _)) => {
+                index == other
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Two-motor rumble, as found in most gamepads
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rumble {
+    /// Magnitude of the heavy, low frequency motor
+    pub strong: u8,
+    /// Magnitude of the light, high frequency motor
+    pub weak: u8,
+    /// `None` rumbles until the next command
+    pub duration: Option<Duration>,
+}
+
+impl Rumble {
+    pub const OFF: Rumble = Rumble {
+        strong: 0,
+        weak: 0,
+        duration: None,
+    };
+
+    pub fn is_off(&self) -> bool {
+        self.strong == 0 && self.weak == 0
+    }
+}
+
+impl From<HdRumble> for Rumble {
+    fn from(rumble: HdRumble) -> Self {
+        Rumble {
+            strong: (rumble.low_amp.clamp(0.0, 1.0) * 255.0) as u8,
+            weak: (rumble.high_amp.clamp(0.0, 1.0) * 255.0) as u8,
+            duration: rumble.duration,
+        }
+    }
+}
+
+/// Linear actuator rumble, as found in Switch controllers
+///
+/// Frequencies are in Hz and amplitudes range from 0.0 to 1.0.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HdRumble {
+    pub high_freq: f32,
+    pub high_amp: f32,
+    pub low_freq: f32,
+    pub low_amp: f32,
+    /// `None` rumbles until the next command
+    pub duration: Option<Duration>,
+}
+
+impl HdRumble {
+    pub const DEFAULT_HIGH_FREQ: f32 = 320.0;
+    pub const DEFAULT_LOW_FREQ: f32 = 160.0;
+
+    pub const OFF: HdRumble = HdRumble {
+        high_freq: Self::DEFAULT_HIGH_FREQ,
+        high_amp: 0.0,
+        low_freq: Self::DEFAULT_LOW_FREQ,
+        low_amp: 0.0,
+        duration: None,
+    };
+
+    pub fn is_off(&self) -> bool {
+        self.high_amp <= 0.0 && self.low_amp <= 0.0
+    }
+}
+
+impl From<Rumble> for HdRumble {
+    fn from(rumble: Rumble) -> Self {
+        HdRumble {
+            high_freq: Self::DEFAULT_HIGH_FREQ,
+            high_amp: rumble.weak as f32 / 255.0,
+            low_freq: Self::DEFAULT_LOW_FREQ,
+            low_amp: rumble.strong as f32 / 255.0,
+            duration: rumble.duration,
+        }
+    }
+}
@@ -3,92 +3,147 @@
 use paste::paste;
 
 pub mod component;
+pub mod feedback;
 
+/// Calls `$macro` with every component as `name(plural): type`
+///
+/// The plural is spelled out, since it names the component's fields and not every name takes an `s`.
 #[macro_export]
 macro_rules! components {
     (config $macro:ident) => {
         $macro! {
-            controller: $crate::component::controller::ControllerConfig,
-            motion:     $crate::component::motion::MotionConfig,
-            analog:     $crate::component::analogs::AnalogsConfig,
-            button:     $crate::component::buttons::ButtonsConfig,
-            touch_pad:  $crate::component::touch_pad::TouchPadConfig,
+            controller(controllers):             $crate::component::controller::ControllerConfig,
+            motion(motions):                     $crate::component::motion::MotionConfig,
+            analog(analogs):                     $crate::component::analogs::AnalogsConfig,
+            button(buttons):                     $crate::component::buttons::ButtonsConfig,
+            touch_pad(touch_pads):               $crate::component::touch_pad::TouchPadConfig,
+            status(statuses):                    $crate::component::status::StatusConfig,
+            keyboard(keyboards):                 $crate::component::keyboard::KeyboardConfig,
+            mouse(mouses):                       $crate::component::mouse::MouseConfig,
+            motor(motors):                       $crate::component::motor::MotorConfig,
+            player_led(player_leds):             $crate::component::player_leds::PlayerLedsConfig,
+            lightbar(lightbars):                 $crate::component::lightbar::LightbarConfig,
+            adaptive_trigger(adaptive_triggers): $crate::component::adaptive_trigger::AdaptiveTriggerConfig,
         }
     };
     (data $macro:ident) => {
         $macro! {
-            controller: $crate::component::controller::Controller,
-            motion:     $crate::component::motion::Motion,
-            analog:     $crate::component::analogs::Analogs,
-            button:     $crate::component::buttons::Buttons,
-            touch_pad:  $crate::component::touch_pad::TouchPad,
+            controller(controllers):             $crate::component::controller::Controller,
+            motion(motions):                     $crate::component::motion::Motion,
+            analog(analogs):                     $crate::component::analogs::Analogs,
+            button(buttons):                     $crate::component::buttons::Buttons,
+            touch_pad(touch_pads):               $crate::component::touch_pad::TouchPad,
+            status(statuses):                    $crate::component::status::Status,
+            keyboard(keyboards):                 $crate::component::keyboard::Keyboard,
+            mouse(mouses):                       $crate::component::mouse::Mouse,
+            motor(motors):                       $crate::component::motor::Motor,
+            player_led(player_leds):             $crate::component::player_leds::PlayerLeds,
+            lightbar(lightbars):                 $crate::component::lightbar::Lightbar,
+            adaptive_trigger(adaptive_triggers): $crate::component::adaptive_trigger::AdaptiveTrigger,
         }
     };
     (info $macro:ident) => {
         $macro! {
-            controller: $crate::component::controller::ControllerInfo,
-            motion:     $crate::component::motion::MotionInfo,
-            analog:     $crate::component::analogs::AnalogsInfo,
-            button:     $crate::component::buttons::ButtonsInfo,
-            touch_pad:  $crate::component::touch_pad::TouchPadInfo,
+            controller(controllers):             $crate::component::controller::ControllerInfo,
+            motion(motions):                     $crate::component::motion::MotionInfo,
+            analog(analogs):                     $crate::component::analogs::AnalogsInfo,
+            button(buttons):                     $crate::component::buttons::ButtonsInfo,
+            touch_pad(touch_pads):               $crate::component::touch_pad::TouchPadInfo,
+            status(statuses):                    $crate::component::status::StatusInfo,
+            keyboard(keyboards):                 $crate::component::keyboard::KeyboardInfo,
+            mouse(mouses):                       $crate::component::mouse::MouseInfo,
+            motor(motors):                       $crate::component::motor::MotorInfo,
+            player_led(player_leds):             $crate::component::player_leds::PlayerLedsInfo,
+            lightbar(lightbars):                 $crate::component::lightbar::LightbarInfo,
+            adaptive_trigger(adaptive_triggers): $crate::component::adaptive_trigger::AdaptiveTriggerInfo,
         }
     };
     (kind $macro:ident) => {
         $macro! {
-            controller: $crate::component::ComponentKind::Controller,
-            motion:     $crate::component::ComponentKind::Motion,
-            analog:     $crate::component::ComponentKind::Analogs,
-            button:     $crate::component::ComponentKind::Buttons,
-            touch_pad:  $crate::component::ComponentKind::TouchPad,
+            controller(controllers):             $crate::component::ComponentKind::Controller,
+            motion(motions):                     $crate::component::ComponentKind::Motion,
+            analog(analogs):                     $crate::component::ComponentKind::Analogs,
+            button(buttons):                     $crate::component::ComponentKind::Buttons,
+            touch_pad(touch_pads):               $crate::component::ComponentKind::TouchPad,
+            status(statuses):                    $crate::component::ComponentKind::Status,
+            keyboard(keyboards):                 $crate::component::ComponentKind::Keyboard,
+            mouse(mouses):                       $crate::component::ComponentKind::Mouse,
+            motor(motors):                       $crate::component::ComponentKind::Motor,
+            player_led(player_leds):             $crate::component::ComponentKind::PlayerLeds,
+            lightbar(lightbars):                 $crate::component::ComponentKind::Lightbar,
+            adaptive_trigger(adaptive_triggers): $crate::component::ComponentKind::AdaptiveTrigger,
         }
     };
 }
 
 macro_rules! device_config {
-    ($($cname:ident : $ctype:ty),* $(,)?) => {
+    ($($cname:ident ($plural:ident) : $ctype:ty),* $(,)?) => {
         paste! {
             use serde::{Deserialize, Serialize};
 
-            #[derive(Clone, Deserialize, Serialize)]
+            #[derive(Clone, PartialEq, Deserialize, Serialize)]
             pub struct DeviceConfig {
-                $(pub [< $cname s >]: Vec<$ctype>,)*
+                $(#[serde(default)] pub $plural: Vec<$ctype>,)*
             }
 
             impl DeviceConfig {
-                pub fn configure(&self, device: DeviceMut) {
+                /// Configures every component, then applies configs that write to other components.
+                ///
+                /// `previous` is the configured device from the update before, for configs that follow movement.
+                pub fn configure(&self, info: &DeviceInfo, device: DeviceMut, previous: &Device) {
                     use component::ComponentData;
                     $(
-                        for i in 0..device.[< $cname s >].len() {
-                            device.[< $cname s >][i].configure(&self.[< $cname s >][i]);
+                        for i in 0..device.$plural.len() {
+                            device.$plural[i].configure(
+                                &self.$plural[i],
+                                &previous.$plural[i],
+                            );
                         }
                     )*
+
+                    for i in 0..device.buttons.len() {
+                        self.buttons[i].apply(&device.buttons[i], device.controllers.get_mut(0));
+                    }
+
+                    for i in 0..device.touch_pads.len() {
+                        self.touch_pads[i].apply(
+                            &info.touch_pads[i],
+                            &device.touch_pads[i],
+                            previous.touch_pads.get(i),
+                            device.controllers.get_mut(0),
+                            device.mouses.get_mut(0),
+                        );
+                    }
                 }
 
                 pub fn as_mut(&mut self) -> DeviceConfigMut {
                     DeviceConfigMut {
-                        $([< $cname s >]: &mut self.[< $cname s >],)*
+                        $($plural: &mut self.$plural,)*
                     }
                 }
             }
 
             pub struct DeviceConfigMut<'a> {
-                $(pub [< $cname s >]: &'a mut [$ctype],)*
+                $(pub $plural: &'a mut [$ctype],)*
             }
         }
     }
 }
 
 macro_rules! device_info {
-    ($($cname:ident : $ctype:ty),* $(,)?) => {
+    ($($cname:ident ($plural:ident) : $ctype:ty),* $(,)?) => {
         paste! {
-            #[derive(Clone, PartialEq, Eq)]
+            #[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
             pub struct DeviceInfo {
                 pub name: String,
                 pub id: Option<String>,
+                /// What kind of device this is, such as `joycon`.
+                /// Devices of the same kind share saved configs.
+                pub kind: Option<String>,
                 /// If this device has an id, the device config will be loaded without user interaction
                 pub autoload_config: bool,
 
-                $(pub [< $cname s >]: Vec<$ctype>,)*
+                $(#[serde(default)] pub $plural: Vec<$ctype>,)*
             }
 
             impl DeviceInfo {
@@ -96,9 +151,10 @@ macro_rules! device_info {
                     DeviceInfo {
                         name,
                         id: None,
+                        kind: None,
                         autoload_config: false,
 
-                        $([< $cname s >]: Vec::new(),)*
+                        $($plural: Vec::new(),)*
                     }
                 }
 
@@ -107,6 +163,11 @@ macro_rules! device_info {
                     self
                 }
 
+                pub fn with_kind(mut self, kind: String) -> Self {
+                    self.kind = Some(kind);
+                    self
+                }
+
                 pub fn autoload_config(mut self, autoload_config: bool) -> Self {
                     self.autoload_config = autoload_config;
                     self
@@ -114,8 +175,8 @@ macro_rules! device_info {
 
                 $(
                     pub fn [< add_ $cname >](&mut self, info: $ctype) -> usize {
-                        self.[< $cname s >].push(info);
-                        self.[< $cname s >].len() - 1
+                        self.$plural.push(info);
+                        self.$plural.len() - 1
                     }
                 )*
             }
@@ -124,28 +185,49 @@ macro_rules! device_info {
 }
 
 macro_rules! device {
-    ($($cname:ident : $ctype:ty),* $(,)?) => {
+    ($($cname:ident ($plural:ident) : $ctype:ty),* $(,)?) => {
         paste! {
+            #[derive(Clone, Default, Deserialize, Serialize)]
             pub struct Device {
-                $(pub [< $cname s >]: Vec<$ctype>,)*
+                $(#[serde(default)] pub $plural: Vec<$ctype>,)*
             }
 
             impl Device {
                 pub fn as_mut(&mut self) -> DeviceMut {
                     DeviceMut {
-                        $([< $cname s >]: &mut self.[< $cname s >],)*
+                        $($plural: &mut self.$plural,)*
                     }
                 }
             }
 
             pub struct DeviceMut<'a> {
-                $(pub [< $cname s >]: &'a mut [$ctype],)*
+                $(pub $plural: &'a mut [$ctype],)*
+            }
+
+            impl<'a> DeviceMut<'a> {
+                /// Copies every component from a device with the same layout
+                pub fn update_from(&mut self, from: &Device) {
+                    use component::ComponentData;
+                    $(
+                        for (to, from) in self.$plural.iter_mut().zip(&from.$plural) {
+                            to.update(from);
+                        }
+                    )*
+                }
+
+                /// Resets the values that are relative to the previous update, see [`component::ComponentData::clear_relative`]
+                pub fn clear_relative(&mut self) {
+                    use component::ComponentData;
+                    $(
+                        self.$plural.iter_mut().for_each(ComponentData::clear_relative);
+                    )*
+                }
             }
 
             #[repr(C)]
             pub struct DeviceMutFfi<'a> {
                 ph: std::marker::PhantomData<DeviceMut<'a>>,
-                $(pub [< $cname s >]: FfiSlice,)*
+                $(pub $plural: FfiSlice,)*
             }
 
             unsafe impl<'a> bindlang::ty::BLType for DeviceMutFfi<'a> {
@@ -158,7 +240,7 @@ macro_rules! device {
                         let mut fields = HashMap::new();
                         let mut _i = 0;
                         $(
-                            fields.insert(stringify!([< $cname s >]), Field {
+                            fields.insert(stringify!($plural), Field {
                                 ty: Type::Slice(<$ctype as BLType>::bl_type().into()),
                                 byte_offset: _i,
                             });
@@ -185,6 +267,123 @@ macro_rules! device {
     }
 }
 
+macro_rules! device_config_validate {
+    ($($cname:ident ($plural:ident) : $ctype:ty),* $(,)?) => {
+        paste! {
+            impl DeviceConfig {
+                /// Checks that this config can be used with a device described by `info`
+                pub fn validate(&self, info: &DeviceInfo) -> Result<(), ConfigError> {
+                    use component::ComponentData;
+                    $(
+                        if self.$plural.len() != info.$plural.len() {
+                            return Err(ConfigError::ComponentCount {
+                                component: stringify!($cname),
+                                expected: info.$plural.len(),
+                                found: self.$plural.len(),
+                            });
+                        }
+
+                        let configs = self.$plural.iter();
+                        let infos = info.$plural.iter();
+
+                        for (index, (config, info)) in configs.zip(infos).enumerate() {
+                            <$ctype as ComponentData>::validate(config, info).map_err(|reason| {
+                                ConfigError::Invalid {
+                                    component: stringify!($cname),
+                                    index,
+                                    reason,
+                                }
+                            })?;
+                        }
+                    )*
+
+                    // touch pads press buttons and move sticks on the first controller
+                    for (index, config) in self.touch_pads.iter().enumerate() {
+                        config
+                            .validate_targets(info.controllers.first())
+                            .map_err(|reason| ConfigError::Invalid {
+                                component: "touch_pad",
+                                index,
+                                reason,
+                            })?;
+                    }
+
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The config has a different number of components of one kind than the device
+    ComponentCount {
+        component: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    /// A component's config has a value the component can't use
+    Invalid {
+        component: &'static str,
+        index: usize,
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::ComponentCount {
+                component,
+                expected,
+                found,
+            } => write!(
+                f,
+                "config has {found} {component} components but the device has {expected}"
+            ),
+            ConfigError::Invalid {
+                component,
+                index,
+                reason,
+            } => write!(f, "invalid config for {component} {index}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+macro_rules! device_changed {
+    ($($cname:ident ($plural:ident) : $ckind:expr),* $(,)?) => {
+        paste! {
+            impl Device {
+                /// The components that differ from `previous`, a device with the same layout
+                pub fn changed(&self, previous: &Device) -> Vec<component::ComponentId> {
+                    let mut changed = Vec::new();
+
+                    $(
+                        let components = self.$plural.iter();
+                        let previous_components = previous.$plural.iter();
+
+                        for (index, (component, old)) in components.zip(previous_components).enumerate() {
+                            if component != old {
+                                changed.push(component::ComponentId {
+                                    kind: $ckind,
+                                    index,
+                                });
+                            }
+                        }
+                    )*
+
+                    changed
+                }
+            }
+        }
+    };
+}
+
 components!(config device_config);
 components!(info device_info);
 components!(data device);
+components!(data device_config_validate);
+components!(kind device_changed);
@@ -0,0 +1,165 @@
+//! Publishing device updates while other threads read the device
+//!
+//! `update` measures how long a backend spends in `DeviceHandle::update`.
+//! `update_to_read` measures the time from the start of an update until another thread sees it.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use zinput_engine::{
+    device::{
+        component::{controller::ControllerInfo, motion::MotionInfo},
+        DeviceInfo,
+    },
+    ConfigStore, DeviceHandle, DeviceView, Engine,
+};
+
+/// Numbers of threads reading the device during a benchmark
+const READERS: [usize; 3] = [0, 1, 4];
+
+/// How long a reader holds each snapshot, like a gui drawing a frame
+const HOLD: Duration = Duration::from_micros(50);
+
+fn device() -> (Arc<Engine>, DeviceHandle, DeviceView) {
+    let root = std::env::temp_dir().join(format!("zinput-bench-{}", std::process::id()));
+    let engine = Arc::new(Engine::new(ConfigStore::new(root).without_legacy()));
+
+    let mut info = DeviceInfo::new("Bench".to_owned());
+    info.add_controller(ControllerInfo::default());
+    info.add_motion(MotionInfo::new(true, true));
+
+    let handle = engine
+        .new_device(info)
+        .unwrap_or_else(|_| panic!("failed to create device"));
+    let view = engine
+        .get_device(handle.uuid())
+        .expect("device was not added");
+
+    (engine, handle, view)
+}
+
+/// Threads that take a snapshot, hold it for [`HOLD`], and repeat until dropped
+struct Readers {
+    stop: Arc<AtomicBool>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Readers {
+    fn spawn(view: &DeviceView, count: usize) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let handles = (0..count)
+            .map(|_| {
+                let view = view.clone();
+                let stop = stop.clone();
+
+                std::thread::spawn(move || {
+                    while !stop.load(Ordering::Acquire) {
+                        let device = view.device();
+                        let held = Instant::now();
+
+                        while held.elapsed() < HOLD {
+                            black_box(device.controllers[0].buttons);
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        Readers { stop, handles }
+    }
+}
+
+impl Drop for Readers {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update");
+
+    for readers in READERS {
+        let (_engine, handle, view) = device();
+        let _readers = Readers::spawn(&view, readers);
+
+        let mut buttons = 0u64;
+
+        group.bench_function(BenchmarkId::new("readers", readers), |b| {
+            b.iter(|| {
+                buttons = buttons.wrapping_add(1);
+                handle.update(|dev| dev.controllers[0].buttons = buttons);
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn update_to_read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_to_read");
+
+    for readers in READERS {
+        let (_engine, handle, view) = device();
+        let _readers = Readers::spawn(&view, readers);
+
+        group.bench_function(BenchmarkId::new("readers", readers), |b| {
+            b.iter_custom(|iters| {
+                let seen = Arc::new(AtomicU64::new(view.device().sequence()));
+                let stop = Arc::new(AtomicBool::new(false));
+
+                // sums the time between each update starting and this thread seeing it
+                let watcher = std::thread::spawn({
+                    let view = view.clone();
+                    let seen = seen.clone();
+                    let stop = stop.clone();
+
+                    move || {
+                        let mut total = Duration::ZERO;
+
+                        while !stop.load(Ordering::Acquire) {
+                            let device = view.device();
+
+                            if device.sequence() > seen.load(Ordering::Acquire) {
+                                total += device.timestamp().elapsed();
+                                seen.store(device.sequence(), Ordering::Release);
+                            } else {
+                                std::thread::yield_now();
+                            }
+                        }
+
+                        total
+                    }
+                });
+
+                for i in 0..iters {
+                    let sequence = seen.load(Ordering::Acquire) + 1;
+                    handle.update(|dev| dev.controllers[0].buttons = i);
+
+                    while seen.load(Ordering::Acquire) < sequence {
+                        std::thread::yield_now();
+                    }
+                }
+
+                stop.store(true, Ordering::Release);
+                watcher.join().expect("watcher panicked")
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, update, update_to_read);
+criterion_main!(benches);
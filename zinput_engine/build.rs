@@ -0,0 +1,148 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet},
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    process::Command,
+};
+
+fn main() {
+    // plugins are only compatible with an engine built by the same compiler
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+
+    println!("cargo:rustc-env=ZINPUT_RUSTC_VERSION={}", version.trim());
+    println!("cargo:rerun-if-env-changed=RUSTC");
+
+    // ... with the same dependencies, features and profile
+    let mut hasher = DefaultHasher::new();
+    version.trim().hash(&mut hasher);
+
+    for var in [
+        "TARGET",
+        "PROFILE",
+        "OPT_LEVEL",
+        "DEBUG",
+        "CARGO_CFG_PANIC",
+        "CARGO_ENCODED_RUSTFLAGS",
+    ] {
+        std::env::var(var).unwrap_or_default().hash(&mut hasher);
+        println!("cargo:rerun-if-env-changed={var}");
+    }
+
+    let mut features = std::env::vars()
+        .map(|(var, _)| var)
+        .filter(|var| var.starts_with("CARGO_FEATURE_"))
+        .collect::<Vec<_>>();
+    features.sort();
+    features.hash(&mut hasher);
+
+    match find_lockfile() {
+        Some(path) => {
+            println!("cargo:rerun-if-changed={}", path.display());
+            let lockfile = std::fs::read_to_string(&path).unwrap_or_default();
+            engine_dependencies(&lockfile).hash(&mut hasher);
+        }
+        // no plugin can prove it was built against the same dependencies
+        None => std::env::var("OUT_DIR").unwrap_or_default().hash(&mut hasher),
+    }
+
+    println!("cargo:rustc-env=ZINPUT_BUILD_HASH={:016x}", hasher.finish());
+}
+
+/// The lockfile of the package being built, which is next to its target directory
+fn find_lockfile() -> Option<PathBuf> {
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").ok()?);
+
+    out_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.exists())
+}
+
+/// Every package `zinput_engine` depends on, as `name version source`
+///
+/// Only these are shared with plugins, so the rest of the lockfile can differ.
+fn engine_dependencies(lockfile: &str) -> BTreeSet<String> {
+    let packages = parse_lockfile(lockfile);
+
+    let mut found = BTreeSet::new();
+    let mut queue = vec!["zinput_engine".to_owned()];
+
+    while let Some(dependency) = queue.pop() {
+        let mut parts = dependency.split(' ');
+        let name = parts.next().unwrap_or_default();
+        let version = parts.next();
+
+        let Some(package) = packages
+            .iter()
+            .find(|package| package.name == name && version.map_or(true, |v| package.version == v))
+        else { continue; };
+
+        if found.insert(package.id()) {
+            queue.extend(package.dependencies.iter().cloned());
+        }
+    }
+
+    found
+}
+
+#[derive(Default)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: String,
+    dependencies: Vec<String>,
+}
+
+impl LockedPackage {
+    fn id(&self) -> String {
+        format!("{} {} {}", self.name, self.version, self.source)
+    }
+}
+
+/// Reads the `[[package]]` tables of a lockfile
+fn parse_lockfile(lockfile: &str) -> Vec<LockedPackage> {
+    let mut packages = Vec::new();
+    let mut in_dependencies = false;
+
+    for line in lockfile.lines().map(str::trim) {
+        if line == "[[package]]" {
+            packages.push(LockedPackage::default());
+            in_dependencies = false;
+            continue;
+        }
+
+        let Some(package) = packages.last_mut()
+        else { continue; };
+
+        if in_dependencies {
+            if line == "]" {
+                in_dependencies = false;
+            } else {
+                package
+                    .dependencies
+                    .push(line.trim_end_matches(',').trim_matches('"').to_owned());
+            }
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(" = ")
+        else { continue; };
+        let value = value.trim_matches('"').to_owned();
+
+        match key {
+            "name" => package.name = value,
+            "version" => package.version = value,
+            "source" => package.source = value,
+            "dependencies" => in_dependencies = value == "[",
+            _ => {}
+        }
+    }
+
+    packages
+}
@@ -0,0 +1,158 @@
+//! Reporting which components of a device changed
+
+use crossbeam_channel::{Sender, TrySendError};
+use uuid::Uuid;
+use zinput_device::{
+    component::{ComponentId, ComponentKind},
+    Device,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The components that changed in one update of a device
+#[derive(Clone, Debug)]
+pub struct DeviceChange {
+    pub uuid: Uuid,
+    /// The update's sequence number
+    pub sequence: u64,
+    /// The changed components that pass the subscriber's filter
+    pub components: Vec<ComponentChange>,
+    /// Earlier changes were dropped because the channel was full.
+    /// The subscriber should re-read every component it cares about.
+    pub dropped: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ComponentChange {
+    pub component: ComponentId,
+    /// The controller buttons that changed, always 0 for other components
+    pub buttons: u64,
+}
+
+/// Which changes a subscriber is sent
+///
+/// ```ignore
+/// // only the buttons of controller 0
+/// ChangeFilter::all()
+///     .component(ComponentKind::Controller, 0)
+///     .buttons(u64::MAX)
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ChangeFilter {
+    /// Empty to accept every component
+    components: Vec<(ComponentKind, Option<usize>)>,
+    buttons: Option<u64>,
+}
+
+impl ChangeFilter {
+    /// Accepts changes to every component
+    pub fn all() -> Self {
+        ChangeFilter::default()
+    }
+
+    /// Limits the filter to components of `kind`, in addition to any other components it accepts
+    pub fn kind(mut self, kind: ComponentKind) -> Self {
+        self.components.push((kind, None));
+        self
+    }
+
+    /// Limits the filter to one component, in addition to any other components it accepts
+    pub fn component(mut self, kind: ComponentKind, index: usize) -> Self {
+        self.components.push((kind, Some(index)));
+        self
+    }
+
+    /// Only accepts a controller change if one of the buttons in `mask` changed
+    pub fn buttons(mut self, mask: u64) -> Self {
+        self.buttons = Some(mask);
+        self
+    }
+
+    fn apply(&self, change: &ComponentChange) -> Option<ComponentChange> {
+        let ComponentId { kind, index } = change.component;
+
+        let accepted = self.components.is_empty()
+            || self
+                .components
+                .iter()
+                .any(|&(k, i)| k == kind && i.map_or(true, |i| i == index));
+
+        if !accepted {
+            return None;
+        }
+
+        match (kind, self.buttons) {
+            (ComponentKind::Controller, Some(mask)) if change.buttons & mask == 0 => None,
+            (ComponentKind::Controller, Some(mask)) => Some(ComponentChange {
+                buttons: change.buttons & mask,
+                ..*change
+            }),
+            _ => Some(*change),
+        }
+    }
+}
+
+/// The components of `next` that differ from `previous`
+pub(super) fn diff(previous: &Device, next: &Device) -> Vec<ComponentChange> {
+    next.changed(previous)
+        .into_iter()
+        .map(|component| ComponentChange {
+            component,
+            buttons: match component.kind {
+                ComponentKind::Controller => {
+                    previous.controllers[component.index].buttons
+                        ^ next.controllers[component.index].buttons
+                }
+                _ => 0,
+            },
+        })
+        .collect()
+}
+
+pub(super) struct ChangeSubscriber {
+    sender: Sender<DeviceChange>,
+    filter: ChangeFilter,
+    dropped: bool,
+}
+
+impl ChangeSubscriber {
+    pub(super) fn new(sender: Sender<DeviceChange>, filter: ChangeFilter) -> Self {
+        ChangeSubscriber {
+            sender,
+            filter,
+            dropped: false,
+        }
+    }
+
+    /// Sends the changes that pass the filter, returns false if the receiver is gone
+    pub(super) fn send(&mut self, uuid: Uuid, sequence: u64, changes: &[ComponentChange]) -> bool {
+        let components = changes
+            .iter()
+            .filter_map(|change| self.filter.apply(change))
+            .collect::<Vec<_>>();
+
+        if components.is_empty() {
+            return true;
+        }
+
+        let change = DeviceChange {
+            uuid,
+            sequence,
+            components,
+            dropped: self.dropped,
+        };
+
+        match self.sender.try_send(change) {
+            Ok(()) => {
+                self.dropped = false;
+                true
+            }
+            Err(TrySendError::Full(_)) => {
+                self.dropped = true;
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
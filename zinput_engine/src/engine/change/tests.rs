@@ -0,0 +1,103 @@
+use zinput_device::{
+    component::{controller::Controller, ComponentId, ComponentKind},
+    Device,
+};
+
+use super::{diff, ChangeFilter, ChangeSubscriber, ComponentChange};
+use crate::util::Uuid;
+
+fn device() -> Device {
+    Device {
+        controllers: vec![Controller::default(); 2],
+        motions: vec![Default::default()],
+        ..Device::default()
+    }
+}
+
+fn controller(index: usize, buttons: u64) -> ComponentChange {
+    ComponentChange {
+        component: ComponentId {
+            kind: ComponentKind::Controller,
+            index,
+        },
+        buttons,
+    }
+}
+
+#[test]
+fn diff_components() {
+    let previous = device();
+    let mut next = device();
+
+    assert!(diff(&previous, &next).is_empty());
+
+    next.controllers[1].buttons = 0b101;
+    next.motions[0].gyro_pitch = 1.0;
+
+    let changes = diff(&previous, &next);
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0], controller(1, 0b101));
+    assert_eq!(changes[1].component.kind, ComponentKind::Motion);
+}
+
+#[test]
+fn filter() {
+    let changes = [
+        controller(0, 0),
+        controller(1, 0b110),
+        ComponentChange {
+            component: ComponentId {
+                kind: ComponentKind::Motion,
+                index: 0,
+            },
+            buttons: 0,
+        },
+    ];
+
+    let accepted = |filter: ChangeFilter| {
+        changes
+            .iter()
+            .filter_map(|change| filter.apply(change))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(accepted(ChangeFilter::all()).len(), 3);
+    assert_eq!(
+        accepted(ChangeFilter::all().kind(ComponentKind::Controller)).len(),
+        2
+    );
+    assert_eq!(
+        accepted(
+            ChangeFilter::all()
+                .component(ComponentKind::Controller, 1)
+                .buttons(0b011)
+        ),
+        vec![controller(1, 0b010)]
+    );
+    assert!(accepted(
+        ChangeFilter::all()
+            .component(ComponentKind::Controller, 0)
+            .buttons(u64::MAX)
+    )
+    .is_empty());
+}
+
+#[test]
+fn full_channel() {
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+    let mut subscriber = ChangeSubscriber::new(sender, ChangeFilter::all());
+    let uuid = Uuid::new_v4();
+
+    assert!(subscriber.send(uuid, 1, &[controller(0, 1)]));
+    assert!(subscriber.send(uuid, 2, &[controller(0, 1)]));
+
+    let change = receiver.try_recv().unwrap();
+    assert_eq!((change.sequence, change.dropped), (1, false));
+
+    assert!(subscriber.send(uuid, 3, &[controller(0, 1)]));
+    let change = receiver.try_recv().unwrap();
+    assert_eq!((change.sequence, change.dropped), (3, true));
+
+    drop(receiver);
+    assert!(!subscriber.send(uuid, 4, &[controller(0, 1)]));
+}
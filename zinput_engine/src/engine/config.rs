@@ -0,0 +1,452 @@
+//! Saved device configs
+//!
+//! Configs are saved as JSON with a `version` field.
+//! Loading a config written by an older version runs it through [`MIGRATIONS`] first.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use zinput_device::{DeviceConfig, DeviceInfo};
+
+use super::{
+    layers::{ConfigLayer, ConfigLayers},
+    profiles::Profiles,
+};
+use crate::util::write_atomic;
+
+#[cfg(test)]
+mod tests;
+
+/// Version written to new configs
+pub const CONFIG_VERSION: u64 = 4;
+
+/// `MIGRATIONS[n]` upgrades a config from version `n` to version `n + 1`
+const MIGRATIONS: [fn(&mut Map<String, Value>) -> Result<()>; CONFIG_VERSION as usize] =
+    [migrate_v0, migrate_v1, migrate_v2, migrate_v3];
+
+/// Version 0 configs were saved before configs had a version and need no changes
+fn migrate_v0(_config: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+/// Motion configs were `()`, saved as `null`, before version 2
+fn migrate_v1(config: &mut Map<String, Value>) -> Result<()> {
+    replace_nulls(config, "motions")
+}
+
+/// Touch pad configs were `()` before version 3
+fn migrate_v2(config: &mut Map<String, Value>) -> Result<()> {
+    replace_nulls(config, "touch_pads")
+}
+
+/// Buttons configs were `()` before version 4
+fn migrate_v3(config: &mut Map<String, Value>) -> Result<()> {
+    replace_nulls(config, "buttons")
+}
+
+/// Replaces the `null`s in the component list `key` with empty objects, which deserialize to the default config
+fn replace_nulls(config: &mut Map<String, Value>, key: &str) -> Result<()> {
+    let list = match config.get_mut(key) {
+        Some(Value::Array(list)) => list,
+        // layers can give one config for every component
+        Some(Value::Object(_)) | None => return Ok(()),
+        Some(_) => anyhow::bail!("'{key}' is not a list"),
+    };
+
+    for value in list {
+        if value.is_null() {
+            *value = Value::Object(Map::new());
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) fn parse_config(string: &str) -> Result<DeviceConfig> {
+    let config = parse_layer(string)?;
+
+    serde_json::from_value(Value::Object(config)).context("failed to deserialize config")
+}
+
+/// Parses and migrates a config, without filling in the values it leaves out
+fn parse_layer(string: &str) -> Result<Map<String, Value>> {
+    let value: Value = serde_json::from_str(string).context("failed to parse config")?;
+
+    let Value::Object(mut config) = value
+    else { anyhow::bail!("config is not an object"); };
+
+    let version = match config.remove("version") {
+        Some(version) => version.as_u64().context("config version is not a number")?,
+        None => 0,
+    };
+
+    if version > CONFIG_VERSION {
+        anyhow::bail!(
+            "config version {version} is newer than the supported version {CONFIG_VERSION}"
+        );
+    }
+
+    migrate_config(&mut config, version)?;
+
+    Ok(config)
+}
+
+/// Upgrades a config saved at `version` to [`CONFIG_VERSION`]
+pub(crate) fn migrate_config(config: &mut Map<String, Value>, version: u64) -> Result<()> {
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(config)
+            .with_context(|| format!("failed to migrate config from version {from}"))?;
+    }
+
+    Ok(())
+}
+
+pub(super) fn write_config(config: &DeviceConfig) -> Result<String> {
+    let Value::Object(value) =
+        serde_json::to_value(config).context("failed to serialize config")?
+    else { anyhow::bail!("config did not serialize to an object"); };
+
+    write_layer(value)
+}
+
+fn write_layer(mut value: Map<String, Value>) -> Result<String> {
+    value.insert("version".to_owned(), CONFIG_VERSION.into());
+
+    serde_json::to_string(&value).context("failed to serialize config")
+}
+
+/// Environment variable that overrides the default config root
+pub const CONFIG_ROOT_VAR: &'static str = "ZINPUT_CONFIG_DIR";
+
+/// Directory configs were saved to before the config store existed, relative to the working directory
+const LEGACY_DIR: &'static str = "config";
+
+/// Namespace for devices that have neither a kind nor an id
+const SHARED_NAMESPACE: &'static str = "shared";
+
+/// Where device configs are saved
+///
+/// ```text
+/// <root>/global.json                   global layer, see [`ConfigLayers`]
+/// <root>/presets/<namespace>.json      preset layer for devices of one kind
+/// <root>/devices/<device id>.json      device layer, loaded automatically for that device
+/// <root>/profiles/<device id>.json     the device's profiles and their hotkeys, see [`Profiles`]
+/// <root>/configs/<namespace>/<name>.json  named configs, shared by devices of one kind
+/// ```
+///
+/// Configs that only exist in the old `config/` directory are still found and listed,
+/// but are always saved to the store.
+pub struct ConfigStore {
+    root: PathBuf,
+    legacy: Option<PathBuf>,
+}
+
+impl ConfigStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        ConfigStore {
+            root: root.into(),
+            legacy: Some(PathBuf::from(LEGACY_DIR)),
+        }
+    }
+
+    /// Uses the directory in [`CONFIG_ROOT_VAR`] if it is set, or else the platform's config directory
+    pub fn from_env() -> Self {
+        match std::env::var_os(CONFIG_ROOT_VAR) {
+            Some(root) if !root.is_empty() => ConfigStore::new(root),
+            _ => ConfigStore::new(default_root()),
+        }
+    }
+
+    /// Stops looking for configs in the old `config/` directory
+    pub fn without_legacy(mut self) -> Self {
+        self.legacy = None;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The namespace a device's named configs are saved in.
+    ///
+    /// This is the device's kind, or the first part of its id if it has no kind.
+    pub fn namespace(info: &DeviceInfo) -> String {
+        info.kind
+            .as_deref()
+            .or_else(|| info.id.as_deref().and_then(|id| id.split('/').next()))
+            .map(sanitize)
+            .unwrap_or_else(|| SHARED_NAMESPACE.to_owned())
+    }
+
+    pub fn device_path(&self, id: &str) -> PathBuf {
+        self.id_path("devices", id)
+    }
+
+    pub fn profiles_path(&self, id: &str) -> PathBuf {
+        self.id_path("profiles", id)
+    }
+
+    /// A file in `dir` named after a device id, with a directory for every `/` in the id
+    fn id_path(&self, dir: &str, id: &str) -> PathBuf {
+        let mut path = self.root.join(dir);
+
+        let mut segments = id.split('/').map(sanitize).peekable();
+        while let Some(segment) = segments.next() {
+            match segments.peek() {
+                Some(_) => path.push(segment),
+                None => path.push(format!("{segment}.json")),
+            }
+        }
+
+        path
+    }
+
+    pub fn config_path(&self, namespace: &str, name: &str) -> Result<PathBuf> {
+        check_name(name)?;
+
+        Ok(self
+            .root
+            .join("configs")
+            .join(sanitize(namespace))
+            .join(format!("{name}.json")))
+    }
+
+    pub fn global_path(&self) -> PathBuf {
+        self.root.join("global.json")
+    }
+
+    pub fn preset_path(&self, namespace: &str) -> PathBuf {
+        self.root
+            .join("presets")
+            .join(format!("{}.json", sanitize(namespace)))
+    }
+
+    /// Loads the layers that apply to a device. Missing layers are `None`.
+    pub fn load_layers(&self, info: &DeviceInfo) -> Result<ConfigLayers> {
+        let mut layers = ConfigLayers::default();
+
+        for layer in ConfigLayer::LAYERS {
+            let Some(path) = self.layer_file(info, layer)
+            else { continue; };
+
+            if !path.exists() {
+                continue;
+            }
+
+            let string = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read file '{}'", path.display()))?;
+            let values = parse_layer(&string)
+                .with_context(|| format!("failed to load file '{}'", path.display()))?;
+
+            layers.set(layer, Some(values));
+        }
+
+        Ok(layers)
+    }
+
+    /// Saves one layer of a device, which should only hold the values it changes
+    pub fn save_layer(
+        &self,
+        info: &DeviceInfo,
+        layer: ConfigLayer,
+        values: Map<String, Value>,
+    ) -> Result<()> {
+        let path = match layer {
+            ConfigLayer::Global => self.global_path(),
+            ConfigLayer::Preset => self.preset_path(&Self::namespace(info)),
+            ConfigLayer::Device => {
+                let Some(id) = &info.id
+                else { anyhow::bail!("device '{}' has no id", info.name); };
+
+                self.device_path(id)
+            }
+        };
+
+        write_atomic(&path, write_layer(values)?.as_bytes())
+            .with_context(|| format!("failed to write file '{}'", path.display()))
+    }
+
+    /// Loads a device's profiles, which are empty if none were saved
+    pub fn load_profiles(&self, id: &str) -> Result<Profiles> {
+        let path = self.profiles_path(id);
+        if !path.exists() {
+            return Ok(Profiles::default());
+        }
+
+        let string = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read file '{}'", path.display()))?;
+
+        serde_json::from_str(&string)
+            .with_context(|| format!("failed to deserialize file '{}'", path.display()))
+    }
+
+    pub fn save_profiles(&self, id: &str, profiles: &Profiles) -> Result<()> {
+        let path = self.profiles_path(id);
+        let string =
+            serde_json::to_string_pretty(profiles).context("failed to serialize profiles")?;
+
+        write_atomic(&path, string.as_bytes())
+            .with_context(|| format!("failed to write file '{}'", path.display()))
+    }
+
+    pub fn load(&self, namespace: &str, name: &str) -> Result<DeviceConfig> {
+        read_config(&self.config_file(namespace, name)?)
+    }
+
+    pub fn save(&self, namespace: &str, name: &str, config: &DeviceConfig) -> Result<()> {
+        let path = self.config_path(namespace, name)?;
+
+        write_atomic(&path, write_config(config)?.as_bytes())
+            .with_context(|| format!("failed to write file '{}'", path.display()))
+    }
+
+    /// Deletes the file [`load`](Self::load) would read.
+    ///
+    /// A config in the old `config/` directory with the same name is only deleted once the store has none.
+    pub fn delete(&self, namespace: &str, name: &str) -> Result<()> {
+        let path = self.config_file(namespace, name)?;
+
+        std::fs::remove_file(&path)
+            .with_context(|| format!("failed to delete file '{}'", path.display()))
+    }
+
+    /// Names of the configs saved in `namespace`, including configs in the old `config/` directory
+    pub fn list(&self, namespace: &str) -> Result<Vec<String>> {
+        let mut configs = Vec::new();
+
+        let dir = self.root.join("configs").join(sanitize(namespace));
+        if dir.exists() {
+            list_configs(&dir, &mut configs)
+                .with_context(|| format!("failed to read config directory '{}'", dir.display()))?;
+        }
+
+        if let Some(legacy) = &self.legacy {
+            if legacy.exists() {
+                list_configs(legacy, &mut configs).with_context(|| {
+                    format!("failed to read config directory '{}'", legacy.display())
+                })?;
+            }
+        }
+
+        configs.sort();
+        configs.dedup();
+
+        Ok(configs)
+    }
+
+    /// The file a layer is read from, or `None` for the device layer of a device without an id
+    fn layer_file(&self, info: &DeviceInfo, layer: ConfigLayer) -> Option<PathBuf> {
+        match layer {
+            ConfigLayer::Global => Some(self.global_path()),
+            ConfigLayer::Preset => Some(self.preset_path(&Self::namespace(info))),
+            ConfigLayer::Device => info.id.as_deref().map(|id| self.device_file(id)),
+        }
+    }
+
+    /// The named config's file, or its file in the old `config/` directory if only that one exists
+    fn config_file(&self, namespace: &str, name: &str) -> Result<PathBuf> {
+        let path = self.config_path(namespace, name)?;
+
+        match self.legacy_path(name) {
+            Some(legacy) if !path.exists() && legacy.exists() => Ok(legacy),
+            _ => Ok(path),
+        }
+    }
+
+    /// The device's file, or its file in the old `config/` directory if only that one exists
+    fn device_file(&self, id: &str) -> PathBuf {
+        let path = self.device_path(id);
+
+        match self.legacy_path(id) {
+            Some(legacy) if !path.exists() && legacy.exists() => legacy,
+            _ => path,
+        }
+    }
+
+    fn legacy_path(&self, name: &str) -> Option<PathBuf> {
+        self.legacy
+            .as_ref()
+            .map(|legacy| legacy.join(format!("{name}.json")))
+    }
+}
+
+impl Default for ConfigStore {
+    fn default() -> Self {
+        ConfigStore::from_env()
+    }
+}
+
+fn default_root() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    let base = std::env::var_os("APPDATA").map(PathBuf::from);
+
+    #[cfg(target_os = "macos")]
+    let base = std::env::var_os("HOME").map(|home| {
+        PathBuf::from(home)
+            .join("Library")
+            .join("Application Support")
+    });
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    match base {
+        Some(base) => base.join("zinput"),
+        None => PathBuf::from(LEGACY_DIR),
+    }
+}
+
+/// Makes a string safe to use as a single path component
+fn sanitize(segment: &str) -> String {
+    let mut out: String = segment
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' | ' ' => c,
+            _ => '_',
+        })
+        .collect();
+
+    if out.is_empty() || out.starts_with('.') {
+        out.insert(0, '_');
+    }
+
+    out
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        anyhow::bail!("invalid config name '{name}'");
+    }
+
+    Ok(())
+}
+
+fn read_config(path: &Path) -> Result<DeviceConfig> {
+    let string = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read file '{}'", path.display()))?;
+
+    parse_config(&string).with_context(|| format!("failed to load file '{}'", path.display()))
+}
+
+fn list_configs(dir: &Path, configs: &mut Vec<String>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let Ok(entry) = entry
+        else { continue; };
+
+        let path = entry.path();
+
+        let Some("json") = path.extension().and_then(|e| e.to_str())
+        else { continue; };
+
+        let Some(name) = path.file_stem().and_then(|s| s.to_str())
+        else { continue; };
+
+        configs.push(name.to_owned());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,357 @@
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use zinput_device::{
+    component::{
+        buttons::ButtonsInfo,
+        controller::{
+            Button, ControllerConfig, ControllerInfo, DeadzoneMode, ResponseCurve, Threshold,
+        },
+        touch_pad::{Regions, StickSide, TouchPadInfo, TouchPadMode, TouchPadShape},
+    },
+    ConfigError, DeviceInfo,
+};
+
+use super::{parse_config, write_config, ConfigStore, CONFIG_VERSION};
+use crate::engine::layers::ConfigLayer;
+
+/// A version 0 config, as saved before configs had a version
+const UNVERSIONED: &str = r#"{"controllers":[{"left_stick":{"deadzone":40,"samples":null},"right_stick":{"deadzone":33,"samples":null},"l1_range":[0,255],"r1_range":[0,255],"l2_range":[32,235],"r2_range":[44,234],"remap":[0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,27,28,29,30,31,32,33,34,35,36,37,38,39,40,41,42,43,44,45,46,47,48,49,50,51,52,53,54,55,56,57,58,59,60,61,62,63]}],"motions":[],"analogs":[],"buttons":[],"touch_pads":[]}"#;
+
+fn controller_device() -> DeviceInfo {
+    let mut info = DeviceInfo::new("Test".to_owned());
+    info.add_controller(ControllerInfo::default());
+    info
+}
+
+#[test]
+fn unversioned() {
+    let config = parse_config(UNVERSIONED).unwrap();
+
+    assert_eq!(config.controllers.len(), 1);
+    assert_eq!(config.controllers[0].left_stick.deadzone, 40);
+    assert_eq!(config.controllers[0].l2_range, [32, 235]);
+    assert!(config.validate(&controller_device()).is_ok());
+}
+
+#[test]
+fn round_trip() {
+    let config = parse_config(UNVERSIONED).unwrap();
+    let written = write_config(&config).unwrap();
+
+    let value: serde_json::Value = serde_json::from_str(&written).unwrap();
+    assert_eq!(value["version"], CONFIG_VERSION);
+
+    let config = parse_config(&written).unwrap();
+    assert_eq!(config.controllers[0].right_stick.deadzone, 33);
+}
+
+#[test]
+fn missing_fields() {
+    let config = parse_config(r#"{"version":1,"controllers":[{"l1_range":[10,200]}]}"#).unwrap();
+
+    assert_eq!(config.controllers[0].l1_range, [10, 200]);
+    assert_eq!(
+        config.controllers[0].remap,
+        ControllerConfig::default().remap
+    );
+    assert!(config.motions.is_empty());
+}
+
+#[test]
+fn null_motion_configs() {
+    let config = parse_config(r#"{"version":1,"motions":[null,null]}"#).unwrap();
+
+    assert_eq!(config.motions.len(), 2);
+    assert_eq!(config.motions[1].gyro_axes, [0, 1, 2]);
+    assert_eq!(config.motions[1].gyro_sensitivity, [1.0; 3]);
+}
+
+#[test]
+fn null_touch_pad_configs() {
+    let config = parse_config(r#"{"version":2,"touch_pads":[null]}"#).unwrap();
+
+    assert_eq!(config.touch_pads.len(), 1);
+    assert!(matches!(config.touch_pads[0].mode, TouchPadMode::None));
+}
+
+#[test]
+fn null_buttons_configs() {
+    let config = parse_config(r#"{"version":3,"buttons":[null]}"#).unwrap();
+
+    assert_eq!(config.buttons.len(), 1);
+    assert_eq!(config.buttons[0].remap[63], 63);
+    assert!(config.buttons[0].routes.is_empty());
+}
+
+#[test]
+fn buttons_remap_out_of_range() {
+    let mut info = DeviceInfo::new("Test".to_owned());
+    info.add_button(ButtonsInfo { buttons: 1 });
+
+    let mut config = parse_config(r#"{"buttons":[{}]}"#).unwrap();
+    assert!(config.validate(&info).is_ok());
+
+    config.buttons[0].remap[0] = 64;
+    assert!(config.validate(&info).is_err());
+}
+
+#[test]
+fn remap_to_missing_button() {
+    let mut info = DeviceInfo::new("Test".to_owned());
+    info.add_controller(ControllerInfo {
+        buttons: (1 << Button::A.bit()) | (1 << Button::B.bit()),
+        analogs: 0,
+    });
+    info.add_button(ButtonsInfo { buttons: 0b11 });
+
+    let mut config = parse_config(r#"{"controllers":[{}],"buttons":[{}]}"#).unwrap();
+    config.controllers[0].remap[Button::A.bit() as usize] = Button::B.bit() as u8;
+    config.buttons[0].remap[0] = 1;
+    assert!(config.validate(&info).is_ok());
+
+    config.controllers[0].remap[Button::A.bit() as usize] = Button::X.bit() as u8;
+    assert!(config.validate(&info).is_err());
+
+    config.controllers[0].remap[Button::A.bit() as usize] = Button::A.bit() as u8;
+    config.buttons[0].remap[0] = 2;
+    assert!(config.validate(&info).is_err());
+
+    // buttons the device doesn't have can go anywhere
+    config.buttons[0].remap[0] = 0;
+    config.buttons[0].remap[5] = 2;
+    assert!(config.validate(&info).is_ok());
+}
+
+#[test]
+fn touch_pad_targets_missing_controller_parts() {
+    let pad = TouchPadInfo::new(TouchPadShape::Rectangle, true, [100, 100], 2);
+
+    let mut info = DeviceInfo::new("Test".to_owned());
+    info.add_controller(
+        ControllerInfo {
+            buttons: 1 << Button::A.bit(),
+            analogs: 0,
+        }
+        .with_lstick(),
+    );
+    info.add_touch_pad(pad.clone());
+
+    let mut config = parse_config(r#"{"controllers":[{}],"touch_pads":[{}]}"#).unwrap();
+    config.touch_pads[0].regions = Regions::Grid {
+        columns: 2,
+        rows: 1,
+        buttons: vec![Some(Button::A.bit() as u8), None],
+    };
+    config.touch_pads[0].mode = TouchPadMode::Stick {
+        side: StickSide::Left,
+        relative: false,
+        sensitivity: 1.0,
+    };
+    assert!(config.validate(&info).is_ok());
+
+    config.touch_pads[0].mode = TouchPadMode::Stick {
+        side: StickSide::Right,
+        relative: false,
+        sensitivity: 1.0,
+    };
+    assert!(config.validate(&info).is_err());
+
+    config.touch_pads[0].mode = TouchPadMode::None;
+    config.touch_pads[0].regions = Regions::Grid {
+        columns: 2,
+        rows: 1,
+        buttons: vec![Some(Button::A.bit() as u8), Some(Button::B.bit() as u8)],
+    };
+    assert!(config.validate(&info).is_err());
+
+    // without a controller, the pad can't press anything
+    let mut info = DeviceInfo::new("Test".to_owned());
+    info.add_touch_pad(pad);
+    let mut config = parse_config(r#"{"touch_pads":[{}]}"#).unwrap();
+    assert!(config.validate(&info).is_ok());
+
+    config.touch_pads[0].regions = Regions::Radial {
+        rotation: 0.0,
+        inner_radius: 0.0,
+        center: Some(Button::A.bit() as u8),
+        buttons: vec![None],
+    };
+    assert!(config.validate(&info).is_err());
+}
+
+#[test]
+fn stick_shaping_defaults() {
+    let mut config = parse_config(UNVERSIONED).unwrap();
+    let stick = &config.controllers[0].left_stick;
+
+    assert_eq!(stick.deadzone, 40);
+    assert_eq!(stick.deadzone_mode, DeadzoneMode::Radial);
+    assert_eq!(stick.curve, ResponseCurve::Linear);
+    assert!(config.validate(&controller_device()).is_ok());
+
+    config.controllers[0].right_stick.curve = ResponseCurve::Points(vec![[0.5, 0.2], [0.4, 0.6]]);
+    assert!(config.validate(&controller_device()).is_err());
+}
+
+#[test]
+fn trigger_thresholds() {
+    let mut config = parse_config(UNVERSIONED).unwrap();
+    assert!(config.controllers[0].l2_trigger.threshold.is_none());
+
+    config.controllers[0].l2_trigger.threshold = Some(Threshold {
+        press: 200,
+        release: 150,
+    });
+    assert!(config.validate(&controller_device()).is_ok());
+
+    config.controllers[0].l2_trigger.threshold = Some(Threshold {
+        press: 150,
+        release: 200,
+    });
+    assert!(config.validate(&controller_device()).is_err());
+}
+
+#[test]
+fn newer_version() {
+    let newer = format!(r#"{{"version":{},"controllers":[]}}"#, CONFIG_VERSION + 1);
+
+    assert!(parse_config(&newer).is_err());
+}
+
+#[test]
+fn component_count() {
+    let config = parse_config(r#"{"controllers":[]}"#).unwrap();
+
+    assert_eq!(
+        config.validate(&controller_device()),
+        Err(ConfigError::ComponentCount {
+            component: "controller",
+            expected: 1,
+            found: 0,
+        })
+    );
+}
+
+#[test]
+fn invalid_values() {
+    let mut config = parse_config(UNVERSIONED).unwrap();
+    config.controllers[0].remap[3] = 64;
+    assert!(matches!(
+        config.validate(&controller_device()),
+        Err(ConfigError::Invalid { index: 0, .. })
+    ));
+
+    let mut config = parse_config(UNVERSIONED).unwrap();
+    config.controllers[0].r2_range = [200, 100];
+    assert!(config.validate(&controller_device()).is_err());
+
+    // an empty range would divide by zero
+    let mut config = parse_config(UNVERSIONED).unwrap();
+    config.controllers[0].l1_range = [100, 100];
+    assert!(config.validate(&controller_device()).is_err());
+}
+
+fn temp_store(name: &str) -> (ConfigStore, PathBuf) {
+    let root = std::env::temp_dir().join(format!("zinput-test-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+
+    (ConfigStore::new(&root).without_legacy(), root)
+}
+
+#[test]
+fn store_namespaces() {
+    let (store, root) = temp_store("namespaces");
+
+    let mut info = controller_device();
+    assert_eq!(ConfigStore::namespace(&info), "shared");
+    info.id = Some("gc_adaptor/1234/0".to_owned());
+    assert_eq!(ConfigStore::namespace(&info), "gc_adaptor");
+    info.kind = Some("gamecube".to_owned());
+    assert_eq!(ConfigStore::namespace(&info), "gamecube");
+
+    assert_eq!(
+        store.device_path("gc_adaptor/1234/0"),
+        root.join("devices")
+            .join("gc_adaptor")
+            .join("1234")
+            .join("0.json")
+    );
+    assert_eq!(
+        store.device_path("../x"),
+        root.join("devices").join("_..").join("x.json")
+    );
+    assert!(store.config_path("gamecube", "../escape").is_err());
+}
+
+#[test]
+fn store_overwrite() {
+    let (store, root) = temp_store("overwrite");
+
+    let long = parse_config(UNVERSIONED).unwrap();
+    let short = parse_config(r#"{"controllers":[{}]}"#).unwrap();
+
+    store.save("gamecube", "silver", &long).unwrap();
+    store.save("gamecube", "silver", &short).unwrap();
+
+    let loaded = store.load("gamecube", "silver").unwrap();
+    assert_eq!(loaded.controllers[0].left_stick.deadzone, 0);
+
+    assert_eq!(store.list("gamecube").unwrap(), vec!["silver".to_owned()]);
+    assert!(store.list("joycon").unwrap().is_empty());
+
+    store.delete("gamecube", "silver").unwrap();
+    assert!(store.list("gamecube").unwrap().is_empty());
+
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[test]
+fn delete_only_resolved_file() {
+    let (mut store, root) = temp_store("delete");
+    let legacy = root.join("legacy");
+    store.legacy = Some(legacy.clone());
+
+    let config = parse_config(r#"{"controllers":[{}]}"#).unwrap();
+    std::fs::create_dir_all(&legacy).unwrap();
+    std::fs::write(legacy.join("silver.json"), write_config(&config).unwrap()).unwrap();
+    store.save("gamecube", "silver", &config).unwrap();
+
+    store.delete("gamecube", "silver").unwrap();
+    assert!(!store.config_path("gamecube", "silver").unwrap().exists());
+    assert!(legacy.join("silver.json").exists());
+
+    store.delete("gamecube", "silver").unwrap();
+    assert!(!legacy.join("silver.json").exists());
+    assert!(store.delete("gamecube", "silver").is_err());
+
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[test]
+fn store_layers() {
+    let (store, root) = temp_store("layers");
+
+    let mut info = controller_device();
+    info.id = Some("gc_adaptor/1234/0".to_owned());
+    info.kind = Some("gamecube".to_owned());
+
+    let Value::Object(global) = json!({"controllers": {"left_stick": {"deadzone": 10}}})
+    else { unreachable!() };
+    let Value::Object(device) = json!({"controllers": [{"right_stick": {"deadzone": 5}}]})
+    else { unreachable!() };
+
+    store.save_layer(&info, ConfigLayer::Global, global).unwrap();
+    store.save_layer(&info, ConfigLayer::Device, device).unwrap();
+    assert!(store.preset_path("gamecube").parent().unwrap().starts_with(&root));
+
+    let layers = store.load_layers(&info).unwrap();
+    assert!(layers.preset.is_none());
+
+    let config = layers.apply(&parse_config(r#"{"controllers":[{}]}"#).unwrap()).unwrap();
+    assert_eq!(config.controllers[0].left_stick.deadzone, 10);
+    assert_eq!(config.controllers[0].right_stick.deadzone, 5);
+
+    let _ = std::fs::remove_dir_all(root);
+}
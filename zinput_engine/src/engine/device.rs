@@ -1,44 +1,185 @@
 use std::{
     ops::Deref,
     sync::{
-        atomic::{AtomicBool, AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
+use anyhow::Context;
+use arc_swap::ArcSwap;
 use crossbeam_channel::{Sender, TrySendError};
 use index_map::IndexMap;
-use parking_lot::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use parking_lot::{Mutex, MutexGuard};
 use paste::paste;
 use uuid::Uuid;
-use zinput_device::{Device, DeviceConfig, DeviceConfigMut, DeviceInfo, DeviceMut};
+use zinput_device::{
+    component::player_leds::PlayerLeds,
+    feedback::{Feedback, Rumble},
+    Device, DeviceConfig, DeviceConfigMut, DeviceInfo, DeviceMut,
+};
+
+use super::{
+    change::{self, ChangeFilter, ChangeSubscriber, DeviceChange},
+    config::ConfigStore,
+    layers::{ConfigLayer, ConfigLayers, ConfigSource},
+    fusion::MotionFusion,
+    profiles::{ProfileState, Profiles},
+    registry::Registry,
+    FeedbackError,
+};
+use crate::event::{Event, EventBus};
 
 pub struct DeviceHandle {
     internal: Arc<InternalDevice>,
+    events: Arc<EventBus>,
+    registry: Arc<Registry>,
 }
 
 impl DeviceHandle {
-    pub(super) fn new(internal: Arc<InternalDevice>) -> Option<Self> {
+    pub(super) fn new(
+        internal: Arc<InternalDevice>,
+        events: Arc<EventBus>,
+        registry: Arc<Registry>,
+    ) -> Option<Self> {
         internal
             .handle
             .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
             .ok()
-            .map(|_| DeviceHandle { internal })
+            .map(|_| {
+                internal.feedback.lock().clear();
+                internal.fusion.lock().iter_mut().for_each(MotionFusion::recenter);
+                DeviceHandle {
+                    internal,
+                    events,
+                    registry,
+                }
+            })
     }
 
-    pub fn update<F>(&self, mut updater: F)
+    pub fn uuid(&self) -> &Uuid {
+        &self.internal.uuid
+    }
+
+    /// Takes the oldest pending feedback command sent to this device.
+    ///
+    /// Commands that were superseded before they were taken are discarded.
+    pub fn take_feedback(&self) -> Option<Feedback> {
+        let mut pending = self.internal.feedback.lock();
+        if pending.is_empty() {
+            return None;
+        }
+
+        Some(pending.remove(0))
+    }
+
+    pub fn update<F>(&self, updater: F)
     where
         F: for<'a> FnMut(DeviceMut<'a>),
     {
-        {
-            let mut device_raw = self.internal.device_raw.write();
-            updater(device_raw.as_mut());
-        }
+        self.update_inner(None, updater);
+    }
+
+    /// Like [`update`](Self::update), but also records the time
+    /// the device's own clock reported for this sample.
+    pub fn update_with_time<F>(&self, hardware_time: Duration, updater: F)
+    where
+        F: for<'a> FnMut(DeviceMut<'a>),
+    {
+        self.update_inner(Some(hardware_time), updater);
+    }
+
+    fn update_inner<F>(&self, hardware_time: Option<Duration>, mut updater: F)
+    where
+        F: for<'a> FnMut(DeviceMut<'a>),
+    {
+        let stamp = Stamp {
+            captured: Instant::now(),
+            sequence: self.internal.sequence.fetch_add(1, Ordering::AcqRel) + 1,
+            hardware_time,
+        };
+
+        let switch = {
+            let mut spare = self.internal.spare.lock();
+            let current = self.internal.sample.load_full();
+
+            // reuse the sample published before the current one if no reader still holds it
+            let mut next = spare
+                .take()
+                .filter(|sample| Arc::strong_count(sample) == 1)
+                .unwrap_or_else(|| Arc::new(Sample::clone(&current)));
+
+            let sample = Arc::get_mut(&mut next).expect("sample is shared");
+            sample.raw.as_mut().update_from(&current.raw);
+            sample.device.as_mut().update_from(&current.device);
+            // mouse motion is only reported by the update it happened in
+            sample.raw.as_mut().clear_relative();
+            sample.device.as_mut().clear_relative();
+            sample.stamp = stamp;
+
+            updater(sample.raw.as_mut());
+            updater(sample.device.as_mut());
+            let switch = self.internal.profiles.lock().check_hotkeys(
+                &current.raw,
+                &sample.raw,
+                &mut sample.device,
+            );
+            self.internal
+                .config
+                .load()
+                .configure(&self.internal.info, sample.device.as_mut(), &current.device);
+
+            let mut fusion = self.internal.fusion.lock();
+            for (filter, motion) in fusion.iter_mut().zip(&mut sample.device.motions) {
+                filter.update(motion, &stamp);
+            }
+            drop(fusion);
+
+            let mut change_channels = self.internal.change_channels.lock();
+            let changes = if change_channels.is_empty() {
+                Vec::new()
+            } else {
+                change::diff(&current.device, &sample.device)
+            };
+
+            let mut update_channels = self.internal.update_channels.lock();
+            if !update_channels.is_empty() {
+                update_channels.retain(|_, channel| {
+                    let update = DeviceRead {
+                        sample: next.clone(),
+                        raw: false,
+                    };
+
+                    match channel.try_send(update) {
+                        Ok(()) => true,
+                        Err(TrySendError::Full(_)) => true,
+                        Err(TrySendError::Disconnected(_)) => false,
+                    }
+                });
+            }
+            drop(update_channels);
+
+            drop(current);
+            *spare = Some(self.internal.sample.swap(next));
+
+            if !changes.is_empty() {
+                change_channels.retain(|_, subscriber| {
+                    subscriber.send(self.internal.uuid, stamp.sequence, &changes)
+                });
+            }
+
+            switch
+        };
 
-        {
-            let mut device = self.internal.device.write();
-            updater(device.as_mut());
-            self.internal.config.read().configure(device.as_mut());
+        // the new config is used from the next update, since switching waits for the config lock
+        if let Some(name) = switch {
+            if let Err(err) = self.internal.switch_profile(&name) {
+                log::warn!(
+                    "failed to switch profile of device '{}': {err:?}",
+                    self.internal.info.name
+                );
+            }
         }
 
         self.internal.channels.lock().retain(|_, channel| {
@@ -48,6 +189,8 @@ impl DeviceHandle {
                 Err(TrySendError::Disconnected(_)) => false,
             }
         });
+
+        self.events.emit(Event::DeviceUpdate(self.internal.uuid));
     }
 }
 
@@ -59,6 +202,13 @@ impl Drop for DeviceHandle {
         );
 
         self.internal.handle.store(false, Ordering::Release);
+        self.internal.feedback.lock().clear();
+
+        if let Some(id) = &self.internal.info.id {
+            self.registry.disconnect(id);
+        }
+
+        self.events.emit(Event::DeviceRemoved(self.internal.uuid));
     }
 }
 
@@ -66,6 +216,8 @@ pub struct DeviceView {
     internal: Arc<InternalDevice>,
 
     channel: Option<usize>,
+    change_channel: Option<usize>,
+    update_channel: Option<usize>,
 }
 
 impl DeviceView {
@@ -74,18 +226,26 @@ impl DeviceView {
         DeviceView {
             internal,
             channel: None,
+            change_channel: None,
+            update_channel: None,
         }
     }
 
     pub fn config(&self) -> ConfigRead {
         ConfigRead {
-            lock: self.internal.config.read(),
+            config: self.internal.config.load_full(),
         }
     }
 
+    /// Edits a copy of the config, which replaces the config when the [`ConfigWrite`] is dropped
     pub fn config_mut(&self) -> ConfigWrite {
+        let lock = self.internal.config_lock.lock();
+
         ConfigWrite {
-            lock: self.internal.config.write(),
+            config: Some(DeviceConfig::clone(&self.internal.config.load())),
+            dirty: false,
+            target: &self.internal.config,
+            _lock: lock,
         }
     }
 
@@ -93,15 +253,19 @@ impl DeviceView {
         &self.internal.info
     }
 
+    /// The latest configured state of the device
     pub fn device(&self) -> DeviceRead {
         DeviceRead {
-            lock: self.internal.device.read(),
+            sample: self.internal.sample.load_full(),
+            raw: false,
         }
     }
 
+    /// The latest state of the device, before its config was applied
     pub fn device_raw(&self) -> DeviceRead {
         DeviceRead {
-            lock: self.internal.device_raw.read(),
+            sample: self.internal.sample.load_full(),
+            raw: true,
         }
     }
 
@@ -109,6 +273,20 @@ impl DeviceView {
         &self.internal.uuid
     }
 
+    /// Sends a feedback command to the backend that owns this device.
+    ///
+    /// A command replaces any command for the same output the backend has not yet received.
+    pub fn send_feedback(&self, feedback: Feedback) -> Result<(), FeedbackError> {
+        self.internal.send_feedback(feedback)
+    }
+
+    /// Resets the orientation of motion component `index` to its current tilt, facing forward
+    pub fn recenter_motion(&self, index: usize) {
+        if let Some(fusion) = self.internal.fusion.lock().get_mut(index) {
+            fusion.recenter();
+        }
+    }
+
     pub fn register_channel(&mut self, channel: Sender<Uuid>) {
         if let Some(channel) = self.channel.take() {
             self.internal.channels.lock().remove(channel);
@@ -118,8 +296,39 @@ impl DeviceView {
         self.channel = Some(channel);
     }
 
+    /// Sends the components that pass `filter` to `channel` after every update that changes one of them
+    ///
+    /// Like [`register_channel`](Self::register_channel), this never blocks the backend.
+    /// If the channel is full the change is dropped, and the next one sent is marked [`DeviceChange::dropped`].
+    pub fn subscribe_changes(&mut self, channel: Sender<DeviceChange>, filter: ChangeFilter) {
+        if let Some(channel) = self.change_channel.take() {
+            self.internal.change_channels.lock().remove(channel);
+        }
+
+        let channel = self
+            .internal
+            .change_channels
+            .lock()
+            .insert(ChangeSubscriber::new(channel, filter));
+        self.change_channel = Some(channel);
+    }
+
+    /// Sends a snapshot of the device to `channel` after every update
+    ///
+    /// Unlike [`register_channel`](Self::register_channel), which only says that the device changed,
+    /// every update is its own snapshot, so none are lost to the reader falling behind.
+    /// Updates are dropped only if the channel is bounded and full.
+    pub fn subscribe_updates(&mut self, channel: Sender<DeviceRead>) {
+        if let Some(channel) = self.update_channel.take() {
+            self.internal.update_channels.lock().remove(channel);
+        }
+
+        let channel = self.internal.update_channels.lock().insert(channel);
+        self.update_channel = Some(channel);
+    }
+
     pub fn saved_configs(&self) -> anyhow::Result<Vec<String>> {
-        saved_configs()
+        self.internal.saved_configs()
     }
 
     pub fn load_config(&self, name: &str) -> anyhow::Result<()> {
@@ -134,9 +343,56 @@ impl DeviceView {
         self.internal.delete_config(name)
     }
 
+    /// Saves the current config as the one loaded automatically for this device
+    pub fn save_device_config(&self) -> anyhow::Result<()> {
+        self.internal.save_config_layer(ConfigLayer::Device)
+    }
+
+    /// Saves the current config into `layer`, see [`ConfigLayers::diff`] for which values are kept
+    pub fn save_config_layer(&self, layer: ConfigLayer) -> anyhow::Result<()> {
+        self.internal.save_config_layer(layer)
+    }
+
+    /// Reads the config layers again and replaces the config with them
+    pub fn reload_config_layers(&self) -> anyhow::Result<()> {
+        self.internal.reload_config_layers()
+    }
+
+    /// Every field of the current config with the layer it came from
+    pub fn config_sources(&self) -> anyhow::Result<Vec<(String, ConfigSource)>> {
+        self.internal.config_sources()
+    }
+
     pub fn reset_config(&self) {
         self.internal.reset_config()
     }
+
+    pub fn profiles(&self) -> Profiles {
+        self.internal.profiles.lock().profiles.clone()
+    }
+
+    /// Saves the device's profiles and hotkeys, and loads the configs of the profiles
+    pub fn set_profiles(&self, profiles: Profiles) -> anyhow::Result<()> {
+        self.internal.set_profiles(profiles)
+    }
+
+    /// The name of the profile the config was last switched to
+    pub fn active_profile(&self) -> Option<String> {
+        let state = self.internal.profiles.lock();
+        state
+            .active
+            .and_then(|active| state.profiles.profiles.get(active))
+            .cloned()
+    }
+
+    /// Switches to a profile like its hotkey does
+    pub fn select_profile(&self, name: &str) -> anyhow::Result<()> {
+        self.internal.switch_profile(name)
+    }
+
+    pub fn set_config(&self, config: DeviceConfig) {
+        self.internal.set_config(config);
+    }
 }
 
 impl Clone for DeviceView {
@@ -156,90 +412,209 @@ impl Drop for DeviceView {
             self.internal.channels.lock().remove(channel);
         }
 
+        if let Some(channel) = self.change_channel.take() {
+            self.internal.change_channels.lock().remove(channel);
+        }
+
+        if let Some(channel) = self.update_channel.take() {
+            self.internal.update_channels.lock().remove(channel);
+        }
+
         self.internal.views.fetch_sub(1, Ordering::AcqRel);
     }
 }
 
-pub struct DeviceRead<'a> {
-    lock: RwLockReadGuard<'a, Device>,
+/// When and in what order a device update was captured
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Stamp {
+    /// When the engine received the update
+    pub captured: Instant,
+    /// Number of updates up to and including this one, 0 if the device was never updated
+    pub sequence: u64,
+    /// Time reported by the device's own clock, if it has one
+    pub hardware_time: Option<Duration>,
 }
 
-impl<'a> Deref for DeviceRead<'a> {
+/// The state of a device after one update
+///
+/// Samples are never changed once published, so readers never block the backend.
+#[derive(Clone)]
+struct Sample {
+    raw: Device,
+    device: Device,
+    stamp: Stamp,
+}
+
+/// A snapshot of a device
+///
+/// Holding it does not block updates, it just stops seeing them.
+pub struct DeviceRead {
+    sample: Arc<Sample>,
+    raw: bool,
+}
+
+impl DeviceRead {
+    pub fn stamp(&self) -> Stamp {
+        self.sample.stamp
+    }
+
+    pub fn timestamp(&self) -> Instant {
+        self.sample.stamp.captured
+    }
+
+    pub fn sequence(&self) -> u64 {
+        self.sample.stamp.sequence
+    }
+
+    pub fn hardware_time(&self) -> Option<Duration> {
+        self.sample.stamp.hardware_time
+    }
+
+    /// The state before the config was applied, from the same update
+    pub fn raw(&self) -> &Device {
+        &self.sample.raw
+    }
+
+    /// The state after the config was applied, from the same update
+    pub fn configured(&self) -> &Device {
+        &self.sample.device
+    }
+}
+
+impl Deref for DeviceRead {
     type Target = Device;
 
     fn deref(&self) -> &Device {
-        self.lock.deref()
+        match self.raw {
+            true => &self.sample.raw,
+            false => &self.sample.device,
+        }
     }
 }
 
-pub struct ConfigRead<'a> {
-    lock: RwLockReadGuard<'a, DeviceConfig>,
+pub struct ConfigRead {
+    config: Arc<DeviceConfig>,
 }
 
-impl<'a> Deref for ConfigRead<'a> {
+impl Deref for ConfigRead {
     type Target = DeviceConfig;
 
     fn deref(&self) -> &DeviceConfig {
-        self.lock.deref()
+        &self.config
     }
 }
 
+/// Only replaces the config if it was borrowed mutably and changed,
+/// so a gui that edits every frame does not publish a new config every frame
 pub struct ConfigWrite<'a> {
-    lock: RwLockWriteGuard<'a, DeviceConfig>,
+    config: Option<DeviceConfig>,
+    dirty: bool,
+    target: &'a ArcSwap<DeviceConfig>,
+    _lock: MutexGuard<'a, ()>,
 }
 
 impl<'a> ConfigWrite<'a> {
     pub fn get(&mut self) -> DeviceConfigMut {
-        self.lock.as_mut()
+        self.dirty = true;
+        self.config.as_mut().expect("config was taken").as_mut()
     }
 }
 
+impl<'a> Drop for ConfigWrite<'a> {
+    fn drop(&mut self) {
+        if !self.dirty {
+            return;
+        }
+
+        if let Some(config) = self.config.take() {
+            // the lock is still held, so the loaded config is the one that was copied
+            if config != **self.target.load() {
+                self.target.store(Arc::new(config));
+            }
+        }
+    }
+}
+
+/// How long a device rumbles when its profile is switched
+const PROFILE_RUMBLE: Duration = Duration::from_millis(150);
+
 pub(super) struct InternalDevice {
     pub(super) uuid: Uuid,
 
     pub(super) handle: AtomicBool,
     views: AtomicUsize,
 
-    config: RwLock<DeviceConfig>,
+    config: ArcSwap<DeviceConfig>,
+    /// Held while the config is replaced, so concurrent edits are not lost
+    config_lock: Mutex<()>,
     info: DeviceInfo,
-    device: RwLock<Device>,
-    device_raw: RwLock<Device>,
+    sample: ArcSwap<Sample>,
+    /// The sample published before the current one, reused by the next update
+    spare: Mutex<Option<Arc<Sample>>>,
+    sequence: AtomicU64,
 
     channels: Mutex<IndexMap<Sender<Uuid>>>,
+    change_channels: Mutex<IndexMap<ChangeSubscriber>>,
+    update_channels: Mutex<IndexMap<Sender<DeviceRead>>>,
+    /// At most one command per output
+    feedback: Mutex<Vec<Feedback>>,
+    /// One per motion component
+    fusion: Mutex<Vec<MotionFusion>>,
+
+    store: Arc<ConfigStore>,
+    /// The layers the config was last loaded from or saved to
+    layers: Mutex<ConfigLayers>,
+    profiles: Mutex<ProfileState>,
 }
 
 macro_rules! internal_device_components {
-    ($($field_name:ident : $ctype:ty),* $(,)?) => {
+    ($($field_name:ident ($plural:ident) : $ctype:ty),* $(,)?) => {
         paste! {
             impl InternalDevice {
-                pub(super) fn new(info: DeviceInfo, uuid: Uuid) -> Arc<Self> {
-                    let device = Device {
-                        $([< $field_name s >]: vec![Default::default(); info.[< $field_name s >].len()]),*
+                pub(super) fn new(info: DeviceInfo, uuid: Uuid, store: Arc<ConfigStore>) -> Arc<Self> {
+                    let stamp = Stamp {
+                        captured: Instant::now(),
+                        sequence: 0,
+                        hardware_time: None,
                     };
-                    let device = RwLock::new(device);
 
-                    let device_raw = Device {
-                        $([< $field_name s >]: vec![Default::default(); info.[< $field_name s >].len()]),*
+                    let device = Device {
+                        $($plural: vec![Default::default(); info.$plural.len()]),*
                     };
-                    let device_raw = RwLock::new(device_raw);
+                    let sample = ArcSwap::from_pointee(Sample {
+                        raw: device.clone(),
+                        device,
+                        stamp,
+                    });
 
-                    let mut config = DeviceConfig {
-                        $([< $field_name s >]: vec![Default::default(); info.[< $field_name s >].len()]),*
-                    };
+                    let mut config = Self::default_config(&info);
+                    let mut layers = ConfigLayers::default();
 
                     if info.autoload_config {
-                        if let Some(id) = &info.id {
-                            match load_config(id) {
-                                // TODO: Config validation
-                                Ok(loaded) => { config = loaded; },
-                                Err(err) => {
-                                    log::warn!("failed to load config for device '{id}': {err:?}");
-                                }
+                        match Self::load_layers(&store, &info) {
+                            Ok((loaded_layers, loaded)) => {
+                                layers = loaded_layers;
+                                config = loaded;
+                            }
+                            Err(err) => {
+                                log::warn!("failed to load config for device '{}': {err:?}", info.name);
                             }
                         }
                     }
 
-                    let config = RwLock::new(config);
+                    let profiles = match &info.id {
+                        Some(id) => match store.load_profiles(id) {
+                            Ok(profiles) => Self::load_profiles(&store, &info, profiles),
+                            Err(err) => {
+                                log::warn!("failed to load profiles for device '{}': {err:?}", info.name);
+                                ProfileState::default()
+                            }
+                        },
+                        None => ProfileState::default(),
+                    };
+
+                    let config = ArcSwap::from_pointee(config);
+                    let fusion = info.motions.iter().map(MotionFusion::new).collect();
 
                     Arc::new(InternalDevice {
                         uuid,
@@ -248,14 +623,67 @@ macro_rules! internal_device_components {
                         views: AtomicUsize::new(0),
 
                         config,
+                        config_lock: Mutex::new(()),
                         info,
-                        device,
-                        device_raw,
+                        sample,
+                        spare: Mutex::new(None),
+                        sequence: AtomicU64::new(0),
 
                         channels: Mutex::default(),
+                        change_channels: Mutex::default(),
+                        update_channels: Mutex::default(),
+                        feedback: Mutex::default(),
+                        fusion: Mutex::new(fusion),
+
+                        store,
+                        layers: Mutex::new(layers),
+                        profiles: Mutex::new(profiles),
                     })
                 }
 
+                fn default_config(info: &DeviceInfo) -> DeviceConfig {
+                    DeviceConfig {
+                        $($plural: vec![Default::default(); info.$plural.len()]),*
+                    }
+                }
+
+                fn load_layers(store: &ConfigStore, info: &DeviceInfo) -> anyhow::Result<(ConfigLayers, DeviceConfig)> {
+                    let layers = store.load_layers(info)?;
+                    let config = layers.apply(&Self::default_config(info))?;
+                    config.validate(info)?;
+
+                    Ok((layers, config))
+                }
+
+                /// Loads the config of every profile, so switching to one never waits on a file
+                fn load_profiles(store: &ConfigStore, info: &DeviceInfo, profiles: Profiles) -> ProfileState {
+                    let configs = profiles
+                        .profiles
+                        .iter()
+                        .map(|name| match Self::load_profile(store, info, name) {
+                            Ok(config) => Some(config),
+                            Err(err) => {
+                                log::warn!("failed to load profile '{name}': {err:?}");
+                                None
+                            }
+                        })
+                        .collect();
+
+                    ProfileState {
+                        profiles,
+                        configs,
+                        active: None,
+                    }
+                }
+
+                fn load_profile(store: &ConfigStore, info: &DeviceInfo, name: &str) -> anyhow::Result<DeviceConfig> {
+                    let cfg = store.load(&ConfigStore::namespace(info), name)?;
+                    cfg.validate(info)
+                        .with_context(|| format!("config '{name}' does not fit device '{}'", info.name))?;
+
+                    Ok(cfg)
+                }
+
                 pub(super) fn info(&self) -> &DeviceInfo {
                     &self.info
                 }
@@ -265,122 +693,170 @@ macro_rules! internal_device_components {
                         && (self.views.load(Ordering::Acquire) == 0)
                 }
 
+                fn saved_configs(&self) -> anyhow::Result<Vec<String>> {
+                    self.store.list(&ConfigStore::namespace(&self.info))
+                }
+
                 fn load_config(&self, name: &str) -> anyhow::Result<()> {
-                    let cfg = load_config(name)?;
-                    // TODO: Config validation
-                    *self.config.write() = cfg;
+                    let cfg = self.store.load(&ConfigStore::namespace(&self.info), name)?;
+                    cfg.validate(&self.info)
+                        .with_context(|| format!("config '{name}' does not fit device '{}'", self.info.name))?;
+                    self.set_config(cfg);
+
+                    let mut profiles = self.profiles.lock();
+                    profiles.active = profiles.profiles.profiles.iter().position(|profile| profile == name);
 
                     Ok(())
                 }
 
                 fn save_config(&self, name: &str) -> anyhow::Result<()> {
-                    let cfg = self.config.read();
+                    let cfg = self.config.load_full();
 
-                    save_config(name, &cfg)
+                    self.store.save(&ConfigStore::namespace(&self.info), name, &cfg)?;
+                    self.profiles.lock().set_config(name, Some(DeviceConfig::clone(&cfg)));
+
+                    Ok(())
                 }
 
                 fn delete_config(&self, name: &str) -> anyhow::Result<()> {
-                    delete_config(name)
+                    self.store.delete(&ConfigStore::namespace(&self.info), name)?;
+                    self.profiles.lock().set_config(name, None);
+
+                    Ok(())
                 }
 
-                pub fn reset_config(&self) {
-                    *self.config.write() = DeviceConfig {
-                        $([< $field_name s >]: vec![Default::default(); self.info.[< $field_name s >].len()]),*
-                    };
+                fn save_config_layer(&self, layer: ConfigLayer) -> anyhow::Result<()> {
+                    let cfg = self.config.load();
+                    let mut layers = self.layers.lock();
+
+                    let values = layers.diff(layer, &cfg, &Self::default_config(&self.info))?;
+                    self.store.save_layer(&self.info, layer, values.clone())?;
+                    layers.set(layer, Some(values));
+
+                    Ok(())
                 }
-            }
-        }
-    };
-}
 
-internal_device_components!(
-    controller: Controller,
-    motion: Motion,
-    analog: Analogs,
-    button: Buttons,
-    touch_pad: TouchPad,
-);
+                fn reload_config_layers(&self) -> anyhow::Result<()> {
+                    let (layers, cfg) = Self::load_layers(&self.store, &self.info)
+                        .with_context(|| format!("config layers do not fit device '{}'", self.info.name))?;
 
-fn load_config(name: &str) -> anyhow::Result<DeviceConfig> {
-    use anyhow::Context;
-    use std::fs::File;
-    use std::io::Read;
+                    *self.layers.lock() = layers;
+                    self.set_config(cfg);
 
-    let mut file = File::open(format!("config/{name}.json"))
-        .with_context(|| format!("failed to open file '{}'", format!("config/{name}.json")))?;
+                    Ok(())
+                }
 
-    let mut string =
-        String::with_capacity(file.metadata().map(|meta| meta.len() as usize).unwrap_or(0));
-    file.read_to_string(&mut string)
-        .with_context(|| format!("failed to read file '{}'", format!("config/{name}.json")))?;
+                fn config_sources(&self) -> anyhow::Result<Vec<(String, ConfigSource)>> {
+                    let cfg = self.config.load();
 
-    let config: DeviceConfig = serde_json::from_str(&string).with_context(|| {
-        format!(
-            "failed to deserialize file '{}'",
-            format!("config/{name}.json")
-        )
-    })?;
+                    self.layers.lock().sources(&Self::default_config(&self.info), &cfg)
+                }
 
-    Ok(config)
-}
+                fn set_profiles(&self, profiles: Profiles) -> anyhow::Result<()> {
+                    let Some(id) = &self.info.id
+                    else { anyhow::bail!("device '{}' has no id, so it can't have profiles", self.info.name); };
 
-fn save_config(name: &str, config: &DeviceConfig) -> anyhow::Result<()> {
-    use anyhow::Context;
-    use std::fs::OpenOptions;
-    use std::io::Write;
+                    self.store.save_profiles(id, &profiles)?;
 
-    let _ = std::fs::create_dir("config");
+                    let mut state = Self::load_profiles(&self.store, &self.info, profiles);
+                    let mut old = self.profiles.lock();
 
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .open(format!("config/{name}.json"))
-        .with_context(|| format!("failed to open file '{}'", format!("config/{name}.json")))?;
+                    // keep the active profile if it was not removed
+                    state.active = old
+                        .active
+                        .and_then(|active| old.profiles.profiles.get(active))
+                        .and_then(|name| state.profiles.profiles.iter().position(|profile| profile == name));
+                    *old = state;
 
-    let string = serde_json::to_string(config).with_context(|| {
-        format!(
-            "failed to serialize file '{}'",
-            format!("config/{name}.json")
-        )
-    })?;
+                    Ok(())
+                }
 
-    file.write_all(string.as_bytes())
-        .with_context(|| format!("failed to write file '{}'", format!("config/{name}.json")))?;
+                /// Applies a profile's config on top of the config layers, confirmed by a short rumble,
+                /// or by lighting the profile's number on the player LEDs if the device can't rumble
+                fn switch_profile(&self, name: &str) -> anyhow::Result<()> {
+                    let (index, profile) = {
+                        let state = self.profiles.lock();
+
+                        let index = state
+                            .profiles
+                            .profiles
+                            .iter()
+                            .position(|profile| profile == name)
+                            .with_context(|| format!("'{name}' is not a profile of this device"))?;
+                        let Some(Some(profile)) = state.configs.get(index)
+                        else { anyhow::bail!("config of profile '{name}' could not be loaded"); };
+
+                        (index, profile.clone())
+                    };
 
-    Ok(())
-}
+                    let cfg = self.layers.lock().apply_over(&Self::default_config(&self.info), &profile)?;
+                    cfg.validate(&self.info)
+                        .with_context(|| format!("config '{name}' does not fit device '{}'", self.info.name))?;
 
-fn delete_config(name: &str) -> anyhow::Result<()> {
-    let _ = std::fs::create_dir("config");
+                    log::info!("switching device '{}' to profile '{name}'", self.info.name);
 
-    let _ = std::fs::remove_file(format!("config/{name}.json"));
+                    self.set_config(cfg);
 
-    Ok(())
-}
+                    // the profiles can have changed since the config was taken
+                    let mut state = self.profiles.lock();
+                    state.active = state.profiles.profiles.iter().position(|profile| profile == name);
+                    drop(state);
 
-fn saved_configs() -> anyhow::Result<Vec<String>> {
-    use anyhow::Context;
+                    let rumble = Feedback::Rumble(Rumble {
+                        strong: 0,
+                        weak: 160,
+                        duration: Some(PROFILE_RUMBLE),
+                    });
 
-    let _ = std::fs::create_dir("config");
+                    if let Err(FeedbackError::Unsupported) = self.send_feedback(rumble) {
+                        let _ = self.send_feedback(Feedback::PlayerLeds(PlayerLeds::player(index)));
+                    }
 
-    let mut configs = Vec::new();
+                    Ok(())
+                }
 
-    for entry in std::fs::read_dir("config").context("failed to read config directory")? {
-        let Ok(entry) = entry
-        else { continue; };
+                /// A command replaces any command for the same output the backend has not yet received
+                fn send_feedback(&self, feedback: Feedback) -> Result<(), FeedbackError> {
+                    if !feedback.is_supported_by(&self.info) {
+                        return Err(FeedbackError::Unsupported);
+                    }
 
-        let path = entry.path();
+                    let mut pending = self.feedback.lock();
 
-        let Some("json") = path.extension().and_then(|e| e.to_str())
-        else { continue; };
+                    if !self.handle.load(Ordering::Acquire) {
+                        return Err(FeedbackError::Disconnected);
+                    }
 
-        let file_name = entry.file_name();
+                    pending.retain(|old| !feedback.replaces(old));
+                    pending.push(feedback);
 
-        let Some(file_name) = file_name.to_str()
-        else { continue; };
+                    Ok(())
+                }
 
-        configs.push(file_name[..(file_name.len() - 5)].to_owned());
-    }
+                pub fn reset_config(&self) {
+                    self.set_config(Self::default_config(&self.info));
+                }
 
-    Ok(configs)
+                fn set_config(&self, config: DeviceConfig) {
+                    let _lock = self.config_lock.lock();
+                    self.config.store(Arc::new(config));
+                }
+            }
+        }
+    };
 }
+
+internal_device_components!(
+    controller(controllers): Controller,
+    motion(motions): Motion,
+    analog(analogs): Analogs,
+    button(buttons): Buttons,
+    touch_pad(touch_pads): TouchPad,
+    status(statuses): Status,
+    keyboard(keyboards): Keyboard,
+    mouse(mouses): Mouse,
+    motor(motors): Motor,
+    player_led(player_leds): PlayerLeds,
+    lightbar(lightbars): Lightbar,
+    adaptive_trigger(adaptive_triggers): AdaptiveTrigger,
+);
@@ -0,0 +1,154 @@
+//! Orientation of motion components from their gyro and accelerometer
+//!
+//! Each motion component gets a Madgwick filter.
+//! The gyro is integrated every update and the accelerometer slowly pulls the estimate towards gravity.
+
+use std::time::{Duration, Instant};
+
+use zinput_device::component::motion::{Motion, MotionInfo};
+
+use super::device::Stamp;
+
+#[cfg(test)]
+mod tests;
+
+/// How strongly the accelerometer corrects the gyro.
+/// Higher values drift less but pick up more noise from movement.
+const BETA: f32 = 0.1;
+
+/// Updates further apart than this restart the filter, as the gyro can't be trusted over the gap
+const MAX_STEP: Duration = Duration::from_millis(100);
+
+pub(super) struct MotionFusion {
+    has_gyro: bool,
+    has_accel: bool,
+
+    /// `[w, x, y, z]`
+    quat: [f32; 4],
+    last: Option<(Instant, Option<Duration>)>,
+    recenter: bool,
+}
+
+impl MotionFusion {
+    pub fn new(info: &MotionInfo) -> Self {
+        MotionFusion {
+            has_gyro: info.has_gyro,
+            has_accel: info.has_accel,
+
+            quat: [1.0, 0.0, 0.0, 0.0],
+            last: None,
+            recenter: true,
+        }
+    }
+
+    /// Restarts the orientation from the current acceleration on the next update, which zeroes the yaw
+    pub fn recenter(&mut self) {
+        self.recenter = true;
+    }
+
+    /// Fills in the orientation and gravity of `motion`, which should already be configured
+    pub fn update(&mut self, motion: &mut Motion, stamp: &Stamp) {
+        let step = match (self.last, stamp.hardware_time) {
+            // prefer the device's clock, it isn't affected by when the update was received
+            (Some((_, Some(last))), Some(now)) => now.checked_sub(last),
+            (Some((last, _)), _) => stamp.captured.checked_duration_since(last),
+            (None, _) => None,
+        };
+        self.last = Some((stamp.captured, stamp.hardware_time));
+
+        match step {
+            Some(step) if step <= MAX_STEP && !self.recenter => {
+                self.step(motion, step.as_secs_f32());
+            }
+            _ => self.reset(motion),
+        }
+
+        let [w, x, y, z] = self.quat;
+        motion.set_orientation(self.quat);
+        motion.set_gravity([
+            2.0 * (x * z - w * y),
+            2.0 * (w * x + y * z),
+            w * w - x * x - y * y + z * z,
+        ]);
+    }
+
+    /// Starts from the rotation that lines the acceleration up with the world's z axis
+    fn reset(&mut self, motion: &Motion) {
+        self.recenter = false;
+
+        let Some([ax, ay, az]) = self.accel(motion)
+        else {
+            self.quat = [1.0, 0.0, 0.0, 0.0];
+            return;
+        };
+
+        // shortest rotation from the acceleration to (0, 0, 1)
+        self.quat = if az < -0.9999 {
+            [0.0, 1.0, 0.0, 0.0]
+        } else {
+            normalize([1.0 + az, ay, -ax, 0.0])
+        };
+    }
+
+    fn step(&mut self, motion: &Motion, dt: f32) {
+        let [q0, q1, q2, q3] = self.quat;
+
+        let [gx, gy, gz] = match self.has_gyro {
+            // pitch turns around the x axis, yaw around y and roll around z
+            true => [motion.gyro_pitch, motion.gyro_yaw, motion.gyro_roll].map(f32::to_radians),
+            false => [0.0; 3],
+        };
+
+        let mut dq = [
+            0.5 * (-q1 * gx - q2 * gy - q3 * gz),
+            0.5 * (q0 * gx + q2 * gz - q3 * gy),
+            0.5 * (q0 * gy - q1 * gz + q3 * gx),
+            0.5 * (q0 * gz + q1 * gy - q2 * gx),
+        ];
+
+        if let Some([ax, ay, az]) = self.accel(motion) {
+            // gradient of the difference between the estimated and measured gravity
+            let f = [
+                2.0 * (q1 * q3 - q0 * q2) - ax,
+                2.0 * (q0 * q1 + q2 * q3) - ay,
+                2.0 * (0.5 - q1 * q1 - q2 * q2) - az,
+            ];
+            let s = [
+                -2.0 * q2 * f[0] + 2.0 * q1 * f[1],
+                2.0 * q3 * f[0] + 2.0 * q0 * f[1] - 4.0 * q1 * f[2],
+                -2.0 * q0 * f[0] + 2.0 * q3 * f[1] - 4.0 * q2 * f[2],
+                2.0 * q1 * f[0] + 2.0 * q2 * f[1],
+            ];
+
+            if s.iter().any(|v| *v != 0.0) {
+                let s = normalize(s);
+                for i in 0..4 {
+                    dq[i] -= BETA * s[i];
+                }
+            }
+        }
+
+        let mut quat = self.quat;
+        for i in 0..4 {
+            quat[i] += dq[i] * dt;
+        }
+        self.quat = normalize(quat);
+    }
+
+    /// The normalized acceleration, if there is one
+    fn accel(&self, motion: &Motion) -> Option<[f32; 3]> {
+        if !self.has_accel {
+            return None;
+        }
+
+        let accel = [motion.accel_x, motion.accel_y, motion.accel_z];
+        let len = accel.iter().map(|v| v * v).sum::<f32>().sqrt();
+
+        len.is_normal().then(|| accel.map(|v| v / len))
+    }
+}
+
+fn normalize<const N: usize>(v: [f32; N]) -> [f32; N] {
+    let len = v.iter().map(|v| v * v).sum::<f32>().sqrt();
+    v.map(|v| v / len)
+}
@@ -0,0 +1,96 @@
+use std::time::{Duration, Instant};
+
+use zinput_device::component::motion::{Motion, MotionInfo};
+
+use super::MotionFusion;
+use crate::Stamp;
+
+const STEP: Duration = Duration::from_millis(10);
+
+fn stamp(start: Instant, step: u32) -> Stamp {
+    Stamp {
+        captured: start + STEP * step,
+        sequence: step as u64 + 1,
+        hardware_time: None,
+    }
+}
+
+fn at_rest(accel: [f32; 3]) -> Motion {
+    let mut motion = Motion::default();
+    [motion.accel_x, motion.accel_y, motion.accel_z] = accel;
+    motion
+}
+
+fn assert_near<const N: usize>(actual: [f32; N], expected: [f32; N]) {
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 0.01, "{actual:?} != {expected:?}");
+    }
+}
+
+#[test]
+fn starts_from_gravity() {
+    let mut fusion = MotionFusion::new(&MotionInfo::new(true, true));
+    let start = Instant::now();
+
+    let mut motion = at_rest([0.0, -1.0, 0.0]);
+    fusion.update(&mut motion, &stamp(start, 0));
+
+    assert_near(motion.gravity(), [0.0, -1.0, 0.0]);
+}
+
+#[test]
+fn integrates_gyro() {
+    let mut fusion = MotionFusion::new(&MotionInfo::new(true, false));
+    let start = Instant::now();
+
+    let mut motion = Motion::default();
+    fusion.update(&mut motion, &stamp(start, 0));
+    assert_near(motion.orientation(), [1.0, 0.0, 0.0, 0.0]);
+
+    // a quarter turn of pitch over one second
+    motion.gyro_pitch = 90.0;
+    for step in 1..=100 {
+        fusion.update(&mut motion, &stamp(start, step));
+    }
+
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    assert_near(motion.orientation(), [half, half, 0.0, 0.0]);
+}
+
+#[test]
+fn recenter() {
+    let mut fusion = MotionFusion::new(&MotionInfo::new(true, true));
+    let start = Instant::now();
+
+    let mut motion = at_rest([0.0, -1.0, 0.0]);
+    fusion.update(&mut motion, &stamp(start, 0));
+    let centered = motion.orientation();
+
+    // turning around gravity only changes the yaw
+    motion.gyro_yaw = 45.0;
+    for step in 1..=100 {
+        fusion.update(&mut motion, &stamp(start, step));
+    }
+    assert_near(motion.gravity(), [0.0, -1.0, 0.0]);
+    assert!(motion.orientation() != centered);
+
+    fusion.recenter();
+    motion.gyro_yaw = 0.0;
+    fusion.update(&mut motion, &stamp(start, 101));
+
+    assert_near(motion.orientation(), centered);
+}
+
+#[test]
+fn restarts_after_gap() {
+    let mut fusion = MotionFusion::new(&MotionInfo::new(true, true));
+    let start = Instant::now();
+
+    let mut motion = at_rest([0.0, 0.0, 1.0]);
+    fusion.update(&mut motion, &stamp(start, 0));
+
+    motion = at_rest([1.0, 0.0, 0.0]);
+    fusion.update(&mut motion, &stamp(start, 1000));
+
+    assert_near(motion.gravity(), [1.0, 0.0, 0.0]);
+}
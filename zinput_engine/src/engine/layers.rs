@@ -0,0 +1,423 @@
+//! Layered device configs
+//!
+//! A device's config starts from its defaults and is overridden by up to three layers, lowest first:
+//!
+//! - the global layer, used by every device
+//! - the preset for the device's kind, see [`ConfigStore::namespace`](super::ConfigStore::namespace)
+//! - the device's own layer
+//!
+//! Layers only hold the values they change. Their component lists are either merged by index,
+//! or given as a single object that is merged into every component of that kind:
+//!
+//! ```json
+//! {"controllers": {"left_stick": {"deadzone": 10}, "right_stick": {"deadzone": 10}}}
+//! ```
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use zinput_device::DeviceConfig;
+
+#[cfg(test)]
+mod tests;
+
+/// Fields that only fit the device they were set on, like its calibration,
+/// as a component list and the path to the field in its config. They are never saved into a shared layer.
+const DEVICE_FIELDS: [(&str, &[&str]); 3] = [
+    ("controllers", &["left_stick", "samples"]),
+    ("controllers", &["right_stick", "samples"]),
+    ("motions", &["gyro_bias"]),
+];
+
+/// Fields that only fit devices of one kind, like button remaps, which are kept out of the global layer
+const KIND_FIELDS: [(&str, &[&str]); 3] = [
+    ("controllers", &["remap"]),
+    ("buttons", &["remap"]),
+    ("buttons", &["routes"]),
+];
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ConfigLayer {
+    Global,
+    Preset,
+    Device,
+}
+
+impl ConfigLayer {
+    /// Lowest first
+    pub const LAYERS: [ConfigLayer; 3] = {
+        use ConfigLayer::*;
+        [Global, Preset, Device]
+    };
+}
+
+impl std::fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigLayer::Global => write!(f, "Global"),
+            ConfigLayer::Preset => write!(f, "Preset"),
+            ConfigLayer::Device => write!(f, "Device"),
+        }
+    }
+}
+
+/// Where the current value of a config field came from
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ConfigSource {
+    Default,
+    Layer(ConfigLayer),
+    /// Changed since the layers were loaded
+    Edited,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "Default"),
+            ConfigSource::Layer(layer) => write!(f, "{layer}"),
+            ConfigSource::Edited => write!(f, "Edited"),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ConfigLayers {
+    pub global: Option<Map<String, Value>>,
+    pub preset: Option<Map<String, Value>>,
+    pub device: Option<Map<String, Value>>,
+}
+
+impl ConfigLayers {
+    pub fn get(&self, layer: ConfigLayer) -> Option<&Map<String, Value>> {
+        match layer {
+            ConfigLayer::Global => self.global.as_ref(),
+            ConfigLayer::Preset => self.preset.as_ref(),
+            ConfigLayer::Device => self.device.as_ref(),
+        }
+    }
+
+    pub fn set(&mut self, layer: ConfigLayer, value: Option<Map<String, Value>>) {
+        match layer {
+            ConfigLayer::Global => self.global = value,
+            ConfigLayer::Preset => self.preset = value,
+            ConfigLayer::Device => self.device = value,
+        }
+    }
+
+    /// Merges every layer onto `defaults`
+    pub fn apply(&self, defaults: &DeviceConfig) -> Result<DeviceConfig> {
+        let merged = self.merged(defaults, None)?;
+        serde_json::from_value(merged).context("failed to deserialize layered config")
+    }
+
+    /// Merges every layer onto `defaults`, then the values of `top` that differ from `defaults`
+    pub fn apply_over(&self, defaults: &DeviceConfig, top: &DeviceConfig) -> Result<DeviceConfig> {
+        let mut merged = self.merged(defaults, None)?;
+        let top = serde_json::to_value(top).context("failed to serialize config")?;
+        let defaults = serde_json::to_value(defaults).context("failed to serialize config")?;
+
+        if let Some(top) = diff(&top, &defaults, true) {
+            merge(&mut merged, &top, true);
+        }
+
+        serde_json::from_value(merged).context("failed to deserialize layered config")
+    }
+
+    /// The values to save into `layer` for `config`.
+    ///
+    /// The device layer gets every value that differs from `defaults` merged with the layers below it.
+    /// The shared layers keep their values and only take the fields edited since the layers were loaded,
+    /// given as one object per component list and without the [`DEVICE_FIELDS`].
+    /// The global layer doesn't take the [`KIND_FIELDS`] either.
+    pub fn diff(
+        &self,
+        layer: ConfigLayer,
+        config: &DeviceConfig,
+        defaults: &DeviceConfig,
+    ) -> Result<Map<String, Value>> {
+        let value = serde_json::to_value(config).context("failed to serialize config")?;
+
+        if layer == ConfigLayer::Device {
+            let base = self.merged(defaults, Some(layer))?;
+
+            return match diff(&value, &base, true) {
+                Some(Value::Object(diff)) => Ok(diff),
+                _ => Ok(Map::new()),
+            };
+        }
+
+        let merged = self.merged(defaults, None)?;
+        let mut values = self.get(layer).cloned().unwrap_or_default();
+
+        let kept_out = DEVICE_FIELDS
+            .iter()
+            .chain(KIND_FIELDS.iter().filter(|_| layer == ConfigLayer::Global));
+
+        let Some(Value::Object(edits)) = diff(&value, &merged, true)
+        else { return Ok(values); };
+
+        for (key, list) in edits {
+            let Value::Array(list) = list
+            else { continue; };
+
+            let mut shared = Value::Object(Map::new());
+            for mut edit in list {
+                for (_, path) in kept_out.clone().filter(|(list, _)| *list == key) {
+                    remove_field(&mut edit, path);
+                }
+                merge(&mut shared, &edit, false);
+            }
+
+            if matches!(&shared, Value::Object(map) if map.is_empty()) {
+                continue;
+            }
+
+            match values.get_mut(&key) {
+                Some(Value::Array(list)) => {
+                    for value in list {
+                        merge(value, &shared, false);
+                    }
+                }
+                Some(value) => merge(value, &shared, false),
+                None => {
+                    values.insert(key, shared);
+                }
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Every field of `config` with where its value came from, as JSON pointers like `/controllers/0/remap`.
+    ///
+    /// Lists inside a component config are one field.
+    pub fn sources(
+        &self,
+        defaults: &DeviceConfig,
+        config: &DeviceConfig,
+    ) -> Result<Vec<(String, ConfigSource)>> {
+        let merged = self.merged(defaults, None)?;
+        let value = serde_json::to_value(config).context("failed to serialize config")?;
+
+        let mut fields = Vec::new();
+        flatten(&value, &mut Vec::new(), &mut fields);
+
+        Ok(fields
+            .into_iter()
+            .map(|path| {
+                let pointer = format!("/{}", path.join("/"));
+
+                let source = if value.pointer(&pointer) != merged.pointer(&pointer) {
+                    ConfigSource::Edited
+                } else {
+                    ConfigLayer::LAYERS
+                        .iter()
+                        .rev()
+                        .find(|layer| {
+                            self.get(**layer)
+                                .map_or(false, |values| sets(values, &path))
+                        })
+                        .map_or(ConfigSource::Default, |layer| ConfigSource::Layer(*layer))
+                };
+
+                (pointer, source)
+            })
+            .collect())
+    }
+
+    /// `defaults` with the layers merged on top, stopping before `until`
+    fn merged(&self, defaults: &DeviceConfig, until: Option<ConfigLayer>) -> Result<Value> {
+        let mut merged = serde_json::to_value(defaults).context("failed to serialize config")?;
+
+        for layer in ConfigLayer::LAYERS {
+            if Some(layer) == until {
+                break;
+            }
+
+            if let Some(values) = self.get(layer) {
+                merge(&mut merged, &Value::Object(values.clone()), true);
+            }
+        }
+
+        Ok(merged)
+    }
+}
+
+/// Merges `layer` into `base`. `top` is set for the config itself, whose values are component lists.
+pub(super) fn merge(base: &mut Value, layer: &Value, top: bool) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) if top => {
+            for (key, list) in layer {
+                let Some(Value::Array(base)) = base.get_mut(key)
+                else { continue; };
+
+                match list {
+                    Value::Array(list) => {
+                        for (base, value) in base.iter_mut().zip(list) {
+                            merge(base, value, false);
+                        }
+                    }
+                    Value::Object(_) => {
+                        for base in base {
+                            merge(base, list, false);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        // used as a placeholder in component lists
+        (_, Value::Object(layer)) if layer.is_empty() => {}
+        (Value::Object(base), Value::Object(layer)) if !is_other_variant(base, layer) => {
+            for (key, value) in layer {
+                match base.get_mut(key) {
+                    Some(base) => merge(base, value, false),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer.clone(),
+    }
+}
+
+/// The parts of `value` that differ from `base`, in the form [`merge`] takes
+pub(super) fn diff(value: &Value, base: &Value, top: bool) -> Option<Value> {
+    match (value, base) {
+        (Value::Object(value), Value::Object(base)) if top => {
+            let mut out = Map::new();
+
+            for (key, list) in value {
+                let (Value::Array(list), Some(Value::Array(base))) = (list, base.get(key))
+                else { continue; };
+
+                let mut diffs = list
+                    .iter()
+                    .zip(base)
+                    .map(|(value, base)| diff(value, base, false))
+                    .collect::<Vec<_>>();
+
+                while let Some(None) = diffs.last() {
+                    diffs.pop();
+                }
+
+                if !diffs.is_empty() {
+                    let diffs = diffs
+                        .into_iter()
+                        .map(|diff| diff.unwrap_or_else(|| Value::Object(Map::new())))
+                        .collect();
+                    out.insert(key.clone(), Value::Array(diffs));
+                }
+            }
+
+            Some(Value::Object(out))
+        }
+        // a variant is saved whole, so it still deserializes if a lower layer changes variant
+        (Value::Object(value), Value::Object(base)) if !is_variant(value) => {
+            let mut out = Map::new();
+
+            for (key, value) in value {
+                let diff = match base.get(key) {
+                    Some(base) => diff(value, base, false),
+                    None => Some(value.clone()),
+                };
+
+                if let Some(diff) = diff {
+                    out.insert(key.clone(), diff);
+                }
+            }
+
+            match out.is_empty() {
+                true => None,
+                false => Some(Value::Object(out)),
+            }
+        }
+        (value, base) if value == base => None,
+        (value, _) => Some(value.clone()),
+    }
+}
+
+/// One variant is replaced by another instead of merged into it
+fn is_other_variant(a: &Map<String, Value>, b: &Map<String, Value>) -> bool {
+    is_variant(a) && is_variant(b) && a.keys().next() != b.keys().next()
+}
+
+/// Enums serialize as objects with a single key, named after the variant.
+/// Variants are capitalized, unlike fields.
+fn is_variant(map: &Map<String, Value>) -> bool {
+    let mut keys = map.keys();
+
+    match (keys.next(), keys.next()) {
+        (Some(key), None) => key.starts_with(|c: char| c.is_ascii_uppercase()),
+        _ => false,
+    }
+}
+
+/// Removes the field at `path` from a component's diff, along with the objects it leaves empty
+fn remove_field(value: &mut Value, path: &[&str]) {
+    let (Value::Object(map), [key, rest @ ..]) = (value, path)
+    else { return; };
+
+    if rest.is_empty() {
+        map.remove(*key);
+        return;
+    }
+
+    let Some(child) = map.get_mut(*key)
+    else { return; };
+
+    remove_field(child, rest);
+    if matches!(child, Value::Object(child) if child.is_empty()) {
+        map.remove(*key);
+    }
+}
+
+/// Collects the path of every field in `value`. Objects are followed and everything else is a field.
+fn flatten(value: &Value, path: &mut Vec<String>, fields: &mut Vec<Vec<String>>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                path.push(key.clone());
+                flatten(value, path, fields);
+                path.pop();
+            }
+        }
+        // component lists
+        Value::Array(list) if path.len() == 1 => {
+            for (i, value) in list.iter().enumerate() {
+                path.push(i.to_string());
+                flatten(value, path, fields);
+                path.pop();
+            }
+        }
+        _ => fields.push(path.clone()),
+    }
+}
+
+/// Whether a layer sets the field at `path`, which starts with a component list and index
+fn sets(layer: &Map<String, Value>, path: &[String]) -> bool {
+    let [list, index, path @ ..] = path
+    else { return false; };
+
+    let mut value = match layer.get(list) {
+        Some(Value::Array(list)) => match index.parse::<usize>().ok().and_then(|i| list.get(i)) {
+            Some(value) => value,
+            None => return false,
+        },
+        Some(value @ Value::Object(_)) => value,
+        _ => return false,
+    };
+
+    for key in path {
+        match value {
+            Value::Object(map) => match map.get(key) {
+                Some(next) => value = next,
+                None => return false,
+            },
+            // replaces everything below it
+            _ => return true,
+        }
+    }
+
+    !matches!(value, Value::Object(map) if map.is_empty())
+}
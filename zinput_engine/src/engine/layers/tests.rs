@@ -0,0 +1,193 @@
+use serde_json::{json, Map, Value};
+use zinput_device::{
+    component::{
+        controller::ResponseCurve,
+        touch_pad::{StickSide, TouchPadMode},
+    },
+    DeviceConfig,
+};
+
+use super::{ConfigLayer, ConfigLayers, ConfigSource};
+
+fn two_controllers() -> DeviceConfig {
+    serde_json::from_str(r#"{"controllers":[{},{}]}"#).unwrap()
+}
+
+fn layer(value: Value) -> Option<Map<String, Value>> {
+    match value {
+        Value::Object(map) => Some(map),
+        _ => panic!("layer is not an object"),
+    }
+}
+
+fn layers() -> ConfigLayers {
+    ConfigLayers {
+        global: layer(json!({"controllers": {"left_stick": {"deadzone": 10}}})),
+        preset: layer(json!({"controllers": [{"left_stick": {"deadzone": 20}}]})),
+        device: None,
+    }
+}
+
+#[test]
+fn layers_override_lower_layers() {
+    let config = layers().apply(&two_controllers()).unwrap();
+
+    assert_eq!(config.controllers[0].left_stick.deadzone, 20);
+    assert_eq!(config.controllers[1].left_stick.deadzone, 10);
+    assert_eq!(config.controllers[1].right_stick.deadzone, 0);
+}
+
+#[test]
+fn device_layer_only_stores_changes() {
+    let defaults = two_controllers();
+    let mut layers = layers();
+
+    let mut config = layers.apply(&defaults).unwrap();
+    config.controllers[1].right_stick.deadzone = 5;
+
+    let diff = layers
+        .diff(ConfigLayer::Device, &config, &defaults)
+        .unwrap();
+    assert_eq!(
+        Value::Object(diff.clone()),
+        json!({"controllers": [{}, {"right_stick": {"deadzone": 5}}]})
+    );
+
+    layers.device = Some(diff);
+    let config = layers.apply(&defaults).unwrap();
+    assert_eq!(config.controllers[0].left_stick.deadzone, 20);
+    assert_eq!(config.controllers[1].right_stick.deadzone, 5);
+}
+
+#[test]
+fn variants_are_replaced() {
+    let mut layers = layers();
+    layers.preset = layer(json!({"controllers": [{"left_stick": {"curve": {"Exponent": 2.0}}}]}));
+    layers.device =
+        layer(json!({"controllers": [{"left_stick": {"curve": {"Points": [[0.5, 0.2]]}}}]}));
+
+    let config = layers.apply(&two_controllers()).unwrap();
+
+    assert_eq!(
+        config.controllers[0].left_stick.curve,
+        ResponseCurve::Points(vec![[0.5, 0.2]])
+    );
+}
+
+#[test]
+fn shared_layers_only_take_edits() {
+    let defaults = two_controllers();
+    let mut layers = layers();
+    layers.device = layer(json!({"controllers": [{}, {"right_stick": {"deadzone": 5}}]}));
+
+    let mut config = layers.apply(&defaults).unwrap();
+    config.controllers[1].left_stick.anti_deadzone = 7;
+    // calibration is kept out of shared layers, and remapping out of the global layer
+    config.controllers[0].left_stick.samples = Some([0.5; 32]);
+    config.controllers[0].remap[0] = 1;
+
+    let global = layers
+        .diff(ConfigLayer::Global, &config, &defaults)
+        .unwrap();
+    assert_eq!(
+        Value::Object(global),
+        json!({"controllers": {"left_stick": {"deadzone": 10, "anti_deadzone": 7}}})
+    );
+
+    let mut remap = (0..64).collect::<Vec<u8>>();
+    remap[0] = 1;
+
+    let preset = layers
+        .diff(ConfigLayer::Preset, &config, &defaults)
+        .unwrap();
+    assert_eq!(
+        Value::Object(preset),
+        json!({"controllers": [{"left_stick": {"deadzone": 20, "anti_deadzone": 7}, "remap": remap}]})
+    );
+}
+
+#[test]
+fn lower_layer_changes_variant() {
+    let defaults: DeviceConfig = serde_json::from_str(r#"{"touch_pads":[{}]}"#).unwrap();
+    let mut layers = ConfigLayers {
+        preset: layer(
+            json!({"touch_pads": [{"mode": {"Stick": {"side": "Left", "relative": false, "sensitivity": 1.0}}}]}),
+        ),
+        ..Default::default()
+    };
+
+    let mut config = layers.apply(&defaults).unwrap();
+    let TouchPadMode::Stick { sensitivity, .. } = &mut config.touch_pads[0].mode else {
+        panic!("preset did not set the mode");
+    };
+    *sensitivity = 2.0;
+
+    let diff = layers
+        .diff(ConfigLayer::Device, &config, &defaults)
+        .unwrap();
+    assert_eq!(
+        Value::Object(diff.clone()),
+        json!({"touch_pads": [{"mode": {"Stick": {"side": "Left", "relative": false, "sensitivity": 2.0}}}]})
+    );
+    layers.device = Some(diff);
+
+    layers.preset = layer(json!({"touch_pads": [{"mode": {"Mouse": {"sensitivity": 1.0}}}]}));
+    let config = layers.apply(&defaults).unwrap();
+
+    let TouchPadMode::Stick {
+        side,
+        relative,
+        sensitivity,
+    } = &config.touch_pads[0].mode
+    else {
+        panic!("device layer did not keep its mode");
+    };
+    assert_eq!(*side, StickSide::Left);
+    assert!(!relative);
+    assert_eq!(*sensitivity, 2.0);
+}
+
+#[test]
+fn profiles_apply_over_layers() {
+    let defaults = two_controllers();
+    let mut profile = defaults.clone();
+    profile.controllers[1].right_stick.deadzone = 3;
+
+    let config = layers().apply_over(&defaults, &profile).unwrap();
+
+    assert_eq!(config.controllers[0].left_stick.deadzone, 20);
+    assert_eq!(config.controllers[1].left_stick.deadzone, 10);
+    assert_eq!(config.controllers[1].right_stick.deadzone, 3);
+}
+
+#[test]
+fn sources() {
+    let defaults = two_controllers();
+    let layers = layers();
+
+    let mut config = layers.apply(&defaults).unwrap();
+    config.controllers[1].right_stick.deadzone = 5;
+
+    let sources = layers.sources(&defaults, &config).unwrap();
+    let source = |pointer: &str| {
+        sources
+            .iter()
+            .find(|(field, _)| field == pointer)
+            .map(|(_, source)| *source)
+            .unwrap()
+    };
+
+    assert_eq!(
+        source("/controllers/0/left_stick/deadzone"),
+        ConfigSource::Layer(ConfigLayer::Preset)
+    );
+    assert_eq!(
+        source("/controllers/1/left_stick/deadzone"),
+        ConfigSource::Layer(ConfigLayer::Global)
+    );
+    assert_eq!(
+        source("/controllers/1/right_stick/deadzone"),
+        ConfigSource::Edited
+    );
+    assert_eq!(source("/controllers/0/remap"), ConfigSource::Default);
+}
@@ -4,38 +4,98 @@ use dashmap::DashMap;
 use uuid::Uuid;
 use zinput_device::DeviceInfo;
 
-mod device;
+use crate::{
+    event::{Event, EventBus, Subscription},
+    plugin::{Plugin, PluginStatus},
+};
 
-use self::device::InternalDevice;
-pub use self::device::{DeviceHandle, DeviceView};
+mod change;
+mod config;
+mod device;
+mod fusion;
+mod layers;
+mod profiles;
+mod registry;
+
+pub use self::change::{ChangeFilter, ComponentChange, DeviceChange};
+pub(crate) use self::config::migrate_config;
+pub use self::config::{ConfigStore, CONFIG_ROOT_VAR, CONFIG_VERSION};
+pub use self::device::{DeviceHandle, DeviceRead, DeviceView, Stamp};
+pub use self::layers::{ConfigLayer, ConfigLayers, ConfigSource};
+pub use self::profiles::{Hotkey, HotkeyAction, Profiles};
+pub use self::registry::{KnownDevice, REGISTRY_FILE};
+use self::{device::InternalDevice, registry::Registry};
 
 pub struct Engine {
     devices: DashMap<Uuid, Arc<InternalDevice>>,
-    ids: DashMap<String, Uuid>,
+    registry: Arc<Registry>,
+
+    events: Arc<EventBus>,
+    store: Arc<ConfigStore>,
 }
 
 impl Engine {
-    pub fn new() -> Self {
+    pub fn new(store: ConfigStore) -> Self {
         Engine {
             devices: Default::default(),
-            ids: DashMap::new(),
+            registry: Arc::new(Registry::load(store.root().join(REGISTRY_FILE))),
+
+            events: Arc::new(EventBus::new()),
+            store: Arc::new(store),
+        }
+    }
+
+    pub fn config_store(&self) -> &ConfigStore {
+        &self.store
+    }
+
+    pub fn emit(&self, event: Event) {
+        self.events.emit(event);
+    }
+
+    pub fn events(&self) -> &Arc<EventBus> {
+        &self.events
+    }
+
+    /// Delivers the events listed in `plugin.events()` to `plugin.on_event`.
+    ///
+    /// Events are only delivered while the plugin is running,
+    /// so a stopped plugin can be started again with the same subscription.
+    ///
+    /// Returns `None` if the plugin does not listen for any events.
+    pub fn subscribe(&self, plugin: Arc<dyn Plugin + Send + Sync>) -> Option<Subscription> {
+        let kinds = plugin.events().to_vec();
+        if kinds.is_empty() {
+            return None;
         }
+
+        Some(self.events.subscribe(&kinds, move |event| {
+            if plugin.status() == PluginStatus::Running {
+                plugin.on_event(event);
+            }
+        }))
     }
 
     pub fn new_device(&self, info: DeviceInfo) -> Result<DeviceHandle, DeviceAlreadyExists> {
         self.release_devices();
 
-        match self.reclaim_device(&info) {
-            Ok(handle) => return Ok(handle),
+        let handle = match self.reclaim_device(&info) {
+            Ok(handle) => handle,
             Err(ReclaimError::InUse) => return Err(DeviceAlreadyExists),
-            Err(ReclaimError::NoId) => {}
-        }
+            Err(ReclaimError::NoId) => {
+                let id = Uuid::new_v4();
+                let internal = InternalDevice::new(info.clone(), id, self.store.clone());
+                let handle =
+                    DeviceHandle::new(internal.clone(), self.events.clone(), self.registry.clone())
+                        .ok_or(DeviceAlreadyExists)?;
 
-        let id = Uuid::new_v4();
-        let internal = InternalDevice::new(info, id);
-        let handle = DeviceHandle::new(internal.clone()).ok_or(DeviceAlreadyExists)?;
+                self.devices.insert(id, internal);
 
-        self.devices.insert(id, internal);
+                handle
+            }
+        };
+
+        self.emit(Event::DeviceAdded(*handle.uuid(), info));
 
         Ok(handle)
     }
@@ -43,28 +103,27 @@ impl Engine {
     fn reclaim_device(&self, info: &DeviceInfo) -> Result<DeviceHandle, ReclaimError> {
         let id = info.id.as_ref().ok_or(ReclaimError::NoId)?;
 
-        let device = match self.ids.get(id) {
-            Some(uuid) => match self.devices.get(uuid.value()) {
-                Some(device) => device.value().clone(),
-                None => {
-                    let device = InternalDevice::new(info.clone(), *uuid.value());
-                    self.devices.insert(*uuid.value(), device.clone());
+        let known = self
+            .registry
+            .uuid(id)
+            .and_then(|uuid| self.devices.get(&uuid))
+            .map(|device| device.value().clone());
 
-                    device
-                }
-            },
-            None => {
-                let uuid = Uuid::new_v4();
-                self.ids.insert(id.to_owned(), uuid);
+        // a device that is in use keeps its registry entry as it is
+        if let Some(device) = known {
+            let handle = DeviceHandle::new(device, self.events.clone(), self.registry.clone())
+                .ok_or(ReclaimError::InUse)?;
+            self.registry.connect(id, info);
 
-                let device = InternalDevice::new(info.clone(), uuid);
-                self.devices.insert(uuid, device.clone());
+            return Ok(handle);
+        }
 
-                device
-            }
-        };
+        let uuid = self.registry.connect(id, info);
+        let device = InternalDevice::new(info.clone(), uuid, self.store.clone());
+        self.devices.insert(uuid, device.clone());
 
-        DeviceHandle::new(device).ok_or(ReclaimError::InUse)
+        DeviceHandle::new(device, self.events.clone(), self.registry.clone())
+            .ok_or(ReclaimError::InUse)
     }
 
     pub fn devices(&self) -> Devices {
@@ -83,6 +142,37 @@ impl Engine {
             .map(|int| DeviceView::new(int.value().clone()))
     }
 
+    /// Every device with an id that has connected, including in earlier runs
+    pub fn known_devices(&self) -> Vec<KnownDevice> {
+        self.registry.devices()
+    }
+
+    /// Known devices that are not connected now
+    ///
+    /// A frontend can output one of these before it connects,
+    /// since the device keeps its uuid when it does.
+    pub fn disconnected_devices(&self) -> Vec<KnownDevice> {
+        let mut devices = self.registry.devices();
+        devices.retain(|device| !self.is_connected(&device.uuid));
+        devices
+    }
+
+    pub fn known_device(&self, uuid: &Uuid) -> Option<KnownDevice> {
+        self.registry.get(uuid)
+    }
+
+    /// Gives a device a name to show instead of its own, or removes it if `nickname` is `None`
+    pub fn set_nickname(&self, uuid: &Uuid, nickname: Option<String>) -> anyhow::Result<()> {
+        self.registry.set_nickname(uuid, nickname)
+    }
+
+    /// Whether a backend is updating the device, views can outlive its connection
+    pub fn is_connected(&self, uuid: &Uuid) -> bool {
+        self.devices
+            .get(uuid)
+            .map_or(false, |device| device.handle.load(Ordering::Acquire))
+    }
+
     fn release_devices(&self) {
         self.devices.retain(|_, int| !int.should_remove());
     }
@@ -104,6 +194,25 @@ impl std::fmt::Display for DeviceAlreadyExists {
 
 impl std::error::Error for DeviceAlreadyExists {}
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FeedbackError {
+    /// The device does not accept this kind of feedback
+    Unsupported,
+    /// The backend that owned the device has released it
+    Disconnected,
+}
+
+impl std::fmt::Display for FeedbackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeedbackError::Unsupported => write!(f, "device does not support feedback"),
+            FeedbackError::Disconnected => write!(f, "device is disconnected"),
+        }
+    }
+}
+
+impl std::error::Error for FeedbackError {}
+
 pub struct Devices<'a> {
     iter: dashmap::iter::Iter<'a, Uuid, Arc<InternalDevice>>,
 }
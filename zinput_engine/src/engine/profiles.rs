@@ -0,0 +1,166 @@
+//! Switching a device between named configs while it is in use
+//!
+//! A device's profiles are named configs from its namespace, see [`ConfigStore::namespace`](super::ConfigStore::namespace).
+//! Hotkeys are chords of controller buttons that select a profile or cycle through them:
+//!
+//! ```json
+//! {
+//!     "profiles": ["racing", "shooter"],
+//!     "hotkeys": [
+//!         {"chord": ["Home", "Right"], "action": "Next"},
+//!         {"chord": ["Home", "Left"], "action": "Previous"},
+//!         {"chord": ["Home", "A"], "action": {"Select": "racing"}}
+//!     ]
+//! }
+//! ```
+//!
+//! Chords are checked against the raw buttons of every controller, so remapping does not change them.
+//! While a chord is held its buttons are released on the configured device.
+//!
+//! Profile configs are loaded when the profiles are set, and reloaded when the device saves over one.
+//! A profile's config is applied like a layer on top of the device's
+//! [config layers](super::layers): only its values that differ from the defaults replace the layered ones.
+
+use serde::{Deserialize, Serialize};
+use zinput_device::{component::controller::Button, Device, DeviceConfig};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Profiles {
+    /// Config names, in the order hotkeys cycle through them
+    pub profiles: Vec<String>,
+    pub hotkeys: Vec<Hotkey>,
+}
+
+impl Profiles {
+    /// The action of the first hotkey whose chord was completed between two updates
+    pub fn triggered(&self, previous: u64, buttons: u64) -> Option<&HotkeyAction> {
+        self.hotkeys
+            .iter()
+            .find(|hotkey| hotkey.is_held(buttons) && !hotkey.is_held(previous))
+            .map(|hotkey| &hotkey.action)
+    }
+
+    /// The buttons of every chord held in `buttons`
+    pub fn held_chords(&self, buttons: u64) -> u64 {
+        self.hotkeys
+            .iter()
+            .filter(|hotkey| hotkey.is_held(buttons))
+            .fold(0, |held, hotkey| held | hotkey.buttons())
+    }
+
+    /// The index of the profile `action` switches to from `active`
+    pub fn target(&self, action: &HotkeyAction, active: Option<usize>) -> Option<usize> {
+        let len = self.profiles.len();
+        if len == 0 {
+            return None;
+        }
+
+        match action {
+            HotkeyAction::Next => Some(active.map_or(0, |active| (active + 1) % len)),
+            HotkeyAction::Previous => {
+                Some(active.map_or(len - 1, |active| (active + len - 1) % len))
+            }
+            HotkeyAction::Select(name) => self.profiles.iter().position(|profile| profile == name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Hotkey {
+    pub chord: Vec<Button>,
+    pub action: HotkeyAction,
+}
+
+impl Hotkey {
+    /// An empty chord is never held
+    pub fn is_held(&self, buttons: u64) -> bool {
+        !self.chord.is_empty() && self.chord.iter().all(|button| button.is_pressed(buttons))
+    }
+
+    fn buttons(&self) -> u64 {
+        let mut buttons = 0;
+        for button in &self.chord {
+            button.set_pressed(&mut buttons);
+        }
+        buttons
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum HotkeyAction {
+    Next,
+    Previous,
+    /// Selects the profile with this name
+    Select(String),
+}
+
+impl std::fmt::Display for HotkeyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HotkeyAction::Next => write!(f, "Next Profile"),
+            HotkeyAction::Previous => write!(f, "Previous Profile"),
+            HotkeyAction::Select(name) => write!(f, "Select '{name}'"),
+        }
+    }
+}
+
+/// A device's profiles, with their configs loaded so they can be switched to during an update
+#[derive(Default)]
+pub(super) struct ProfileState {
+    pub(super) profiles: Profiles,
+    /// One per profile, `None` if the config could not be loaded
+    pub(super) configs: Vec<Option<DeviceConfig>>,
+    pub(super) active: Option<usize>,
+}
+
+impl ProfileState {
+    /// Replaces the loaded config of the profile `name`, if there is one
+    pub(super) fn set_config(&mut self, name: &str, config: Option<DeviceConfig>) {
+        let index = self
+            .profiles
+            .profiles
+            .iter()
+            .position(|profile| profile == name);
+
+        if let Some(loaded) = index.and_then(|index| self.configs.get_mut(index)) {
+            *loaded = config;
+        }
+    }
+
+    /// Releases the buttons of held chords on `device`, before it is configured,
+    /// and returns the name of the profile to switch to if a hotkey was completed between two updates
+    pub(super) fn check_hotkeys(
+        &self,
+        previous: &Device,
+        raw: &Device,
+        device: &mut Device,
+    ) -> Option<String> {
+        if self.profiles.hotkeys.is_empty() {
+            return None;
+        }
+
+        let buttons = held_buttons(raw);
+
+        let chords = self.profiles.held_chords(buttons);
+        for controller in &mut device.controllers {
+            controller.buttons &= !chords;
+        }
+
+        let action = self.profiles.triggered(held_buttons(previous), buttons)?;
+        let index = self.profiles.target(action, self.active)?;
+
+        self.profiles.profiles.get(index).cloned()
+    }
+}
+
+/// The buttons held on any of a device's controllers
+fn held_buttons(device: &Device) -> u64 {
+    device
+        .controllers
+        .iter()
+        .fold(0, |buttons, controller| buttons | controller.buttons)
+}
@@ -0,0 +1,112 @@
+use zinput_device::component::controller::Button;
+
+use super::{Hotkey, HotkeyAction, Profiles};
+
+fn profiles() -> Profiles {
+    Profiles {
+        profiles: vec!["a".to_owned(), "b".to_owned(), "c".to_owned()],
+        hotkeys: vec![
+            Hotkey {
+                chord: vec![Button::Home, Button::Right],
+                action: HotkeyAction::Next,
+            },
+            Hotkey {
+                chord: vec![Button::Home, Button::Left],
+                action: HotkeyAction::Previous,
+            },
+            Hotkey {
+                chord: vec![Button::Home, Button::A],
+                action: HotkeyAction::Select("b".to_owned()),
+            },
+        ],
+    }
+}
+
+fn held(buttons: &[Button]) -> u64 {
+    let mut held = 0;
+    for button in buttons {
+        button.set_pressed(&mut held);
+    }
+    held
+}
+
+#[test]
+fn chords_trigger_once() {
+    let profiles = profiles();
+    let chord = held(&[Button::Home, Button::Right]);
+
+    assert_eq!(
+        profiles.triggered(held(&[Button::Home]), chord),
+        Some(&HotkeyAction::Next)
+    );
+    assert_eq!(
+        profiles.triggered(0, chord | held(&[Button::B])),
+        Some(&HotkeyAction::Next)
+    );
+    // still held
+    assert_eq!(profiles.triggered(chord, chord), None);
+    assert_eq!(profiles.triggered(0, held(&[Button::Home])), None);
+    assert_eq!(profiles.triggered(0, held(&[Button::Right])), None);
+}
+
+#[test]
+fn held_chords() {
+    let profiles = profiles();
+
+    assert_eq!(profiles.held_chords(held(&[Button::Home])), 0);
+    assert_eq!(
+        profiles.held_chords(held(&[Button::Home, Button::Right, Button::B])),
+        held(&[Button::Home, Button::Right])
+    );
+    assert_eq!(
+        profiles.held_chords(held(&[Button::Home, Button::Right, Button::A])),
+        held(&[Button::Home, Button::Right, Button::A])
+    );
+}
+
+#[test]
+fn empty_chords_never_trigger() {
+    let mut profiles = profiles();
+    profiles.hotkeys = vec![Hotkey {
+        chord: Vec::new(),
+        action: HotkeyAction::Next,
+    }];
+
+    assert_eq!(profiles.triggered(0, u64::MAX), None);
+    assert_eq!(profiles.held_chords(u64::MAX), 0);
+}
+
+#[test]
+fn targets() {
+    let profiles = profiles();
+
+    assert_eq!(profiles.target(&HotkeyAction::Next, None), Some(0));
+    assert_eq!(profiles.target(&HotkeyAction::Next, Some(2)), Some(0));
+    assert_eq!(profiles.target(&HotkeyAction::Previous, None), Some(2));
+    assert_eq!(profiles.target(&HotkeyAction::Previous, Some(0)), Some(2));
+    assert_eq!(profiles.target(&HotkeyAction::Previous, Some(1)), Some(0));
+    assert_eq!(
+        profiles.target(&HotkeyAction::Select("b".to_owned()), None),
+        Some(1)
+    );
+    assert_eq!(
+        profiles.target(&HotkeyAction::Select("d".to_owned()), Some(0)),
+        None
+    );
+
+    assert_eq!(Profiles::default().target(&HotkeyAction::Next, None), None);
+}
+
+#[test]
+fn parse() {
+    let profiles: Profiles = serde_json::from_str(
+        r#"{"profiles":["a"],"hotkeys":[{"chord":["Home","Right"],"action":"Next"},{"chord":["Home","A"],"action":{"Select":"a"}}]}"#,
+    )
+    .unwrap();
+
+    assert_eq!(profiles.hotkeys[0].chord, vec![Button::Home, Button::Right]);
+    assert_eq!(
+        profiles.hotkeys[1].action,
+        HotkeyAction::Select("a".to_owned())
+    );
+}
@@ -0,0 +1,212 @@
+//! Devices that have connected before
+//!
+//! Every device with an id keeps the same [`Uuid`] across restarts,
+//! so anything that refers to a device by uuid still finds it after the device reconnects.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    thread::JoinHandle,
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+use crossbeam_channel::{Receiver, Sender};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zinput_device::DeviceInfo;
+
+use crate::util::write_atomic;
+
+#[cfg(test)]
+mod tests;
+
+/// Registry file name, relative to the config root
+pub const REGISTRY_FILE: &'static str = "known_devices.json";
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct KnownDevice {
+    pub id: String,
+    pub uuid: Uuid,
+    /// The device's name when it last connected
+    pub name: String,
+    pub kind: Option<String>,
+    /// When the device last connected or disconnected
+    pub last_seen: SystemTime,
+    pub nickname: Option<String>,
+}
+
+impl KnownDevice {
+    /// The nickname if the device has one, or else its name
+    pub fn display_name(&self) -> &str {
+        self.nickname.as_deref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+struct RegistryFile {
+    devices: Vec<KnownDevice>,
+}
+
+pub(super) struct Registry {
+    devices: Mutex<BTreeMap<String, KnownDevice>>,
+    /// Devices connect and disconnect on backend threads,
+    /// so the registry is written by its own thread instead of theirs
+    writer: Option<Sender<String>>,
+    writer_thread: Option<JoinHandle<()>>,
+}
+
+impl Registry {
+    /// Loads the registry saved at `path`
+    ///
+    /// A registry that can't be read is moved aside and replaced by an empty one.
+    pub(super) fn load(path: PathBuf) -> Self {
+        let devices = match read_registry(&path) {
+            Ok(file) => file
+                .devices
+                .into_iter()
+                .map(|device| (device.id.clone(), device))
+                .collect(),
+            Err(err) => {
+                log::warn!("failed to load device registry: {err:#}");
+
+                let mut bad_name = path.file_name().unwrap_or_default().to_owned();
+                bad_name.push(".bad");
+                let _ = std::fs::rename(&path, path.with_file_name(bad_name));
+
+                BTreeMap::new()
+            }
+        };
+
+        let (writer, contents) = crossbeam_channel::unbounded();
+        let writer_thread = std::thread::spawn(move || write_thread(path, contents));
+
+        Registry {
+            devices: Mutex::new(devices),
+            writer: Some(writer),
+            writer_thread: Some(writer_thread),
+        }
+    }
+
+    /// Records that a device connected and returns its uuid, assigning one if the device is new
+    pub(super) fn connect(&self, id: &str, info: &DeviceInfo) -> Uuid {
+        let mut devices = self.devices.lock();
+
+        let device = devices.entry(id.to_owned()).or_insert_with(|| KnownDevice {
+            id: id.to_owned(),
+            uuid: Uuid::new_v4(),
+            name: info.name.clone(),
+            kind: info.kind.clone(),
+            last_seen: SystemTime::now(),
+            nickname: None,
+        });
+
+        device.name = info.name.clone();
+        device.kind = info.kind.clone();
+        device.last_seen = SystemTime::now();
+
+        let uuid = device.uuid;
+        self.save(&devices);
+
+        uuid
+    }
+
+    pub(super) fn disconnect(&self, id: &str) {
+        let mut devices = self.devices.lock();
+
+        if let Some(device) = devices.get_mut(id) {
+            device.last_seen = SystemTime::now();
+            self.save(&devices);
+        }
+    }
+
+    pub(super) fn set_nickname(&self, uuid: &Uuid, nickname: Option<String>) -> Result<()> {
+        let mut devices = self.devices.lock();
+
+        let device = devices
+            .values_mut()
+            .find(|device| &device.uuid == uuid)
+            .context("device has no id, so it can't be given a nickname")?;
+
+        device.nickname = nickname.filter(|nickname| !nickname.is_empty());
+        self.save(&devices);
+
+        Ok(())
+    }
+
+    pub(super) fn get(&self, uuid: &Uuid) -> Option<KnownDevice> {
+        self.devices
+            .lock()
+            .values()
+            .find(|device| &device.uuid == uuid)
+            .cloned()
+    }
+
+    pub(super) fn uuid(&self, id: &str) -> Option<Uuid> {
+        self.devices.lock().get(id).map(|device| device.uuid)
+    }
+
+    pub(super) fn devices(&self) -> Vec<KnownDevice> {
+        self.devices.lock().values().cloned().collect()
+    }
+
+    /// Queues the registry to be written, without waiting for the disk
+    fn save(&self, devices: &BTreeMap<String, KnownDevice>) {
+        let file = RegistryFile {
+            devices: devices.values().cloned().collect(),
+        };
+
+        let string = match serde_json::to_string_pretty(&file) {
+            Ok(string) => string,
+            Err(err) => {
+                log::warn!("failed to serialize device registry: {err:#}");
+                return;
+            }
+        };
+
+        if let Some(writer) = &self.writer {
+            let _ = writer.send(string);
+        }
+    }
+}
+
+impl Drop for Registry {
+    /// Waits for queued saves to be written
+    fn drop(&mut self) {
+        self.writer.take();
+
+        if let Some(thread) = self.writer_thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn write_thread(path: PathBuf, contents: Receiver<String>) {
+    while let Ok(mut string) = contents.recv() {
+        // only the newest registry needs to be written
+        while let Ok(newer) = contents.try_recv() {
+            string = newer;
+        }
+
+        if let Err(err) = write_atomic(&path, string.as_bytes()) {
+            log::warn!(
+                "failed to save device registry: failed to write file '{}': {err:#}",
+                path.display()
+            );
+        }
+    }
+}
+
+fn read_registry(path: &Path) -> Result<RegistryFile> {
+    if !path.exists() {
+        return Ok(RegistryFile::default());
+    }
+
+    let string = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read file '{}'", path.display()))?;
+
+    serde_json::from_str(&string)
+        .with_context(|| format!("failed to deserialize file '{}'", path.display()))
+}
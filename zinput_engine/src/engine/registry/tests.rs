@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use zinput_device::DeviceInfo;
+
+use super::Registry;
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("zinput-test-{}-{name}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    path
+}
+
+#[test]
+fn same_uuid_after_restart() {
+    let path = temp_path("registry");
+    let info = DeviceInfo::new("Pro Controller".to_owned());
+
+    let registry = Registry::load(path.clone());
+    let uuid = registry.connect("joycon/1234", &info);
+    assert_eq!(registry.connect("joycon/1234", &info), uuid);
+    assert_ne!(registry.connect("joycon/5678", &info), uuid);
+
+    registry
+        .set_nickname(&uuid, Some("Left Hand".to_owned()))
+        .unwrap();
+    registry.disconnect("joycon/1234");
+    // waits for the registry to be written
+    drop(registry);
+
+    let registry = Registry::load(path.clone());
+    assert_eq!(registry.uuid("joycon/1234"), Some(uuid));
+    assert_eq!(registry.uuid("joycon/9999"), None);
+    assert_eq!(registry.connect("joycon/1234", &info), uuid);
+
+    let device = registry.get(&uuid).unwrap();
+    assert_eq!(device.display_name(), "Left Hand");
+    assert_eq!(registry.devices().len(), 2);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn unreadable_registry() {
+    let path = temp_path("registry-bad");
+    std::fs::write(&path, "not json").unwrap();
+
+    let registry = Registry::load(path.clone());
+    assert!(registry.devices().is_empty());
+
+    let mut bad_name = path.file_name().unwrap().to_owned();
+    bad_name.push(".bad");
+    let bad_path = path.with_file_name(bad_name);
+    assert!(bad_path.exists());
+
+    let _ = std::fs::remove_file(bad_path);
+}
@@ -1,7 +1,24 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use arc_swap::ArcSwap;
+use crossbeam_channel::{Sender, TrySendError};
 use zinput_device::DeviceInfo;
 
 use crate::util::Uuid;
 
+#[cfg(test)]
+mod tests;
+
+/// Maximum number of events that can be queued for a single subscriber
+/// before new events for it are dropped.
+const QUEUE_SIZE: usize = 64;
+
 #[derive(Clone)]
 pub enum Event {
     DeviceUpdate(Uuid),
@@ -23,7 +40,7 @@ impl Event {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum EventKind {
     DeviceUpdate,
     DeviceAdded,
@@ -31,3 +48,114 @@ pub enum EventKind {
     UsbConnected,
     UsbDisconnected,
 }
+
+/// Dispatches [`Event`]s to subscribers.
+///
+/// Every subscriber gets its own queue and delivery thread,
+/// so a slow subscriber only delays its own events.
+pub struct EventBus {
+    /// Replaced whenever a subscriber is added or removed, so emitting never waits on a lock
+    subscribers: ArcSwap<Vec<Arc<Subscriber>>>,
+    next_id: AtomicUsize,
+}
+
+struct Subscriber {
+    id: usize,
+    kinds: Vec<EventKind>,
+    sender: Sender<Event>,
+}
+
+impl EventBus {
+    pub(crate) fn new() -> Self {
+        EventBus {
+            subscribers: ArcSwap::default(),
+            next_id: AtomicUsize::new(0),
+        }
+    }
+
+    /// Sends `event` to every subscriber listening for its kind.
+    ///
+    /// This never blocks. If a subscriber's queue is full, the event is dropped for that subscriber.
+    pub fn emit(&self, event: Event) {
+        let kind = event.kind();
+
+        for subscriber in self.subscribers.load().iter() {
+            if !subscriber.kinds.contains(&kind) {
+                continue;
+            }
+
+            match subscriber.sender.try_send(event.clone()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    // device updates are frequent and superseded by the next one
+                    if kind != EventKind::DeviceUpdate {
+                        log::warn!("event queue is full, dropping {kind:?} event");
+                    }
+                }
+                // the handler panicked, the subscriber is removed when its subscription is dropped
+                Err(TrySendError::Disconnected(_)) => {}
+            }
+        }
+    }
+
+    /// Calls `handler` on a new thread for every emitted event whose kind is in `kinds`.
+    ///
+    /// Events stop being delivered when the returned [`Subscription`] is dropped.
+    pub fn subscribe<F>(self: &Arc<Self>, kinds: &[EventKind], mut handler: F) -> Subscription
+    where
+        F: FnMut(&Event) + Send + 'static,
+    {
+        let (sender, receiver) = crossbeam_channel::bounded::<Event>(QUEUE_SIZE);
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let subscriber = Arc::new(Subscriber {
+            id,
+            kinds: kinds.to_vec(),
+            sender,
+        });
+
+        self.subscribers.rcu(|subscribers| {
+            let mut subscribers = Vec::clone(subscribers);
+            subscribers.push(subscriber.clone());
+            subscribers
+        });
+
+        let handle = std::thread::spawn(move || {
+            for event in receiver {
+                handler(&event);
+            }
+        });
+
+        Subscription {
+            bus: self.clone(),
+            id,
+            handle: Some(handle),
+        }
+    }
+}
+
+pub struct Subscription {
+    bus: Arc<EventBus>,
+    id: usize,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        // dropping the sender ends the delivery thread once its queue is drained
+        self.bus.subscribers.rcu(|subscribers| {
+            subscribers
+                .iter()
+                .filter(|subscriber| subscriber.id != self.id)
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+
+        if let Some(handle) = self.handle.take() {
+            match handle.join() {
+                Ok(()) => {}
+                Err(_) => log::error!("event delivery thread panicked"),
+            }
+        }
+    }
+}
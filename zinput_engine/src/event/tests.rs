@@ -0,0 +1,74 @@
+use std::{sync::Arc, time::Duration};
+
+use crossbeam_channel::RecvTimeoutError;
+
+use super::{Event, EventBus, EventKind};
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+#[test]
+fn only_subscribed_kinds_are_delivered() {
+    let bus = Arc::new(EventBus::new());
+    let (sender, receiver) = crossbeam_channel::unbounded();
+
+    let _subscription = bus.subscribe(&[EventKind::UsbConnected], move |event| {
+        sender.send(event.kind()).unwrap();
+    });
+
+    bus.emit(Event::UsbDisconnected);
+    bus.emit(Event::UsbConnected);
+    bus.emit(Event::UsbConnected);
+
+    assert_eq!(receiver.recv_timeout(TIMEOUT), Ok(EventKind::UsbConnected));
+    assert_eq!(receiver.recv_timeout(TIMEOUT), Ok(EventKind::UsbConnected));
+    assert!(receiver.try_recv().is_err());
+}
+
+#[test]
+fn every_subscriber_gets_the_event() {
+    let bus = Arc::new(EventBus::new());
+    let (sender, receiver) = crossbeam_channel::unbounded();
+
+    let subscriptions = (0..3)
+        .map(|i| {
+            let sender = sender.clone();
+            bus.subscribe(&[EventKind::UsbConnected], move |_| {
+                sender.send(i).unwrap();
+            })
+        })
+        .collect::<Vec<_>>();
+
+    bus.emit(Event::UsbConnected);
+
+    let mut received = (0..3)
+        .map(|_| receiver.recv_timeout(TIMEOUT).unwrap())
+        .collect::<Vec<_>>();
+    received.sort();
+    assert_eq!(received, vec![0, 1, 2]);
+
+    drop(subscriptions);
+}
+
+#[test]
+fn dropping_a_subscription_stops_delivery() {
+    let bus = Arc::new(EventBus::new());
+    let (sender, receiver) = crossbeam_channel::unbounded();
+
+    let subscription = bus.subscribe(&[EventKind::UsbConnected], move |event| {
+        sender.send(event.kind()).unwrap();
+    });
+
+    bus.emit(Event::UsbConnected);
+    // waits for the delivery thread, which drops the handler and its sender
+    drop(subscription);
+
+    assert_eq!(receiver.recv_timeout(TIMEOUT), Ok(EventKind::UsbConnected));
+    assert_eq!(
+        receiver.recv_timeout(TIMEOUT),
+        Err(RecvTimeoutError::Disconnected)
+    );
+
+    // no subscribers left
+    bus.emit(Event::UsbConnected);
+    assert!(bus.subscribers.load().is_empty());
+}
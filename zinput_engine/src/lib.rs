@@ -6,6 +6,7 @@ pub use zinput_device as device;
 mod engine;
 pub mod event;
 pub mod plugin;
+pub mod recording;
 pub mod util;
 
 pub use self::engine::*;
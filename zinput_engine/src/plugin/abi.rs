@@ -0,0 +1,151 @@
+//! Declaration exported by plugin libraries
+//!
+//! A plugin library is a `cdylib` that calls [`export_plugin!`](crate::export_plugin).
+//! Plugins are handed engine types directly, so a library only loads
+//! if it was built with the same compiler and the same version of `zinput_engine`,
+//! and with the same locked dependencies, features and profile, see [`BUILD_HASH`].
+//! The declaration itself is `repr(C)` and its function uses the C calling convention,
+//! so a mismatched library is refused before any engine type crosses the boundary.
+
+use std::{ffi::CStr, os::raw::c_char, sync::Arc};
+
+use log::{LevelFilter, Log};
+
+use super::Plugin;
+
+/// Version of [`PluginDeclaration`] and [`Registrar`].
+///
+/// Increase this whenever either of them changes.
+pub const ABI_VERSION: u32 = 2;
+
+/// Name of the [`PluginDeclaration`] exported by plugin libraries
+pub const DECLARATION_SYMBOL: &[u8] = b"ZINPUT_PLUGIN\0";
+
+const RUSTC_VERSION: &str = env!("ZINPUT_RUSTC_VERSION");
+const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Hash of the target, profile, features and the locked versions of every dependency of `zinput_engine`
+///
+/// Computed by the build script from the `Cargo.lock` of the package being built,
+/// so an application and a plugin match only if they resolved the same dependencies.
+pub const BUILD_HASH: &str = env!("ZINPUT_BUILD_HASH");
+
+#[doc(hidden)]
+pub const RUSTC_VERSION_C: &str = concat!(env!("ZINPUT_RUSTC_VERSION"), "\0");
+#[doc(hidden)]
+pub const ENGINE_VERSION_C: &str = concat!(env!("CARGO_PKG_VERSION"), "\0");
+#[doc(hidden)]
+pub const BUILD_HASH_C: &str = concat!(env!("ZINPUT_BUILD_HASH"), "\0");
+
+/// Exported by plugin libraries under [`DECLARATION_SYMBOL`]
+///
+/// `abi_version` is always the first field, so it can be read before anything else is trusted.
+#[repr(C)]
+pub struct PluginDeclaration {
+    pub abi_version: u32,
+    pub rustc_version: *const c_char,
+    pub engine_version: *const c_char,
+    pub build_hash: *const c_char,
+    /// Only called once every version and the build hash match
+    pub register: unsafe extern "C" fn(&mut Registrar),
+}
+
+// the version strings are static
+unsafe impl Sync for PluginDeclaration {}
+
+impl PluginDeclaration {
+    /// Checks that the library was built by the same compiler against the same engine and dependencies
+    ///
+    /// # Safety
+    ///
+    /// `abi_version` must already match [`ABI_VERSION`].
+    pub unsafe fn check(&self) -> Result<(), String> {
+        let rustc_version = CStr::from_ptr(self.rustc_version).to_string_lossy();
+        if rustc_version != RUSTC_VERSION {
+            return Err(format!(
+                "built with '{rustc_version}', expected '{RUSTC_VERSION}'"
+            ));
+        }
+
+        let engine_version = CStr::from_ptr(self.engine_version).to_string_lossy();
+        if engine_version != ENGINE_VERSION {
+            return Err(format!(
+                "built against zinput_engine {engine_version}, expected {ENGINE_VERSION}"
+            ));
+        }
+
+        let build_hash = CStr::from_ptr(self.build_hash).to_string_lossy();
+        if build_hash != BUILD_HASH {
+            return Err(format!(
+                "built with different dependencies, features or profile (build {build_hash}, expected {BUILD_HASH})"
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Collects the plugins of a library
+pub struct Registrar<'a> {
+    plugins: &'a mut Vec<Arc<dyn Plugin + Send + Sync>>,
+    logger: &'static dyn Log,
+    max_level: LevelFilter,
+}
+
+impl<'a> Registrar<'a> {
+    pub(super) fn new(plugins: &'a mut Vec<Arc<dyn Plugin + Send + Sync>>) -> Self {
+        Registrar {
+            plugins,
+            logger: log::logger(),
+            max_level: log::max_level(),
+        }
+    }
+
+    pub fn add_plugin(&mut self, plugin: Arc<dyn Plugin + Send + Sync>) {
+        self.plugins.push(plugin);
+    }
+
+    /// Sends the library's log messages to the application's logger
+    ///
+    /// The library has its own copy of the `log` crate, which has no logger until this is called.
+    #[doc(hidden)]
+    pub fn init_logger(&self) {
+        let _ = log::set_logger(self.logger);
+        log::set_max_level(self.max_level);
+    }
+}
+
+/// Exports the plugins of a `cdylib`
+///
+/// ```ignore
+/// zinput_engine::export_plugin!(register);
+///
+/// fn register(registrar: &mut Registrar) {
+///     registrar.add_plugin(Arc::new(MyPlugin::new()));
+/// }
+/// ```
+#[macro_export]
+macro_rules! export_plugin {
+    ($register:path) => {
+        #[doc(hidden)]
+        #[no_mangle]
+        pub static ZINPUT_PLUGIN: $crate::plugin::abi::PluginDeclaration =
+            $crate::plugin::abi::PluginDeclaration {
+                abi_version: $crate::plugin::abi::ABI_VERSION,
+                rustc_version: $crate::plugin::abi::RUSTC_VERSION_C.as_ptr() as *const _,
+                engine_version: $crate::plugin::abi::ENGINE_VERSION_C.as_ptr() as *const _,
+                build_hash: $crate::plugin::abi::BUILD_HASH_C.as_ptr() as *const _,
+                register: {
+                    // a panic aborts instead of unwinding into the application
+                    unsafe extern "C" fn __zinput_register(
+                        registrar: &mut $crate::plugin::abi::Registrar,
+                    ) {
+                        registrar.init_logger();
+                        $register(registrar);
+                    }
+
+                    __zinput_register
+                },
+            };
+    };
+}
@@ -0,0 +1,152 @@
+//! Loading plugins from shared libraries
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use eframe::egui;
+use libloading::{Library, Symbol};
+
+use super::{
+    abi::{PluginDeclaration, Registrar, ABI_VERSION, DECLARATION_SYMBOL},
+    Plugin, PluginKind, PluginStatus,
+};
+use crate::{
+    event::{Event, EventKind},
+    util::Uuid,
+    Engine,
+};
+
+/// Loads the plugins of every library in `dir`
+///
+/// Libraries that fail to load are logged and skipped.
+///
+/// # Safety
+///
+/// Loading a library runs its initialization code.
+/// Only load libraries from trusted directories.
+pub unsafe fn load_dir(dir: &Path) -> Result<Vec<Arc<dyn Plugin + Send + Sync>>> {
+    let mut plugins = Vec::new();
+
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to read plugin directory '{}'", dir.display()))?;
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().and_then(|ext| ext.to_str()) == Some(std::env::consts::DLL_EXTENSION)
+        })
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    for path in paths {
+        match load_library(&path) {
+            Ok(loaded) => {
+                log::info!("loaded {} plugins from '{}'", loaded.len(), path.display());
+                plugins.extend(loaded);
+            }
+            Err(err) => log::error!("failed to load plugin '{}': {err:#}", path.display()),
+        }
+    }
+
+    Ok(plugins)
+}
+
+/// Loads the plugins of a library
+///
+/// # Safety
+///
+/// Loading a library runs its initialization code.
+pub unsafe fn load_library(path: &Path) -> Result<Vec<Arc<dyn Plugin + Send + Sync>>> {
+    let library = Arc::new(Library::new(path).context("failed to open library")?);
+
+    let declaration: Symbol<*const PluginDeclaration> = library
+        .get(DECLARATION_SYMBOL)
+        .context("library is not a zinput plugin")?;
+    let declaration = *declaration;
+
+    let abi_version = std::ptr::addr_of!((*declaration).abi_version).read();
+    if abi_version != ABI_VERSION {
+        anyhow::bail!("plugin abi version {abi_version} is not supported, expected {ABI_VERSION}");
+    }
+
+    let declaration = &*declaration;
+    declaration.check().map_err(anyhow::Error::msg)?;
+
+    let mut plugins = Vec::new();
+    (declaration.register)(&mut Registrar::new(&mut plugins));
+
+    Ok(plugins
+        .into_iter()
+        .map(|plugin| {
+            Arc::new(LibraryPlugin {
+                plugin,
+                _library: library.clone(),
+            }) as Arc<dyn Plugin + Send + Sync>
+        })
+        .collect())
+}
+
+/// Keeps a plugin's library loaded for as long as the plugin exists
+struct LibraryPlugin {
+    // dropped before the library
+    plugin: Arc<dyn Plugin + Send + Sync>,
+    _library: Arc<Library>,
+}
+
+impl Plugin for LibraryPlugin {
+    fn init(&self, engine: Arc<Engine>) {
+        self.plugin.init(engine)
+    }
+
+    fn stop(&self) {
+        self.plugin.stop()
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.plugin.status()
+    }
+
+    fn name(&self) -> &str {
+        self.plugin.name()
+    }
+
+    fn kind(&self) -> PluginKind {
+        self.plugin.kind()
+    }
+
+    fn events(&self) -> &[EventKind] {
+        self.plugin.events()
+    }
+
+    fn update_gui(&self, ctx: &egui::Context, frame: &mut eframe::Frame, ui: &mut egui::Ui) {
+        self.plugin.update_gui(ctx, frame, ui)
+    }
+
+    fn on_event(&self, event: &Event) {
+        self.plugin.on_event(event)
+    }
+
+    fn slots(&self) -> usize {
+        self.plugin.slots()
+    }
+
+    fn bind_slot(&self, slot: usize, device: Option<Uuid>) {
+        self.plugin.bind_slot(slot, device)
+    }
+
+    fn slot_device(&self, slot: usize) -> Option<Uuid> {
+        self.plugin.slot_device(slot)
+    }
+
+    fn settings(&self) -> Option<serde_json::Value> {
+        self.plugin.settings()
+    }
+
+    fn load_settings(&self, settings: serde_json::Value) {
+        self.plugin.load_settings(settings)
+    }
+}
@@ -4,9 +4,13 @@ use eframe::egui;
 
 use crate::{
     event::{Event, EventKind},
+    util::Uuid,
     Engine,
 };
 
+pub mod abi;
+pub mod loader;
+
 pub trait Plugin {
     fn init(&self, engine: Arc<Engine>);
     fn stop(&self);
@@ -22,6 +26,27 @@ pub trait Plugin {
     fn update_gui(&self, _ctx: &egui::Context, _frame: &mut eframe::Frame, _ui: &mut egui::Ui) {}
 
     fn on_event(&self, _event: &Event) {}
+
+    /// Number of devices this plugin can output at once
+    fn slots(&self) -> usize {
+        0
+    }
+
+    /// Outputs `device` in `slot`, or clears the slot if `device` is `None`
+    fn bind_slot(&self, _slot: usize, _device: Option<Uuid>) {}
+
+    /// The device output in `slot`
+    fn slot_device(&self, _slot: usize) -> Option<Uuid> {
+        None
+    }
+
+    /// Settings to save with the session, given back to [`Plugin::load_settings`] on the next start
+    fn settings(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Restores settings returned by [`Plugin::settings`], before the plugin is started
+    fn load_settings(&self, _settings: serde_json::Value) {}
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -0,0 +1,324 @@
+//! Recording and replay of device updates
+//!
+//! A recording is a text file with one JSON value per line.
+//! The first line is a [`Header`] and every line after it is a [`Frame`].
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Lines, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use crossbeam_channel::RecvTimeoutError;
+use serde::{Deserialize, Serialize};
+use zinput_device::{Device, DeviceConfig, DeviceInfo};
+
+use crate::{engine::migrate_config, ConfigStore, DeviceView, CONFIG_VERSION};
+
+/// Version written to new recordings.
+///
+/// Increase this whenever [`Header`] or [`Frame`] changes in a way older readers can't handle.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Directory recordings are saved in, relative to the config root
+pub const RECORDING_DIR: &'static str = "recordings";
+pub const RECORDING_EXTENSION: &'static str = "zrec";
+
+/// How often the recording thread checks whether it should stop
+const STOP_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Header {
+    pub version: u32,
+    pub info: DeviceInfo,
+    /// The device's config when recording started
+    pub config: DeviceConfig,
+    /// The [`CONFIG_VERSION`] the config was written with
+    pub config_version: u64,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Frame {
+    /// Microseconds since recording started
+    pub time: u64,
+    /// The update's sequence number on the recorded device
+    pub sequence: u64,
+    /// Microseconds on the device's own clock, if it has one
+    pub hardware_time: Option<u64>,
+    pub raw: Device,
+    pub configured: Device,
+}
+
+impl Frame {
+    pub fn time(&self) -> Duration {
+        Duration::from_micros(self.time)
+    }
+
+    pub fn hardware_time(&self) -> Option<Duration> {
+        self.hardware_time.map(Duration::from_micros)
+    }
+}
+
+/// Records every update of a device to a file until stopped
+pub struct Recorder {
+    path: PathBuf,
+    frames: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<Result<()>>>,
+}
+
+impl Recorder {
+    pub fn start(mut view: DeviceView, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+
+        let file = File::create(&path)
+            .with_context(|| format!("failed to create file '{}'", path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        let header = Header {
+            version: FORMAT_VERSION,
+            info: view.info().clone(),
+            config: view.config().clone(),
+            config_version: CONFIG_VERSION,
+        };
+        write_line(&mut writer, &header).context("failed to write recording header")?;
+
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        view.subscribe_updates(sender);
+
+        let frames = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let handle = std::thread::spawn({
+            let frames = frames.clone();
+            let stop = stop.clone();
+
+            move || {
+                // keeps the subscription alive
+                let _view = view;
+                let started = Instant::now();
+
+                while !stop.load(Ordering::Acquire) {
+                    let device = match receiver.recv_timeout(STOP_POLL) {
+                        Ok(device) => device,
+                        Err(RecvTimeoutError::Timeout) => continue,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    };
+
+                    let stamp = device.stamp();
+                    let frame = Frame {
+                        time: stamp
+                            .captured
+                            .saturating_duration_since(started)
+                            .as_micros() as u64,
+                        sequence: stamp.sequence,
+                        hardware_time: stamp.hardware_time.map(|time| time.as_micros() as u64),
+                        raw: device.raw().clone(),
+                        configured: device.configured().clone(),
+                    };
+
+                    write_line(&mut writer, &frame).context("failed to write recording frame")?;
+                    frames.fetch_add(1, Ordering::AcqRel);
+                }
+
+                writer.flush().context("failed to flush recording")?;
+
+                Ok(())
+            }
+        });
+
+        Ok(Recorder {
+            path,
+            frames,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of frames written so far
+    pub fn frames(&self) -> u64 {
+        self.frames.load(Ordering::Acquire)
+    }
+
+    pub fn stop(mut self) -> Result<()> {
+        self.stop_inner()
+    }
+
+    fn stop_inner(&mut self) -> Result<()> {
+        let Some(handle) = self.handle.take()
+        else { return Ok(()); };
+
+        self.stop.store(true, Ordering::Release);
+
+        match handle.join() {
+            Ok(result) => result,
+            Err(_) => Err(anyhow::anyhow!("recording thread panicked")),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Err(err) = self.stop_inner() {
+            log::error!("failed to record '{}': {err:#}", self.path.display());
+        }
+    }
+}
+
+/// A recording opened for replay
+///
+/// Iterating over it reads frames in the order they were recorded.
+pub struct Recording {
+    header: Header,
+    lines: Lines<BufReader<File>>,
+    line: usize,
+}
+
+impl Recording {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        let file = File::open(path)
+            .with_context(|| format!("failed to open file '{}'", path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+
+        let header = lines
+            .next()
+            .context("recording is empty")?
+            .context("failed to read recording header")?;
+
+        let mut header: serde_json::Value =
+            serde_json::from_str(&header).context("failed to parse recording header")?;
+
+        let version = header
+            .get("version")
+            .and_then(|version| version.as_u64())
+            .context("recording header has no version")?;
+
+        if version > FORMAT_VERSION as u64 {
+            anyhow::bail!(
+                "recording version {version} is newer than the supported version {FORMAT_VERSION}"
+            );
+        }
+
+        let config_version = header
+            .get("config_version")
+            .and_then(|version| version.as_u64())
+            .context("recording header has no config version")?;
+
+        if config_version > CONFIG_VERSION {
+            anyhow::bail!(
+                "recording config version {config_version} is newer than the supported version {CONFIG_VERSION}"
+            );
+        }
+
+        if let Some(serde_json::Value::Object(config)) = header.get_mut("config") {
+            migrate_config(config, config_version).context("failed to migrate recording config")?;
+        }
+        header["config_version"] = CONFIG_VERSION.into();
+
+        let header: Header =
+            serde_json::from_value(header).context("failed to deserialize recording header")?;
+
+        Ok(Recording {
+            header,
+            lines,
+            line: 1,
+        })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn info(&self) -> &DeviceInfo {
+        &self.header.info
+    }
+}
+
+impl Iterator for Recording {
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.lines.next()?;
+            self.line += 1;
+
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    return Some(
+                        Err(err).with_context(|| format!("failed to read line {}", self.line)),
+                    )
+                }
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            return Some(
+                serde_json::from_str(&line)
+                    .with_context(|| format!("failed to deserialize frame on line {}", self.line)),
+            );
+        }
+    }
+}
+
+pub fn recording_dir(store: &ConfigStore) -> PathBuf {
+    store.root().join(RECORDING_DIR)
+}
+
+pub fn recording_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{name}.{RECORDING_EXTENSION}"))
+}
+
+pub fn saved_recordings(dir: &Path) -> Result<Vec<String>> {
+    let _ = std::fs::create_dir_all(dir);
+
+    let mut recordings = Vec::new();
+
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to read recording directory '{}'", dir.display()))?;
+
+    for entry in entries {
+        let Ok(entry) = entry
+        else { continue; };
+
+        let path = entry.path();
+
+        let Some(RECORDING_EXTENSION) = path.extension().and_then(|e| e.to_str())
+        else { continue; };
+
+        let Some(name) = path.file_stem().and_then(|s| s.to_str())
+        else { continue; };
+
+        recordings.push(name.to_owned());
+    }
+
+    recordings.sort();
+
+    Ok(recordings)
+}
+
+fn write_line<W: Write, T: Serialize>(writer: &mut W, value: &T) -> Result<()> {
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")?;
+
+    Ok(())
+}
@@ -1 +1,29 @@
+use std::{fs::File, io::Write, path::Path};
+
 pub use uuid::Uuid;
+
+/// Replaces the contents of `path` so that readers see either the old or the new contents,
+/// never a partially written file.
+///
+/// Missing parent directories are created.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_owned();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = File::create(&temp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+
+    if let Err(err) = result.and_then(|()| std::fs::rename(&temp_path, path)) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(err);
+    }
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use parking_lot::Mutex;
-use zinput_engine::{plugin::{Plugin, PluginStatus, PluginKind}, Engine, eframe::{self, egui}, event::{EventKind, Event}};
+use zinput_engine::{plugin::{Plugin, PluginStatus, PluginKind, abi::Registrar}, Engine, eframe::{self, egui}, event::{EventKind, Event}};
 
 mod device_builder;
 mod vdevice;
@@ -9,10 +9,24 @@ mod vdevice;
 use self::device_builder::DeviceBuilder;
 use self::vdevice::VDevice;
 
+zinput_engine::export_plugin!(register);
+
+fn register(registrar: &mut Registrar) {
+    registrar.add_plugin(Arc::new(VConPlugin::new()));
+}
+
 pub struct VConPlugin {
     state: Mutex<State>,
 }
 
+impl VConPlugin {
+    pub fn new() -> Self {
+        VConPlugin {
+            state: Mutex::new(State::Uninit),
+        }
+    }
+}
+
 impl Plugin for VConPlugin {
     fn init(&self, engine: Arc<Engine>) {
         *self.state.lock() = State::init(engine);